@@ -0,0 +1,54 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Times the runs of a `DFA` and of its `ByteDFA` on the same input:
+//!
+//! ```text
+//! cargo run --release --example byte_dfa
+//! ```
+
+extern crate automaton;
+
+use std::time::Instant;
+
+use automaton::dfa::core::*;
+use automaton::dfa::byte::ByteDFA;
+
+const INPUT_LENGTH : usize = 1 << 20;
+const RUNS : u32 = 20;
+
+fn main() {
+    let alphabet : Vec<char> = (b'a'..=b'z').map(char::from).collect();
+    let dfa = DFA::contains_substring("needle", &alphabet);
+    // can't fail because the symbols are ASCII letters
+    let bytes = ByteDFA::from_dfa(&dfa).unwrap();
+    // pseudo-random letters from a linear congruential generator, the needle
+    // being at the end so that the whole input is read
+    let mut seed : u32 = 1;
+    let mut input : String = (0..INPUT_LENGTH)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            alphabet[(seed >> 16) as usize % alphabet.len()]
+        })
+        .collect();
+    input.push_str("needle");
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        assert!(dfa.test(&input));
+    }
+    let hashed = start.elapsed() / RUNS;
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        assert!(bytes.test(input.as_bytes()));
+    }
+    let dense = start.elapsed() / RUNS;
+    println!("input: {} bytes, {} runs", input.len(), RUNS);
+    println!("DFA:     {:?} per run", hashed);
+    println!("ByteDFA: {:?} per run", dense);
+}
@@ -0,0 +1,121 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dfa::core::DFA;
+use nfa::core::NFA;
+use e_nfa::core::ENFA;
+
+/// `Acceptor` is the trait shared by the automata (`DFA`, `NFA` and `ENFA`)
+/// which can tell if a word belongs to their language. It allows to handle
+/// automata of different types through trait objects.
+pub trait Acceptor {
+    /// Returns `true` if `input` is a word of the language of the automaton.
+    fn accepts(&self, input: &str) -> bool;
+}
+
+impl Acceptor for DFA {
+    fn accepts(&self, input: &str) -> bool {
+        self.test(input)
+    }
+}
+
+impl Acceptor for NFA {
+    fn accepts(&self, input: &str) -> bool {
+        self.test(input)
+    }
+}
+
+impl Acceptor for ENFA {
+    fn accepts(&self, input: &str) -> bool {
+        self.test(input)
+    }
+}
+
+/// Returns the indices, in increasing order, of the automata of `acceptors`
+/// accepting `input`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::acceptor::*;
+/// use automaton::dfa::core::*;
+/// use automaton::nfa::core::*;
+///
+/// fn main() {
+///     let keyword = DFA::from_words(&["if", "else"]);
+///     // [a-z]+
+///     let mut identifier = NFABuilder::new().add_start(0).add_final(1);
+///     for symb in "abcdefghijklmnopqrstuvwxyz".chars() {
+///         identifier = identifier
+///             .add_transition(symb, 0, 1)
+///             .add_transition(symb, 1, 1);
+///     }
+///     let identifier = identifier.finalize().unwrap();
+///     let acceptors : Vec<&Acceptor> = vec![&keyword, &identifier];
+///     assert_eq!(classify(&acceptors, "if"), vec![0, 1]);
+///     assert_eq!(classify(&acceptors, "iff"), vec![1]);
+///     assert!(classify(&acceptors, "42").is_empty());
+/// }
+/// ```
+pub fn classify(acceptors: &[&Acceptor], input: &str) -> Vec<usize> {
+    acceptors
+        .iter()
+        .enumerate()
+        .filter(|&(_,acceptor)| acceptor.accepts(input))
+        .map(|(i,_)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::*;
+    use nfa::core::*;
+    use e_nfa::core::*;
+
+    #[test]
+    fn test_classify() {
+        // ab*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        // (a|b)*b
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('b', 0, 1)
+            .finalize()
+            .unwrap();
+        // c(ab)*
+        let e_nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('c', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 2, 3)
+            .add_e_transition(3, 1)
+            .finalize()
+            .unwrap();
+        let acceptors : Vec<&Acceptor> = vec![&dfa, &nfa, &e_nfa];
+        assert_eq!(classify(&acceptors, "abb"), vec![0, 1]);
+        assert_eq!(classify(&acceptors, "a"), vec![0]);
+        assert_eq!(classify(&acceptors, "abab"), vec![1]);
+        assert_eq!(classify(&acceptors, "cab"), vec![2]);
+        assert_eq!(classify(&acceptors, "c"), vec![2]);
+        assert!(classify(&acceptors, "ba").is_empty());
+        assert!(classify(&[], "ab").is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Binary serialization of a `DFA`, enabled by the `binary` feature.
+//!
+//! The format starts with a header made of the magic bytes `DFA\0` followed by
+//! the version of the format on one byte. The body of the version 1 contains,
+//! as little-endian integers:
+//!
+//! * the starting state (`u64`);
+//! * the number of final states (`u64`) followed by the final states (`u64`);
+//! * the number of transitions (`u64`) followed by the transitions, each one
+//!   being a symbol (`u32`), a source state (`u64`) and a destination state
+//!   (`u64`).
+//!
+//! The final states and the transitions are sorted so the encoding of a DFA
+//! is deterministic.
+
+use std::collections::{HashSet,HashMap};
+use std::fmt;
+use std::error;
+use std::result;
+use std::char;
+
+use dfa::core::DFA;
+
+const MAGIC : &[u8] = b"DFA\0";
+const VERSION : u8 = 1;
+
+/// The `BinaryError` type.
+#[derive(Debug,PartialEq,Eq)]
+pub enum BinaryError {
+    /// The input does not start with the magic bytes.
+    InvalidHeader,
+    /// The version `u8` of the format is not supported.
+    UnsupportedVersion(u8),
+    /// The input ends in the middle of the DFA.
+    UnexpectedEnd,
+    /// The value `u32` is not a valid symbol.
+    InvalidSymbol(u32),
+    /// A state does not fit in a `usize`.
+    InvalidState(u64),
+    /// The transition from state `usize` with symbol `char` is defined twice.
+    DuplicatedTransition(char,usize),
+    /// Some bytes remain after the DFA.
+    TrailingBytes,
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BinaryError::InvalidHeader => write!(f, "Invalid header."),
+            BinaryError::UnsupportedVersion(version) => write!(f, "Unsupported version {}.", version),
+            BinaryError::UnexpectedEnd => write!(f, "Unexpected end of input."),
+            BinaryError::InvalidSymbol(symb) => write!(f, "Invalid symbol {:#x}.", symb),
+            BinaryError::InvalidState(state) => write!(f, "Invalid state {}.", state),
+            BinaryError::DuplicatedTransition(symb,state) => write!(f, "Duplicated transition ('{}',{}).", symb, state),
+            BinaryError::TrailingBytes => write!(f, "Trailing bytes after the DFA."),
+        }
+    }
+}
+
+impl error::Error for BinaryError {
+    fn description(&self) -> &str {
+        match *self {
+            BinaryError::InvalidHeader => "Invalid header.",
+            BinaryError::UnsupportedVersion(_) => "Unsupported version.",
+            BinaryError::UnexpectedEnd => "Unexpected end of input.",
+            BinaryError::InvalidSymbol(_) => "Invalid symbol.",
+            BinaryError::InvalidState(_) => "Invalid state.",
+            BinaryError::DuplicatedTransition(_,_) => "Duplicated transition.",
+            BinaryError::TrailingBytes => "Trailing bytes after the DFA.",
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        None
+    }
+}
+
+/// Result type of the binary decoding.
+pub type Result<T> = result::Result<T,BinaryError>;
+
+struct Decoder<'a> {
+    bytes : &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < n {
+            return Err(BinaryError::UnexpectedEnd);
+        }
+        let (head,tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = try!(self.take(4));
+        Ok(bytes.iter().rev().fold(0, |acc,&b| (acc << 8) | b as u32))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let bytes = try!(self.take(8));
+        Ok(bytes.iter().rev().fold(0, |acc,&b| (acc << 8) | b as u64))
+    }
+
+    fn state(&mut self) -> Result<usize> {
+        let state = try!(self.u64());
+        if state > usize::MAX as u64 {
+            return Err(BinaryError::InvalidState(state));
+        }
+        Ok(state as usize)
+    }
+
+    fn symbol(&mut self) -> Result<char> {
+        let symb = try!(self.u32());
+        char::from_u32(symb).ok_or(BinaryError::InvalidSymbol(symb))
+    }
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend((0..4).map(|i| (value >> (8*i)) as u8));
+}
+
+fn push_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend((0..8).map(|i| (value >> (8*i)) as u8));
+}
+
+impl DFA {
+    /// Encodes the DFA in the binary format (see the `binary` module).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let bytes = dfa.to_bytes();
+    ///     assert_eq!(DFA::from_bytes(&bytes).unwrap(), dfa);
+    /// }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let transitions = self.transitions();
+        let mut finals : Vec<_> = self.finals().iter().cloned().collect();
+        finals.sort();
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + 8*(3 + finals.len()) + 20*transitions.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        push_u64(&mut bytes, self.start() as u64);
+        push_u64(&mut bytes, finals.len() as u64);
+        for state in finals {
+            push_u64(&mut bytes, state as u64);
+        }
+        push_u64(&mut bytes, transitions.len() as u64);
+        for (symb,src,dest) in transitions {
+            push_u32(&mut bytes, symb as u32);
+            push_u64(&mut bytes, src as u64);
+            push_u64(&mut bytes, dest as u64);
+        }
+        bytes
+    }
+
+    /// Decodes a DFA encoded with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<DFA> {
+        let mut decoder = Decoder{bytes};
+        if try!(decoder.take(MAGIC.len()).map_err(|_| BinaryError::InvalidHeader)) != MAGIC {
+            return Err(BinaryError::InvalidHeader);
+        }
+        let version = try!(decoder.take(1))[0];
+        if version != VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+        let start = try!(decoder.state());
+        let final_count = try!(decoder.u64());
+        let mut finals = HashSet::new();
+        for _ in 0..final_count {
+            finals.insert(try!(decoder.state()));
+        }
+        let transition_count = try!(decoder.u64());
+        let mut transitions = HashMap::new();
+        for _ in 0..transition_count {
+            let symb = try!(decoder.symbol());
+            let src = try!(decoder.state());
+            let dest = try!(decoder.state());
+            if transitions.insert((symb,src), dest).is_some() {
+                return Err(BinaryError::DuplicatedTransition(symb,src));
+            }
+        }
+        if !decoder.bytes.is_empty() {
+            return Err(BinaryError::TrailingBytes);
+        }
+        Ok(DFA::from_parts(transitions, start, finals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::*;
+
+    #[test]
+    fn test_binary_round_trip() {
+        // counts the 'a' modulo 1000 over the alphabet {a,b,c,é}
+        let mut dfa = DFABuilder::new().add_start(0).add_final(0).add_final(500);
+        for state in 0..1000 {
+            dfa = dfa
+                .add_transition('a', state, (state+1) % 1000)
+                .add_transition('b', state, state)
+                .add_transition('c', state, state)
+                .add_transition('é', state, state);
+        }
+        let dfa = dfa.finalize().unwrap();
+        let bytes = dfa.to_bytes();
+        assert_eq!(bytes, dfa.to_bytes());
+        assert_eq!(DFA::from_bytes(&bytes).unwrap(), dfa);
+    }
+
+    #[test]
+    fn test_binary_errors() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        let bytes = dfa.to_bytes();
+        assert_eq!(DFA::from_bytes(b"NFA\0\x01"), Err(BinaryError::InvalidHeader));
+        assert_eq!(DFA::from_bytes(b"DF"), Err(BinaryError::InvalidHeader));
+        assert_eq!(DFA::from_bytes(b"DFA\0\x02"), Err(BinaryError::UnsupportedVersion(2)));
+        assert_eq!(DFA::from_bytes(&bytes[..bytes.len()-1]), Err(BinaryError::UnexpectedEnd));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(DFA::from_bytes(&trailing), Err(BinaryError::TrailingBytes));
+        let mut invalid = bytes.clone();
+        let symbol = bytes.len() - 20;
+        invalid[symbol..symbol+4].copy_from_slice(&[0x00, 0xd8, 0x00, 0x00]);
+        assert_eq!(DFA::from_bytes(&invalid), Err(BinaryError::InvalidSymbol(0xd800)));
+    }
+}
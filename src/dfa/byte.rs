@@ -0,0 +1,238 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::result;
+
+use dfa::core::{DFA,DFAError};
+
+// the destination of a missing transition
+const MISSING : u32 = u32::MAX;
+
+/// The bound of the states of a `ByteDFA`, which caps its transition table to
+/// 1 GiB.
+pub const MAX_STATES : usize = 1 << 20;
+
+/// The type `ByteDFA` is a DFA over the bytes whose transitions are stored in a
+/// dense table: each state has a row of 256 destinations indexed by the byte.
+/// A run only performs an array access per byte, which is much faster than the
+/// hash table of a `DFA` at the cost of 1 KiB per state. The table grows as the
+/// states are used, the states being numbered from 0 by the caller: the table
+/// has a row for each state up to the greatest one used, so a large state
+/// allocates a large table even if the lower states are unused.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa::byte::*;
+///
+/// fn main() {
+///     // the frames starting with the magic bytes 0xCA 0xFE
+///     let mut dfa = ByteDFA::new(0);
+///     dfa.add_transition(0xca, 0, 1).unwrap();
+///     dfa.add_transition(0xfe, 1, 2).unwrap();
+///     for byte in 0..=255 {
+///         dfa.add_transition(byte, 2, 2).unwrap();
+///     }
+///     dfa.add_final(2);
+///     assert!(dfa.test(&[0xca, 0xfe, 0x00, 0xff]));
+///     assert!(!dfa.test(&[0xfe, 0xca]));
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ByteDFA {
+    transitions : Vec<[u32;256]>,
+    start       : usize,
+    finals      : Vec<bool>,
+}
+
+impl ByteDFA {
+    /// Creates a DFA without transitions nor final states whose starting state
+    /// is `start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is not below `MAX_STATES`.
+    pub fn new(start: usize) -> ByteDFA {
+        let mut dfa = ByteDFA{transitions: Vec::new(), start, finals: Vec::new()};
+        dfa.grow(start);
+        dfa
+    }
+
+    /// Creates the dense representation of a DFA whose symbols are all in the
+    /// range `U+0000..=U+00FF`, each symbol being read as the byte of the same
+    /// value. The states are renumbered from 0, the starting state first.
+    /// Returns `None` if a symbol is above `U+00FF` or if the DFA has more than
+    /// `MAX_STATES` states.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    /// use automaton::dfa::byte::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["GET", "PUT"]);
+    ///     let bytes = ByteDFA::from_dfa(&dfa).unwrap();
+    ///     assert!(bytes.test(b"PUT"));
+    ///     assert!(!bytes.test(b"POST"));
+    ///     assert!(ByteDFA::from_dfa(&DFA::from_words(&["é", "ŵ"])).is_none());
+    /// }
+    /// ```
+    pub fn from_dfa(dfa: &DFA) -> Option<ByteDFA> {
+        let mut ids = HashMap::new();
+        ids.insert(dfa.start(), 0);
+        let mut id = |state: usize| {
+            let next = ids.len();
+            *ids.entry(state).or_insert(next)
+        };
+        let mut bytes = ByteDFA::new(0);
+        for (symb,src,dest) in dfa.transitions() {
+            if symb as u32 > 0xff {
+                return None;
+            }
+            let (src,dest) = (id(src),id(dest));
+            if src.max(dest) >= MAX_STATES {
+                return None;
+            }
+            // can't fail because the transitions of a DFA are deterministic
+            bytes.add_transition(symb as u8, src, dest).unwrap();
+        }
+        for &state in dfa.finals() {
+            let state = id(state);
+            bytes.add_final(state);
+        }
+        Some(bytes)
+    }
+
+    /// Adds a transition from `src` to `dest` on `byte`.
+    ///
+    /// # Errors
+    ///
+    /// Return a `DFAError::DuplicatedTransition`, the byte being converted to a
+    /// symbol, if a transition from `src` on `byte` already leads to another
+    /// state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a state is not below `MAX_STATES`.
+    pub fn add_transition(&mut self, byte: u8, src: usize, dest: usize) -> result::Result<(),DFAError> {
+        self.grow(src.max(dest));
+        let existing = self.transitions[src][byte as usize];
+        if existing != MISSING && existing as usize != dest {
+            return Err(DFAError::DuplicatedTransition{symb: byte as char, src, existing: existing as usize, attempted: dest});
+        }
+        self.transitions[src][byte as usize] = dest as u32;
+        Ok(())
+    }
+
+    /// Makes `state` a final state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state` is not below `MAX_STATES`.
+    pub fn add_final(&mut self, state: usize) {
+        self.grow(state);
+        self.finals[state] = true;
+    }
+
+    /// Returns the number of rows of the transition table, i.e. one more than
+    /// the greatest state used.
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Test if an input is a word of the language defined by the DFA.
+    pub fn test(&self, input: &[u8]) -> bool {
+        let mut state = self.start;
+        for &byte in input {
+            match self.transitions[state][byte as usize] {
+                MISSING => return false,
+                next => state = next as usize,
+            }
+        }
+        self.finals[state]
+    }
+
+    fn grow(&mut self, state: usize) {
+        assert!(state < MAX_STATES, "state out of range");
+        if state >= self.transitions.len() {
+            self.transitions.resize(state+1, [MISSING;256]);
+            self.finals.resize(state+1, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::{DFABuilder,DFABuilding};
+
+    #[test]
+    fn test_byte_dfa() {
+        // a length-prefixed frame: 0x01 then one byte, or 0x02 then two bytes
+        let mut dfa = ByteDFA::new(0);
+        dfa.add_transition(0x01, 0, 2).unwrap();
+        dfa.add_transition(0x02, 0, 1).unwrap();
+        for byte in 0..=255 {
+            dfa.add_transition(byte, 1, 2).unwrap();
+            dfa.add_transition(byte, 2, 3).unwrap();
+        }
+        dfa.add_final(3);
+        assert_eq!(dfa.state_count(), 4);
+        let samples : &[(&[u8],bool)] =
+            &[(&[0x01, 0xff], true),
+              (&[0x02, 0x00, 0x80], true),
+              (&[0x02, 0x00], false),
+              (&[0x01, 0x00, 0x00], false),
+              (&[0x03, 0x00], false),
+              (&[], false)];
+        for &(input,expected_result) in samples {
+            assert!(dfa.test(input) == expected_result, "input false for: {:?}", input);
+        }
+        // the same transition can be added twice but not redirected
+        assert!(dfa.add_transition(0x01, 0, 2).is_ok());
+        match dfa.add_transition(0x01, 0, 3) {
+            Err(DFAError::DuplicatedTransition{symb, src: 0, existing: 2, attempted: 3}) => assert_eq!(symb, '\u{1}'),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_byte_dfa_from_dfa() {
+        // (abc)* with a transition on a symbol from the Latin-1 range
+        let dfa = DFABuilder::new()
+            .add_start(7)
+            .add_final(7)
+            .add_transition('a', 7, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 7)
+            .add_transition('é', 7, 7)
+            .finalize()
+            .unwrap();
+        let bytes = ByteDFA::from_dfa(&dfa).unwrap();
+        assert_eq!(bytes.state_count(), 3);
+        for input in &["", "abc", "abcabc", "ab", "abca", "cab"] {
+            assert_eq!(bytes.test(input.as_bytes()), dfa.test(input), "input: {}", input);
+        }
+        assert!(bytes.test(&[0xe9, b'a', b'b', b'c']));
+        assert!(!bytes.test("é".as_bytes()));
+        assert!(ByteDFA::from_dfa(&DFA::from_words(&["ab", "\u{100}"])).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "state out of range")]
+    fn test_byte_dfa_state_out_of_range() {
+        let mut dfa = ByteDFA::new(0);
+        let _ = dfa.add_transition(b'a', 0, MAX_STATES);
+    }
+}
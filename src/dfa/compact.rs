@@ -0,0 +1,191 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashSet,HashMap};
+
+use dfa::core::DFA;
+
+/// The type `CompactDFA` is a read-only representation of a `DFA` where each
+/// non-branching chain of transitions is stored as a single edge labelled with
+/// the string of the chain.
+///
+/// A state is kept if it is the starting state, a final state, or if it does
+/// not have exactly one incoming and one outgoing transition. The other states
+/// are only crossed by a single path and are merged into the edges between the
+/// kept states. Their labels are expanded on demand during `test`. This saves a
+/// lot of memory on automata with long linear tails, such as word lists.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa::core::*;
+///
+/// fn main() {
+///     // then
+///     let dfa = DFABuilder::new()
+///         .add_start(0)
+///         .add_final(4)
+///         .add_transition('t', 0, 1)
+///         .add_transition('h', 1, 2)
+///         .add_transition('e', 2, 3)
+///         .add_transition('n', 3, 4)
+///         .finalize()
+///         .unwrap();
+///     let compact = dfa.compact();
+///     assert_eq!(compact.edge_count(), 1);
+///     assert!(compact.test("then"));
+///     assert!(!compact.test("the"));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CompactDFA {
+    // the first symbol of an edge and its source state are mapped to the
+    // remaining symbols of the label and to the destination state.
+    edges  : HashMap<(char,usize),(String,usize)>,
+    start  : usize,
+    finals : HashSet<usize>,
+}
+
+impl CompactDFA {
+    /// Creates the compact representation of a DFA.
+    pub fn new(dfa: &DFA) -> CompactDFA {
+        let transitions = dfa.transitions();
+        let mut in_degree = HashMap::new();
+        let mut out_degree = HashMap::new();
+        let mut next = HashMap::new();
+        for &(symb,src,dest) in transitions.iter() {
+            *in_degree.entry(dest).or_insert(0) += 1;
+            *out_degree.entry(src).or_insert(0) += 1;
+            next.insert(src, (symb,dest));
+        }
+        let is_kept = |state: usize| {
+            state == dfa.start()
+                || dfa.finals().contains(&state)
+                || in_degree.get(&state) != Some(&1)
+                || out_degree.get(&state) != Some(&1)
+        };
+        let mut edges = HashMap::new();
+        for &(symb,src,dest) in transitions.iter().filter(|&&(_,src,_)| is_kept(src)) {
+            let mut label = String::new();
+            let mut dest = dest;
+            // A merged state has a single predecessor so the chain can not loop
+            // back on itself before reaching a kept state.
+            while !is_kept(dest) {
+                let (c,d) = next[&dest];
+                label.push(c);
+                dest = d;
+            }
+            edges.insert((symb,src), (label,dest));
+        }
+        CompactDFA{edges, start: dfa.start(), finals: dfa.finals().clone()}
+    }
+
+    /// Test if an input string is a word of the language defined by the DFA.
+    pub fn test(&self, input: &str) -> bool {
+        let mut input = input.chars();
+        let mut state = self.start;
+        while let Some(c) = input.next() {
+            match self.edges.get(&(c,state)) {
+                Some(&(ref label,dest)) => {
+                    for expected in label.chars() {
+                        if input.next() != Some(expected) {
+                            return false;
+                        }
+                    }
+                    state = dest;
+                },
+                None => return false,
+            }
+        }
+        self.finals.contains(&state)
+    }
+
+    /// Returns the number of edges stored.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+impl<'a> From<&'a DFA> for CompactDFA {
+    fn from(dfa: &'a DFA) -> CompactDFA {
+        CompactDFA::new(dfa)
+    }
+}
+
+impl DFA {
+    /// Returns the compact representation of the DFA (see `CompactDFA`).
+    pub fn compact(&self) -> CompactDFA {
+        CompactDFA::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dfa::core::*;
+
+    fn words_dfa(words: &[&str]) -> DFA {
+        // trie of the words
+        let mut dfa = DFABuilder::new().add_start(0);
+        let mut next = 1;
+        let mut trie = ::std::collections::HashMap::new();
+        for word in words {
+            let mut state = 0;
+            for c in word.chars() {
+                state = match trie.get(&(c,state)) {
+                    Some(&dest) => dest,
+                    None => {
+                        trie.insert((c,state), next);
+                        dfa = dfa.add_transition(c, state, next);
+                        next += 1;
+                        next-1
+                    },
+                };
+            }
+            dfa = dfa.add_final(state);
+        }
+        dfa.finalize().unwrap()
+    }
+
+    #[test]
+    fn test_compact_long_word() {
+        let word = "abcdefghijklmnopqrstuvwxyz".repeat(10);
+        let dfa = words_dfa(&[&word, "abd", "ab"]);
+        let compact = dfa.compact();
+        // "ab" up to the final state of "ab", then one chain per longer word
+        assert_eq!(compact.edge_count(), 3);
+        assert!(compact.edge_count() < dfa.transitions().len() / 50);
+        let samples = vec![word.as_str(), "abd", "ab", "a", "", "abc", "abdd", &word[..word.len()-1]];
+        for input in samples {
+            assert!(compact.test(input) == dfa.test(input), "input false for: \"{}\"", input);
+        }
+        let longer = format!("{}a", word);
+        assert!(!compact.test(&longer));
+    }
+
+    #[test]
+    fn test_compact_cycle() {
+        // (ab)*c
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let compact = dfa.compact();
+        let samples = vec!["ababac", "ababc", "", "abc", "c", "ac", "ab", "ababababababababababababababababababababc"];
+        for input in samples {
+            assert!(compact.test(input) == dfa.test(input), "input false for: \"{}\"", input);
+        }
+    }
+}
@@ -10,20 +10,42 @@
 extern crate itertools;
 
 
-use std::collections::{HashSet,HashMap};
+use std::collections::{HashSet,HashMap,BTreeMap,BinaryHeap};
+use std::cmp::Reverse;
 use std::fmt;                          // Formatter, format!, Display, Debug, write!
 use std::error;
 use std::result;
+use std::mem;
+use std::cell::OnceCell;
+use std::io::{self,Write};
+
+use nfa::core::{NFA,NFABuilder,NFABuilding};
+use e_nfa::core::{ENFA,ENFABuilder,ENFABuilding};
+use dfa::reader::DFAReader;
+use dot;
+use render::{self,Render};
 
 /// The `DFAError` type.
-#[derive(Debug)]
+#[derive(Debug,PartialEq,Eq)]
 pub enum DFAError {
-    /// The transition from state `usize` with symbol `char` is defined twice.
-    DuplicatedTransition(char,usize),
+    /// The transition from state `src` with symbol `symb` is defined twice, the
+    /// first time to the state `existing` and then to the state `attempted`.
+    DuplicatedTransition {
+        /// The symbol of the transition.
+        symb      : char,
+        /// The source state of the transition.
+        src       : usize,
+        /// The destination state already defined.
+        existing  : usize,
+        /// The destination state of the rejected transition.
+        attempted : usize,
+    },
     /// No final state is specified.
     MissingFinalStates,
     /// No starting state is specified.
     MissingStartingState,
+    /// The state `usize` already exists and can not be used as a new state.
+    ExistingState(usize),
 }
 
 
@@ -31,9 +53,13 @@ pub enum DFAError {
 impl fmt::Display for DFAError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            DFAError::DuplicatedTransition(symb,state) => write!(f, "Duplicated transition ('{}',{}).", symb, state),
+            DFAError::DuplicatedTransition{symb,src,existing,attempted} => {
+                write!(f, "Duplicated transition ('{}',{}): {} already defined, {} attempted.",
+                       symb, src, existing, attempted)
+            },
             DFAError::MissingFinalStates => write!(f, "Missing final states."),
             DFAError::MissingStartingState => write!(f, "Missing starting state."),
+            DFAError::ExistingState(state) => write!(f, "The state {} already exists.", state),
         }
     }
 }
@@ -41,26 +67,257 @@ impl fmt::Display for DFAError {
 impl error::Error for DFAError {
     fn description(&self) -> &str {
         match *self {
-            DFAError::DuplicatedTransition(_,_) => "Duplicated transition.", 
+            DFAError::DuplicatedTransition{..} => "Duplicated transition.", 
             DFAError::MissingFinalStates => "Missing final states.",
             DFAError::MissingStartingState => "Missing starting state.",
+            DFAError::ExistingState(_) => "Existing state.",
+        }
+    }
+
+
+    fn cause(&self) -> Option<&error::Error> {
+        None
+    }
+}
+
+/// The `EnumError` type, returned when the enumeration of the words of a DFA is
+/// aborted.
+#[derive(Debug,PartialEq,Eq)]
+pub enum EnumError {
+    /// The BFS frontier reached the size `usize`, which exceeds the allowed
+    /// maximum.
+    FrontierExceeded(usize),
+    /// The symbol `char` of the alphabet of the DFA is missing from the order
+    /// of the symbols.
+    MissingSymbol(char),
+}
+
+impl fmt::Display for EnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EnumError::FrontierExceeded(size) => write!(f, "Frontier exceeded ({} words).", size),
+            EnumError::MissingSymbol(symb) => write!(f, "The symbol {:?} is missing from the order.", symb),
         }
     }
+}
 
+impl error::Error for EnumError {
+    fn description(&self) -> &str {
+        match *self {
+            EnumError::FrontierExceeded(_) => "Frontier exceeded.",
+            EnumError::MissingSymbol(_) => "Missing symbol in the order.",
+        }
+    }
 
     fn cause(&self) -> Option<&error::Error> {
         None
     }
 }
 
+/// The `ProductFinality` type tells which states of a `synchronized_product`
+/// are final from the finality of their two components.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ProductFinality {
+    /// Both components are final (intersection).
+    Both,
+    /// At least one component is final (union).
+    Either,
+    /// The first component is final.
+    First,
+    /// The second component is final.
+    Second,
+}
+
 /// The type `DFA` represents a Deterministic Finite Automaton. The transitions
 /// of the automatonn are stored in a hashtable.
+///
+/// The alphabet of a DFA is inferred from the symbols of its transitions, and
+/// extended by the alphabet declared at build time, if any (see
+/// `DFABuilding::declare_alphabet`). The declared alphabet of a DFA built
+/// from other DFAs, by `minimize`, `concat_dfa` or `union_all` for instance, is
+/// the union of the declared alphabets of the operands. `compose_with_map`
+/// renames the declared symbols as it renames the transitions.
 #[derive(Debug)]
 pub struct DFA {
     transitions : HashMap<(char,usize),usize>,
     start       : usize,
     finals      : HashSet<usize>,
+    alphabet    : Option<HashSet<char>>,
+    // the completed form, computed on the first call to `as_complete`
+    complete    : OnceCell<Box<CompleteDFA>>,
+    // the reverse NFA, computed on the first call to `test_reversed`
+    reversed    : OnceCell<NFA>,
+}
+
+// The completed form and the reverse NFA are caches and the declared alphabet
+// does not change the language, so none of them takes part in the equality: the
+// minimal DFAs of two DFAs are equal if and only if the DFAs are equivalent.
+impl PartialEq for DFA {
+    fn eq(&self, other: &DFA) -> bool {
+        self.transitions == other.transitions
+            && self.start == other.start
+            && self.finals == other.finals
+    }
+}
+
+// The caches are not cloned but computed again on demand by the clone.
+impl Clone for DFA {
+    fn clone(&self) -> DFA {
+        DFA::with_alphabet(self.transitions.clone(), self.start, self.finals.clone(), self.alphabet.clone())
+    }
+}
+
+impl Eq for DFA {}
+
+/// The type `CompleteDFA` is a DFA completed over its alphabet: each state has a
+/// transition for each symbol, the missing transitions leading to a trap state
+/// (see `DFA::as_complete`).
+#[derive(Debug,PartialEq,Eq)]
+pub struct CompleteDFA {
+    dfa  : DFA,
+    trap : Option<usize>,
+}
+
+impl CompleteDFA {
+    /// Returns the completed DFA.
+    pub fn dfa(&self) -> &DFA {
+        &self.dfa
+    }
+
+    /// Returns the trap state added by the completion, or `None` if the DFA
+    /// was already complete.
+    pub fn trap(&self) -> Option<usize> {
+        self.trap
+    }
+
+    /// Returns a DFA accepting the words over the alphabet of the completed DFA
+    /// that are not accepted by it.
+    pub fn complement(&self) -> DFA {
+        let finals = self.dfa.states().difference(&self.dfa.finals).cloned().collect();
+        DFA::with_alphabet(self.dfa.transitions.clone(), self.dfa.start, finals, self.dfa.alphabet.clone())
+    }
+}
+
+/// The type `SyntacticMonoid` is the transition monoid of a minimal complete DFA
+/// (see `DFA::syntactic_monoid`). Its states are numbered from 0 and each
+/// element is a transformation mapping a state to its image.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct SyntacticMonoid {
+    elements : Vec<Vec<usize>>,
+}
+
+impl SyntacticMonoid {
+    /// Returns the elements of the monoid, the first one being the identity.
+    pub fn elements(&self) -> &[Vec<usize>] {
+        &self.elements
+    }
+
+    /// Returns the number of elements of the monoid.
+    pub fn size(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the monoid is a group, i.e. if each of its
+    /// transformations is a permutation of the states.
+    pub fn is_group(&self) -> bool {
+        self.elements.iter().all(|f| {
+            let images : HashSet<_> = f.iter().collect();
+            images.len() == f.len()
+        })
+    }
+
+    /// Returns `true` if the monoid is aperiodic, i.e. if each transformation
+    /// `f` satisfies `f^n = f^(n+1)` for some `n`. The number of states is a
+    /// valid `n` for all of them.
+    pub fn is_aperiodic(&self) -> bool {
+        let size = self.elements[0].len();
+        self.elements.iter().all(|f| {
+            let mut power = f.clone();
+            for _ in 1..size {
+                power = power.iter().map(|&state| f[state]).collect();
+            }
+            let next : Vec<_> = power.iter().map(|&state| f[state]).collect();
+            power == next
+        })
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    // the number of completions computed by `as_complete` on the current thread
+    static COMPLETIONS : ::std::cell::Cell<usize> = const { ::std::cell::Cell::new(0) };
+}
+
+/// The type `DFAStats` summarizes the size of a `DFA`.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct DFAStats {
+    /// Number of states.
+    pub state_count      : usize,
+    /// Number of transitions.
+    pub transition_count : usize,
+    /// Number of final states.
+    pub final_count      : usize,
+    /// Number of symbols used by the transitions.
+    pub alphabet_size    : usize,
+    /// Whether each state has a transition for each symbol of the alphabet.
+    pub is_complete      : bool,
+}
+
+impl fmt::Display for DFAStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "states: {}, transitions: {}, finals: {}, alphabet: {}, complete: {}",
+               self.state_count, self.transition_count, self.final_count,
+               self.alphabet_size, self.is_complete)
+    }
+}
+
+/// The type `AutomatonDiff` describes the structural differences between two
+/// DFAs (see `DFA::diff`). The states of the first DFA are used to report its
+/// transitions and final states, and the states of the second DFA to report its
+/// own. The starting states are always aligned, so two DFAs differing only by
+/// the numbering of their states have no difference.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct AutomatonDiff {
+    /// Transitions `(symb,src,dest)` of the first DFA missing from the second.
+    pub removed_transitions : Vec<(char,usize,usize)>,
+    /// Transitions `(symb,src,dest)` of the second DFA missing from the first.
+    pub added_transitions   : Vec<(char,usize,usize)>,
+    /// Final states of the first DFA aligned with non-final states of the
+    /// second, or not aligned at all.
+    pub removed_finals      : Vec<usize>,
+    /// Final states of the second DFA aligned with non-final states of the
+    /// first, or not aligned at all.
+    pub added_finals        : Vec<usize>,
 }
+
+impl AutomatonDiff {
+    /// Returns `true` if no difference is reported.
+    pub fn is_empty(&self) -> bool {
+        self.removed_transitions.is_empty()
+            && self.added_transitions.is_empty()
+            && self.removed_finals.is_empty()
+            && self.added_finals.is_empty()
+    }
+}
+
+impl fmt::Display for AutomatonDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(symb,src,dest) in self.removed_transitions.iter() {
+            try!(writeln!(f, "- ({},{}) => {}", symb, src, dest));
+        }
+        for &(symb,src,dest) in self.added_transitions.iter() {
+            try!(writeln!(f, "+ ({},{}) => {}", symb, src, dest));
+        }
+        for state in self.removed_finals.iter() {
+            try!(writeln!(f, "- FINAL {}", state));
+        }
+        for state in self.added_finals.iter() {
+            try!(writeln!(f, "+ FINAL {}", state));
+        }
+        write!(f, "")
+    }
+}
+
 /*
 /// a
 struct SrcState( u64 );
@@ -131,7 +388,7 @@ struct Symbol( char );
 ///         .add_transition('t', 0, 2)
 ///         .finalize();
 ///     match dfa {
-///         Err(DFAError::DuplicatedTransition(symb,src)) => assert!((symb,src)==('t',0)),
+///         Err(DFAError::DuplicatedTransition{symb,src,..}) => assert!((symb,src)==('t',0)),
 ///         _ => assert!(false),
 ///     }
 /// }
@@ -155,12 +412,13 @@ struct Symbol( char );
 /// }
 /// ```
 ///
-#[derive(Debug)]
+#[derive(Debug,Clone)]
 #[must_use="builders need to be built with .finalize() method"]
 pub struct DFABuilder {
     transitions : HashMap<(char,usize),usize>,
     start       : Option<usize>,
     finals      : HashSet<usize>,
+    alphabet    : Option<HashSet<char>>,
 }
 
 
@@ -196,11 +454,34 @@ pub trait DFABuilding {
     ///
     /// # Errors
     ///
-    /// Return a DFAError::DuplicatedTransition(symb,src) if a transtion
-    /// with the same symb and src has already been inserted, even if
+    /// Return a DFAError::DuplicatedTransition{symb,src,existing,attempted} if a
+    /// transtion with the same symb and src has already been inserted, even if
     /// the destination state is the same.
     fn add_transition(self, symb: char, src: usize, dest: usize) -> Result<Self::Builder>;
 
+    /// Add a transition to the DFA, replacing the transition with the same symb
+    /// and src if it has already been inserted.
+    fn set_transition(self, symb: char, src: usize, dest: usize) -> Result<Self::Builder>;
+
+    /// Remove the transition from `src` with symbol `symb` from the DFA. Nothing
+    /// is done if the transition does not exist.
+    fn remove_transition(self, symb: char, src: usize) -> Result<Self::Builder>;
+
+    /// Declare symbols of the alphabet of the DFA, even if no transition uses
+    /// them. The operations completing the DFA, such as `complement`, take them
+    /// into account. Declaring the alphabet several times adds up the symbols.
+    ///
+    /// The method is provided for the builders convertible to a
+    /// `Result<DFABuilder>`, so that the other builders don't have to implement
+    /// it.
+    fn declare_alphabet(self, syms: &[char]) -> Result<Self::Builder>
+        where Self: Into<Result<DFABuilder>>, Self::Builder: From<DFABuilder> {
+        self.into().map(|mut dfa| {
+            dfa.alphabet.get_or_insert_with(HashSet::new).extend(syms.iter().cloned());
+            dfa.into()
+        })
+    }
+
     /// Finalize the building of the DFA.
     ///
     /// # Errors
@@ -209,12 +490,140 @@ pub trait DFABuilding {
     ///
     /// Return a DFAError::MissingFinalStates if no final state is specified.
     fn finalize(self) -> Result<Self::Type>;
+
+    /// Finalize the building of the DFA, allowing it to have no final state.
+    /// Such a DFA accepts the empty language.
+    ///
+    /// # Errors
+    ///
+    /// Return a DFAError::MissingStartingState if no starting state is specified.
+    ///
+    /// The method is provided for the builders convertible to a
+    /// `Result<DFABuilder>` and building a `DFA`.
+    fn finalize_allow_empty(self) -> Result<Self::Type>
+        where Self: Into<Result<DFABuilder>>, Self::Type: From<DFA> {
+        self.into().and_then(|dfa| {
+            match dfa.start {
+                Some(start) => Ok(DFA::with_alphabet(dfa.transitions, start, dfa.finals, dfa.alphabet).into()),
+                None => Err(DFAError::MissingStartingState),
+            }
+        })
+    }
+
+    /// Finalize the building of the DFA, the starting state being made final if
+    /// no final state is specified. This is a convenience for quick experiments:
+    /// instead of failing, the DFA then accepts the empty word and the words
+    /// leading back to the starting state, which is rarely the intended
+    /// language. Prefer `finalize` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Return a DFAError::MissingStartingState if no starting state is specified.
+    ///
+    /// The method is provided for the builders convertible to a
+    /// `Result<DFABuilder>` and building a `DFA`.
+    fn finalize_or_default_final(self) -> Result<Self::Type>
+        where Self: Into<Result<DFABuilder>>, Self::Type: From<DFA> {
+        self.into().and_then(|mut dfa| {
+            if dfa.finals.is_empty() {
+                dfa.finals.extend(dfa.start);
+            }
+            Ok(dfa).finalize().map(Into::into)
+        })
+    }
 }
 
 impl DFABuilder {
     /// Creates a new DFABuilder.
     pub fn new() -> Result<DFABuilder> {
-        Ok(DFABuilder{transitions: HashMap::new(), start: None, finals: HashSet::new()})
+        Ok(DFABuilder{transitions: HashMap::new(), start: None, finals: HashSet::new(), alphabet: None})
+    }
+
+    /// Returns a copy of the builder in its current state, so that several DFAs
+    /// can be built from a common construction without replaying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let common = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .unwrap();
+    ///     let a = common.checkpoint().add_final(1).finalize().unwrap();
+    ///     let ab = common.add_transition('b', 1, 2).add_final(2).finalize().unwrap();
+    ///     assert!(a.test("a"));
+    ///     assert!(ab.test("ab"));
+    /// }
+    /// ```
+    pub fn checkpoint(&self) -> DFABuilder {
+        self.clone()
+    }
+
+    /// Returns warnings about the DFA being built, to catch the mistakes before
+    /// `finalize`. The warnings are advisory: a DFA with warnings can still be
+    /// finalized. The DFA is checked for:
+    ///
+    /// * a missing starting state or missing final states,
+    /// * final states without incoming transition (other than the starting
+    ///   state) or unreachable from the starting state,
+    /// * non final states without outgoing transition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let builder = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_final(7)
+    ///         .add_transition('a', 0, 1)
+    ///         .unwrap();
+    ///     assert_eq!(builder.lint(), vec!["final state 7 has no incoming transition"]);
+    ///     assert!(builder.finalize().is_ok());
+    /// }
+    /// ```
+    pub fn lint(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut sources = HashSet::new();
+        let mut successors = HashMap::new();
+        let mut states : HashSet<_> = self.finals.iter().cloned().chain(self.start).collect();
+        for (&(_,src),&dest) in self.transitions.iter() {
+            sources.insert(src);
+            successors.entry(src).or_insert_with(Vec::new).push(dest);
+            states.insert(src);
+            states.insert(dest);
+        }
+        let mut states : Vec<_> = states.into_iter().collect();
+        states.sort();
+        let targets : HashSet<_> = self.transitions.values().cloned().collect();
+        let reachable = self.start.map(|start| closure(vec![start], &successors));
+        if self.start.is_none() {
+            warnings.push("missing starting state".to_string());
+        }
+        if self.finals.is_empty() {
+            warnings.push("missing final states".to_string());
+        }
+        for &state in states.iter() {
+            if self.finals.contains(&state) {
+                if !targets.contains(&state) && self.start != Some(state) {
+                    warnings.push(format!("final state {} has no incoming transition", state));
+                } else if reachable.as_ref().map_or(false, |reachable| !reachable.contains(&state)) {
+                    warnings.push(format!("final state {} is unreachable from the starting state", state));
+                }
+            } else if !sources.contains(&state) {
+                warnings.push(format!("state {} has no outgoing transitions and isn't final", state));
+            }
+        }
+        warnings
     }
 }
 
@@ -234,11 +643,26 @@ impl DFABuilding for DFABuilder {
         Ok(self).add_transition(symb,src,dest)
     }
 
+    fn set_transition(self, symb: char, src: usize, dest: usize) -> Result<Self::Builder> {
+        Ok(self).set_transition(symb,src,dest)
+    }
+
+    fn remove_transition(self, symb: char, src: usize) -> Result<Self::Builder> {
+        Ok(self).remove_transition(symb,src)
+    }
+
     fn finalize(self) -> Result<Self::Type> {
         Ok(self).finalize()
     }
 }
 
+// Lifts a builder in the chain of results, for the provided methods of
+// `DFABuilding`.
+impl From<DFABuilder> for Result<DFABuilder> {
+    fn from(dfa: DFABuilder) -> Result<DFABuilder> {
+        Ok(dfa)
+    }
+}
 
 /// Implementing DFABuilding trait for Result<DFABuilder> allows
 /// to chain the return value of the DFABuilder instead of unwrapping them
@@ -263,13 +687,27 @@ impl DFABuilding for Result<DFABuilder> {
 
     fn add_transition(self, symb: char, src: usize, dest: usize) -> Result<Self::Builder> {
         self.and_then(|mut dfa| {
-            if dfa.transitions.insert((symb,src), dest).is_some() {
-                return Err(DFAError::DuplicatedTransition(symb,src));
+            if let Some(existing) = dfa.transitions.insert((symb,src), dest) {
+                return Err(DFAError::DuplicatedTransition{symb, src, existing, attempted: dest});
             }
             Ok(dfa)
         })
     }
 
+    fn set_transition(self, symb: char, src: usize, dest: usize) -> Result<Self::Builder> {
+        self.map(|mut dfa| {
+            dfa.transitions.insert((symb,src), dest);
+            dfa
+        })
+    }
+
+    fn remove_transition(self, symb: char, src: usize) -> Result<Self::Builder> {
+        self.map(|mut dfa| {
+            dfa.transitions.remove(&(symb,src));
+            dfa
+        })
+    }
+
     fn finalize(self) -> Result<Self::Type> {
         self.and_then(|dfa| {
             if dfa.start.is_none() {
@@ -277,12 +715,94 @@ impl DFABuilding for Result<DFABuilder> {
             } else if dfa.finals.is_empty() {
                 Err(DFAError::MissingFinalStates)
             } else {
-                Ok(DFA{transitions: dfa.transitions, start: dfa.start.unwrap(), finals: dfa.finals})
+                Ok(DFA::with_alphabet(dfa.transitions, dfa.start.unwrap(), dfa.finals, dfa.alphabet))
             }
         })
     }
 }
 
+/// The `DFAAutoBuilder` sits on top of the `DFABuilder` and allocates the
+/// identifiers of the states, so that an automaton can be built without
+/// manually tracking the integers used for the states. Unlike the `DFABuilder`,
+/// the `DFAAutoBuilder` is borrowed mutably at each call and the errors are
+/// reported by the `build` method.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa::core::*;
+///
+/// fn main() {
+///     // (ab)*
+///     let mut builder = DFAAutoBuilder::new();
+///     let even = builder.state();
+///     let odd = builder.state();
+///     builder.start(even);
+///     builder.accept(even);
+///     builder.transition(even, 'a', odd);
+///     builder.transition(odd, 'b', even);
+///     let dfa = builder.build().unwrap();
+///     assert!(dfa.test("abab"));
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use="builders need to be built with .build() method"]
+pub struct DFAAutoBuilder {
+    builder : Result<DFABuilder>,
+    next    : usize,
+}
+
+impl DFAAutoBuilder {
+    /// Creates a new DFAAutoBuilder.
+    pub fn new() -> DFAAutoBuilder {
+        DFAAutoBuilder{builder: DFABuilder::new(), next: 0}
+    }
+
+    /// Allocates a fresh state.
+    pub fn state(&mut self) -> usize {
+        self.next += 1;
+        self.next-1
+    }
+
+    /// Add a transition to the DFA.
+    pub fn transition(&mut self, from: usize, symb: char, to: usize) {
+        self.update(|builder| builder.add_transition(symb, from, to));
+    }
+
+    /// Set the starting state of the DFA.
+    pub fn start(&mut self, state: usize) {
+        self.update(|builder| builder.add_start(state));
+    }
+
+    /// Add a final state to the DFA.
+    pub fn accept(&mut self, state: usize) {
+        self.update(|builder| builder.add_final(state));
+    }
+
+    /// Finalize the building of the DFA.
+    ///
+    /// # Errors
+    ///
+    /// Return the first error raised by the underlying `DFABuilder`
+    /// (see `DFABuilding`).
+    pub fn build(self) -> Result<DFA> {
+        self.builder.finalize()
+    }
+
+    fn update<F: FnOnce(Result<DFABuilder>) -> Result<DFABuilder>>(&mut self, f: F) {
+        let builder = mem::replace(&mut self.builder, DFABuilder::new());
+        self.builder = f(builder);
+    }
+}
+
+impl Default for DFAAutoBuilder {
+    fn default() -> DFAAutoBuilder {
+        DFAAutoBuilder::new()
+    }
+}
+
 impl DFA {
     /// Test if an input string is a word of the language defined by the DFA.
     ///
@@ -318,42 +838,4158 @@ impl DFA {
     /// }
     /// ```
     pub fn test(&self, input: &str) -> bool {
-        let f = input
-            .chars()
-            .fold(Some(self.start), |state,c| {
-                match state {
-                    Some(n) => self.transitions.get(&(c,n)).map(|v| *v),
-                    None => None,
-                }
-            });
-        match f {
+        match self.test_from(self.start, input) {
             Some(n) => self.finals.contains(&n),
             None => false
         }
     }
-}
 
-impl fmt::Display for DFA {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(writeln!(f, "START: {}", self.start));
-        try!(writeln!(f, "FINALS:"));
-        for fi in self.finals.iter() {
-            try!(writeln!(f,"  {}", fi));
-        }
-        try!(writeln!(f, "TRANSITIONS:"));
-        for (tr,d) in self.transitions.iter() {
-            let (c,s) = *tr;
-            try!(writeln!(f, "  ({},{}) => {}", c, s, d));
+    /// Test if an input string is a word of the language defined by the DFA and
+    /// reports where the run failed otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err(pos)` where `pos` is the index of the character for which no
+    /// transition is defined, or the number of characters of the input if the run
+    /// ends in a non-final state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.test_detailed("abc"), Ok(()));
+    ///     assert_eq!(dfa.test_detailed("abd"), Err(2));
+    ///     assert_eq!(dfa.test_detailed("abcab"), Err(5));
+    /// }
+    /// ```
+    pub fn test_detailed(&self, input: &str) -> result::Result<(),usize> {
+        let state = try!(self.run(input));
+        if self.finals.contains(&state) {
+            Ok(())
+        } else {
+            Err(input.chars().count())
         }
-        write!(f, "")
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Test if an input string is a word of the language defined by the DFA,
+    /// treating an undefined transition as an error instead of a rejection.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err(pos)` where `pos` is the index of the character for which no
+    /// transition is defined. This distinguishes the words the DFA does not
+    /// define a behavior for from the words that are not in its language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.test_strict("abc"), Ok(true));
+    ///     assert_eq!(dfa.test_strict("ab"), Ok(false));
+    ///     assert_eq!(dfa.test_strict("abd"), Err(2));
+    /// }
+    /// ```
+    pub fn test_strict(&self, input: &str) -> result::Result<bool,usize> {
+        self.run(input).map(|state| self.finals.contains(&state))
+    }
 
-    #[test]
+    /// Returns `true` if the input is rejected because the run falls off the
+    /// transition table, and `false` if it is accepted or if the run ends in a
+    /// non-final state (see `test_strict`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa.rejected_due_to_missing_transition("abd"));
+    ///     assert!(!dfa.rejected_due_to_missing_transition("ab"));
+    ///     assert!(!dfa.rejected_due_to_missing_transition("abc"));
+    /// }
+    /// ```
+    pub fn rejected_due_to_missing_transition(&self, input: &str) -> bool {
+        self.test_strict(input).is_err()
+    }
+
+    /// Runs the DFA on an input as far as the transitions allow and returns the
+    /// number of characters consumed along with the last state reached. All the
+    /// characters are consumed if the run does not fall off the transition
+    /// table. The finality of the states is ignored, see
+    /// `accepting_prefix_lengths` for the prefixes which are accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.run_prefix("abcabx"), (5, 2));
+    ///     assert_eq!(dfa.run_prefix("abca"), (4, 1));
+    /// }
+    /// ```
+    pub fn run_prefix(&self, input: &str) -> (usize,usize) {
+        let mut state = self.start;
+        let mut consumed = 0;
+        for c in input.chars() {
+            match self.transitions.get(&(c,state)) {
+                Some(&next) => state = next,
+                None => break,
+            }
+            consumed += 1;
+        }
+        (consumed,state)
+    }
+
+    // Returns the state reached at the end of the input or the index of the
+    // character for which no transition is defined.
+    fn run(&self, input: &str) -> result::Result<usize,usize> {
+        let mut state = self.start;
+        for (pos,c) in input.chars().enumerate() {
+            match self.transitions.get(&(c,state)) {
+                Some(&next) => state = next,
+                None => return Err(pos),
+            }
+        }
+        Ok(state)
+    }
+
+    /// Test if an input made of ASCII bytes is a word of the language defined by
+    /// the DFA, each byte being read as a symbol without UTF-8 decoding.
+    ///
+    /// The DFA is expected to only use ASCII symbols: the transitions on other
+    /// symbols are never taken, and an input containing a non-ASCII byte is
+    /// always rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa.test_bytes(b"abcabc"));
+    ///     assert!(!dfa.test_bytes(b"abca"));
+    /// }
+    /// ```
+    pub fn test_bytes(&self, input: &[u8]) -> bool {
+        let mut state = self.start;
+        for &byte in input {
+            if !byte.is_ascii() {
+                return false;
+            }
+            match self.transitions.get(&(byte as char,state)) {
+                Some(&next) => state = next,
+                None => return false,
+            }
+        }
+        self.finals.contains(&state)
+    }
+
+    /// Test if an input made of Unicode code points is a word of the language
+    /// defined by the DFA, each code point being read as a symbol. An input
+    /// containing a value which is not a Unicode scalar value (a surrogate or a
+    /// value above `0x10FFFF`) is always rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa.test_codepoints(&[0x61, 0x62, 0x63]));
+    ///     assert!(!dfa.test_codepoints(&[0x61, 0xd800]));
+    /// }
+    /// ```
+    pub fn test_codepoints(&self, input: &[u32]) -> bool {
+        let mut state = self.start;
+        for &codepoint in input {
+            let symb = match ::std::char::from_u32(codepoint) {
+                Some(symb) => symb,
+                None => return false,
+            };
+            match self.transitions.get(&(symb,state)) {
+                Some(&next) => state = next,
+                None => return false,
+            }
+        }
+        self.finals.contains(&state)
+    }
+
+    /// Test if an input string is a word of the language defined by the DFA once
+    /// the characters of `skip` are removed from it. The skipped characters are
+    /// never matched, even if the DFA has transitions on them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    /// use std::collections::HashSet;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["abc"]);
+    ///     let skip : HashSet<_> = [' '].iter().cloned().collect();
+    ///     assert!(dfa.test_ignoring(" a b  c ", &skip));
+    /// }
+    /// ```
+    pub fn test_ignoring(&self, input: &str, skip: &HashSet<char>) -> bool {
+        let mut state = self.start;
+        for c in input.chars().filter(|c| !skip.contains(c)) {
+            match self.transitions.get(&(c,state)) {
+                Some(&next) => state = next,
+                None => return false,
+            }
+        }
+        self.finals.contains(&state)
+    }
+
+    /// Returns an iterator over the strings of `iter` accepted by the DFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab", "ba"]);
+    ///     let lines = vec!["ab", "abc", "ba"];
+    ///     let accepted : Vec<_> = dfa.filter_accepted(lines.into_iter()).collect();
+    ///     assert_eq!(accepted, vec!["ab", "ba"]);
+    /// }
+    /// ```
+    pub fn filter_accepted<'a, I: Iterator<Item=&'a str>>(&'a self, iter: I) -> impl Iterator<Item=&'a str> {
+        iter.filter(move |input| self.test(input))
+    }
+
+    /// Returns the symbols that can follow `input_prefix` in an accepted word,
+    /// i.e. the symbols whose transition from the state reached by the prefix
+    /// leads to a state from which a final state is reachable. The set is empty
+    /// if the DFA has no transition for some symbol of the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["cat", "car", "dog"]);
+    ///     let mut symbs : Vec<_> = dfa.acceptable_next_symbols("ca").into_iter().collect();
+    ///     symbs.sort();
+    ///     assert_eq!(symbs, vec!['r', 't']);
+    ///     assert!(dfa.acceptable_next_symbols("cow").is_empty());
+    /// }
+    /// ```
+    pub fn acceptable_next_symbols(&self, input_prefix: &str) -> HashSet<char> {
+        let mut state = self.start;
+        for symb in input_prefix.chars() {
+            match self.transitions.get(&(symb,state)) {
+                Some(&next) => state = next,
+                None => return HashSet::new(),
+            }
+        }
+        let live = self.coreachable_states();
+        self.transitions
+            .iter()
+            .filter(|&(&(_,src),dest)| src == state && live.contains(dest))
+            .map(|(&(symb,_),_)| symb)
+            .collect()
+    }
+
+    /// Returns `true` if the input is within `max_edits` edits of an accepted
+    /// word, an edit being the substitution, the insertion or the deletion of a
+    /// symbol (Levenshtein distance). The run follows the product of the DFA with
+    /// the Levenshtein automaton of the input: it keeps the live states of the
+    /// DFA along with the least number of edits needed to reach them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["kitten"]);
+    ///     assert!(dfa.test_approx("sitting", 3));
+    ///     assert!(!dfa.test_approx("sitting", 2));
+    /// }
+    /// ```
+    pub fn test_approx(&self, input: &str, max_edits: usize) -> bool {
+        let successors = self.sorted_successors();
+        // follows the transitions without reading the input (insertions), a
+        // state being visited again when it is reached with fewer edits
+        let insert = |mut edits: HashMap<usize,usize>| {
+            let mut stack : Vec<_> = edits.iter().map(|(&state,&cost)| (state,cost)).collect();
+            while let Some((state,cost)) = stack.pop() {
+                if cost == max_edits || edits[&state] < cost {
+                    continue;
+                }
+                for &(_,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                    if edits.get(&dest).map_or(true, |&c| cost+1 < c) {
+                        edits.insert(dest, cost+1);
+                        stack.push((dest,cost+1));
+                    }
+                }
+            }
+            edits
+        };
+        let mut edits = HashMap::new();
+        edits.insert(self.start, 0);
+        edits = insert(edits);
+        for symb in input.chars() {
+            let mut nexts = HashMap::new();
+            {
+                let mut relax = |state: usize, cost: usize| {
+                    if cost <= max_edits && nexts.get(&state).map_or(true, |&c| cost < c) {
+                        nexts.insert(state, cost);
+                    }
+                };
+                for (&state,&cost) in edits.iter() {
+                    // deletion of the symbol
+                    relax(state, cost+1);
+                    for &(next,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                        // match or substitution
+                        relax(dest, if next == symb { cost } else { cost+1 });
+                    }
+                }
+            }
+            if nexts.is_empty() {
+                return false;
+            }
+            edits = insert(nexts);
+        }
+        edits.keys().any(|state| self.finals.contains(state))
+    }
+
+    /// Returns `true` if the DFA accepts a word matching the input template, where
+    /// each occurrence of `wildcard` in the input matches any single symbol. The
+    /// run follows every transition at a wildcard position and keeps the set of
+    /// the live states.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["cat", "cut"]);
+    ///     assert!(dfa.test_with_wildcards("c?t", '?'));
+    ///     assert!(!dfa.test_with_wildcards("?a", '?'));
+    /// }
+    /// ```
+    pub fn test_with_wildcards(&self, input: &str, wildcard: char) -> bool {
+        let successors = self.sorted_successors();
+        let mut states : HashSet<_> = [self.start].iter().cloned().collect();
+        for c in input.chars() {
+            states = if c == wildcard {
+                states
+                    .iter()
+                    .filter_map(|state| successors.get(state))
+                    .flat_map(|nexts| nexts.iter().map(|&(_,dest)| dest))
+                    .collect()
+            } else {
+                states
+                    .iter()
+                    .filter_map(|&state| self.transitions.get(&(c,state)).cloned())
+                    .collect()
+            };
+            if states.is_empty() {
+                return false;
+            }
+        }
+        !states.is_disjoint(&self.finals)
+    }
+
+    /// Returns the lengths, in characters, of the prefixes of the input accepted
+    /// by the DFA, in increasing order. The run stops when it falls off the
+    /// transition table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a+
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 1, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.accepting_prefix_lengths("aab"), vec![1, 2]);
+    /// }
+    /// ```
+    pub fn accepting_prefix_lengths(&self, input: &str) -> Vec<usize> {
+        let mut lengths = Vec::new();
+        let mut state = self.start;
+        if self.finals.contains(&state) {
+            lengths.push(0);
+        }
+        for (pos,c) in input.chars().enumerate() {
+            match self.transitions.get(&(c,state)) {
+                Some(&next) => state = next,
+                None => break,
+            }
+            if self.finals.contains(&state) {
+                lengths.push(pos+1);
+            }
+        }
+        lengths
+    }
+
+    /// Returns the positions `i`, in increasing order, such that `input[..i]` is
+    /// accepted by the DFA. Unlike `accepting_prefix_lengths`, the positions are
+    /// byte offsets, so that they can be used to slice the input. The run starts
+    /// at the beginning of the input and stops when it falls off the transition
+    /// table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["é", "éa"]);
+    ///     let input = "éab";
+    ///     assert_eq!(dfa.match_ends(input), vec![2, 3]);
+    ///     assert_eq!(&input[..3], "éa");
+    /// }
+    /// ```
+    pub fn match_ends(&self, input: &str) -> Vec<usize> {
+        self.match_ends_with_states(input).into_iter().map(|(end,_)| end).collect()
+    }
+
+    // Returns the positions of `match_ends` with the final state reached at
+    // each of them.
+    fn match_ends_with_states(&self, input: &str) -> Vec<(usize,usize)> {
+        let mut ends = Vec::new();
+        let mut state = self.start;
+        if self.finals.contains(&state) {
+            ends.push((0,state));
+        }
+        for (pos,c) in input.char_indices() {
+            match self.transitions.get(&(c,state)) {
+                Some(&next) => state = next,
+                None => break,
+            }
+            if self.finals.contains(&state) {
+                ends.push((pos+c.len_utf8(),state));
+            }
+        }
+        ends
+    }
+
+    // Returns the non-empty longest matches `(start,end,state)` of the scan of
+    // the input: the scan starts at the beginning of the input and restarts
+    // after each longest match, a position without a non-empty match being
+    // skipped. `state` is the final state reached at the end of the match.
+    fn longest_match_scan(&self, input: &str) -> Vec<(usize,usize,usize)> {
+        let mut matches = Vec::new();
+        let mut pos = 0;
+        while pos < input.len() {
+            let rest = &input[pos..];
+            match self.match_ends_with_states(rest).last() {
+                Some(&(end,state)) if end > 0 => {
+                    matches.push((pos,pos+end,state));
+                    pos += end;
+                },
+                // can't fail because `pos` is not at the end of the input
+                _ => pos += rest.chars().next().unwrap().len_utf8(),
+            }
+        }
+        matches
+    }
+
+    /// Returns `true` if the longest-match scan of the input by a tokenizer DFA
+    /// meets an ambiguous token. The scan starts at the beginning of the input
+    /// and restarts after each longest match, a position without a non-empty
+    /// match being skipped.
+    ///
+    /// Since the DFA is deterministic, the run from a position reaches a single
+    /// state at each length, so two final states can only meet at the end of a
+    /// longest match through different cuts of the token. A token is ambiguous
+    /// if it splits into a shorter match followed by a match ending in another
+    /// final state than the whole token: the two final states then recognize
+    /// the same text, e.g. a keyword `if` and the identifiers `i` and `f`.
+    /// Without tags, each final state stands for its own token type; to give
+    /// the token types of the final states, see
+    /// `has_ambiguous_longest_match_tagged`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // the keyword "do" and the identifiers [do]+
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_final(2)
+    ///         .add_final(3)
+    ///         .add_transition('d', 0, 1)
+    ///         .add_transition('o', 0, 3)
+    ///         .add_transition('o', 1, 2)
+    ///         .add_transition('d', 1, 3)
+    ///         .add_transition('d', 2, 3)
+    ///         .add_transition('o', 2, 3)
+    ///         .add_transition('d', 3, 3)
+    ///         .add_transition('o', 3, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     // "do" ends in the keyword state, "d" then "o" in the identifier state
+    ///     assert!(dfa.has_ambiguous_longest_match("oo do"));
+    ///     assert!(!dfa.has_ambiguous_longest_match("oo o"));
+    /// }
+    /// ```
+    pub fn has_ambiguous_longest_match(&self, input: &str) -> bool {
+        self.longest_match_scan(input).into_iter().any(|(start,end,state)| {
+            let token = &input[start..end];
+            self.match_ends_with_states(token)
+                .into_iter()
+                .filter(|&(mid,_)| mid > 0 && mid < token.len())
+                .any(|(mid,_)| {
+                    self.test_from(self.start, &token[mid..])
+                        .map_or(false, |other| other != state && self.finals.contains(&other))
+                })
+        })
+    }
+
+    /// Returns `true` if the longest-match scan of the input by a tokenizer DFA
+    /// (see `has_ambiguous_longest_match`) meets an ambiguous token, i.e. if the
+    /// final state ending a longest match carries several distinct tags. The
+    /// tags tell which token types each final state recognizes, e.g. the
+    /// pattern indices returned by `contains_any`. A final state without tags,
+    /// or with a single distinct tag, is not ambiguous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    /// use std::collections::HashMap;
+    ///
+    /// fn main() {
+    ///     // the keyword "do" (0) and the identifiers [do]+ (1)
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_final(2)
+    ///         .add_final(3)
+    ///         .add_transition('d', 0, 1)
+    ///         .add_transition('o', 0, 3)
+    ///         .add_transition('o', 1, 2)
+    ///         .add_transition('d', 1, 3)
+    ///         .add_transition('d', 2, 3)
+    ///         .add_transition('o', 2, 3)
+    ///         .add_transition('d', 3, 3)
+    ///         .add_transition('o', 3, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let tags : HashMap<_,_> = [(1,vec![1]), (2,vec![0, 1]), (3,vec![1])].iter().cloned().collect();
+    ///     assert!(dfa.has_ambiguous_longest_match_tagged("od do", &tags));
+    ///     assert!(!dfa.has_ambiguous_longest_match_tagged("od dod", &tags));
+    /// }
+    /// ```
+    pub fn has_ambiguous_longest_match_tagged(&self, input: &str, tags: &HashMap<usize,Vec<usize>>) -> bool {
+        self.longest_match_scan(input).into_iter().any(|(_,_,state)| {
+            tags.get(&state).map_or(false, |tags| tags.iter().collect::<HashSet<_>>().len() > 1)
+        })
+    }
+
+    /// Returns `true` if the reverse of the input is accepted by the DFA, without
+    /// reversing the input. The input is read from left to right by the reverse
+    /// NFA of the DFA (see `reverse`), which is built on the first call and
+    /// memoized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["abc"]);
+    ///     assert!(dfa.test_reversed("cba"));
+    ///     assert!(!dfa.test_reversed("abc"));
+    /// }
+    /// ```
+    pub fn test_reversed(&self, input: &str) -> bool {
+        self.reversed.get_or_init(|| self.reverse()).test(input)
+    }
+
+    /// Returns the byte offsets `(start,end)` of the leftmost-longest match of the
+    /// DFA in the input, i.e. the longest accepted substring among those starting
+    /// at the smallest position. The empty word is matched at the beginning of the
+    /// input if the starting state is final. The offsets always lie on character
+    /// boundaries, even with multi-byte symbols, so `&input[start..end]` can't
+    /// panic; see `find_str` to get the slice directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["été", "étés"]);
+    ///     assert_eq!(dfa.find("un été"), Some((3,8)));
+    ///     assert_eq!(dfa.find("les étés"), Some((4,10)));
+    ///     assert_eq!(dfa.find("hiver"), None);
+    /// }
+    /// ```
+    pub fn find(&self, input: &str) -> Option<(usize,usize)> {
+        let starts = input.char_indices().map(|(pos,_)| pos).chain(Some(input.len()));
+        for start in starts {
+            if let Some(&end) = self.match_ends(&input[start..]).last() {
+                return Some((start,start+end));
+            }
+        }
+        None
+    }
+
+    /// Returns the leftmost-longest match of the DFA in the input as a slice of
+    /// the input (see `find`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ça", "çà"]);
+    ///     assert_eq!(dfa.find_str("voilà çà et là"), Some("çà"));
+    /// }
+    /// ```
+    pub fn find_str<'a>(&self, input: &'a str) -> Option<&'a str> {
+        self.find(input).map(|(start,end)| &input[start..end])
+    }
+
+    /// Runs an input string from an arbitrary state and returns the state reached
+    /// at the end of the input.
+    ///
+    /// Returns `None` if `state` is not a state of the DFA or if the run falls off
+    /// the transition table. Since the returned state can be fed back as the
+    /// starting point of another run, the states reached after common prefixes can
+    /// be cached and the matching resumed from there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let prefix = dfa.test_from(0, "ab").unwrap();
+    ///     assert_eq!(dfa.test_from(prefix, "c"), Some(0));
+    ///     assert_eq!(dfa.test_from(prefix, "a"), None);
+    ///     assert_eq!(dfa.test_from(42, ""), None);
+    /// }
+    /// ```
+    pub fn test_from(&self, state: usize, input: &str) -> Option<usize> {
+        if !self.has_state(state) {
+            return None;
+        }
+        input
+            .chars()
+            .fold(Some(state), |state,c| {
+                match state {
+                    Some(n) => self.transitions.get(&(c,n)).map(|v| *v),
+                    None => None,
+                }
+            })
+    }
+
+    /// Returns an NFA accepting the reverse of the words accepted by the DFA. A
+    /// new starting state is added to the NFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let nfa = dfa.reverse();
+    ///     assert!(nfa.test("ba"));
+    ///     assert!(!nfa.test("ab"));
+    /// }
+    /// ```
+    pub fn reverse(&self) -> NFA {
+        let start = self.states().iter().max().unwrap() + 1;
+        let mut nfa = NFABuilder::new().add_start(start).add_final(self.start);
+        if self.finals.contains(&self.start) {
+            nfa = nfa.add_final(start);
+        }
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            nfa = nfa.add_transition(symb, dest, src);
+            if self.finals.contains(&dest) {
+                nfa = nfa.add_transition(symb, start, src);
+            }
+        }
+        // can't fail because the starting state and a final state are specified
+        nfa.finalize().unwrap()
+    }
+
+    /// Returns the minimal DFA accepting the same language. The minimal DFA only
+    /// contains useful states (see `trim`) and its states are numbered in a
+    /// canonical way, so the minimal DFAs of two equivalent DFAs are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)c
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(3)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 2)
+    ///         .add_transition('c', 1, 3)
+    ///         .add_transition('c', 2, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.minimize().stats().state_count, 3);
+    /// }
+    /// ```
+    pub fn minimize(&self) -> DFA {
+        let trimmed = self.trim();
+        let mut alphabet : Vec<_> = trimmed.alphabet().into_iter().collect();
+        alphabet.sort();
+        let states : Vec<_> = trimmed.states().into_iter().collect();
+        // Moore's algorithm: the classes are refined according to the classes of
+        // the destinations until the partition is stable. A missing transition
+        // leads to the dead state which is not equivalent to any useful state.
+        let mut class : HashMap<_,_> = states
+            .iter()
+            .map(|&state| (state,trimmed.finals.contains(&state) as usize))
+            .collect();
+        let mut count = class.values().collect::<HashSet<_>>().len();
+        loop {
+            let mut signatures = HashMap::new();
+            let mut refined = HashMap::new();
+            for &state in states.iter() {
+                let nexts : Vec<_> = alphabet
+                    .iter()
+                    .map(|&symb| trimmed.transitions.get(&(symb,state)).map(|dest| class[dest]))
+                    .collect();
+                let id = signatures.len();
+                let id = *signatures.entry((class[&state],nexts)).or_insert(id);
+                refined.insert(state, id);
+            }
+            class = refined;
+            telemetry!("minimization", signatures.len(), 0);
+            if signatures.len() == count {
+                break;
+            }
+            count = signatures.len();
+        }
+        let transitions = trimmed.transitions
+            .iter()
+            .map(|(&(symb,src),dest)| ((symb,class[&src]),class[dest]))
+            .collect();
+        let finals = trimmed.finals.iter().map(|state| class[state]).collect();
+        DFA::with_alphabet(transitions, class[&trimmed.start], finals, self.alphabet.clone()).renumbered()
+    }
+
+    /// Returns the quotient of the DFA by the equivalence relation generated by
+    /// `same`: the states `a` and `b` such that `same(a,b)` are merged, and so
+    /// are the states related through a chain of such pairs. Each class of
+    /// states is numbered by its smallest state and is final if one of its
+    /// states is final. Unlike `minimize`, the merged states do not need to be
+    /// equivalent, so the language may grow.
+    ///
+    /// # Errors
+    ///
+    /// Return a DFAError::DuplicatedTransition if two merged states have
+    /// transitions with the same symbol to states of different classes, the
+    /// quotient being then nondeterministic. The `src`, `existing` and
+    /// `attempted` fields are the numbers of the classes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // merging the two ends of "ab" loops on it
+    ///     let dfa = DFA::from_words(&["ab"]).quotient_by(|a,b| a+b == 2).unwrap();
+    ///     assert!(dfa.test("ababab"));
+    ///     assert!(dfa.test(""));
+    /// }
+    /// ```
+    pub fn quotient_by<F: Fn(usize,usize) -> bool>(&self, same: F) -> Result<DFA> {
+        let mut states : Vec<_> = self.states().into_iter().collect();
+        states.sort();
+        // union-find, the root of a class being its smallest state
+        let mut parent : HashMap<_,_> = states.iter().map(|&state| (state,state)).collect();
+        fn find(parent: &mut HashMap<usize,usize>, state: usize) -> usize {
+            let mut root = state;
+            while parent[&root] != root {
+                root = parent[&root];
+            }
+            let mut state = state;
+            while state != root {
+                state = mem::replace(parent.get_mut(&state).unwrap(), root);
+            }
+            root
+        }
+        for (i,&a) in states.iter().enumerate() {
+            for &b in states[i+1..].iter() {
+                if same(a, b) || same(b, a) {
+                    let (ra,rb) = (find(&mut parent, a),find(&mut parent, b));
+                    if ra != rb {
+                        parent.insert(ra.max(rb), ra.min(rb));
+                    }
+                }
+            }
+        }
+        let mut transitions = HashMap::new();
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            let (src,dest) = (find(&mut parent, src),find(&mut parent, dest));
+            if let Some(existing) = transitions.insert((symb,src), dest) {
+                if existing != dest {
+                    return Err(DFAError::DuplicatedTransition{symb, src, existing, attempted: dest});
+                }
+            }
+        }
+        let start = find(&mut parent, self.start);
+        let finals = self.finals.iter().map(|&state| find(&mut parent, state)).collect();
+        Ok(DFA::with_alphabet(transitions, start, finals, self.alphabet.clone()))
+    }
+
+    /// Returns the index of the Myhill-Nerode equivalence of the language of the
+    /// DFA over the alphabet of the DFA, i.e. the number of states of the
+    /// minimal complete DFA. Unlike `minimize`, it counts the class of the words
+    /// which are not a prefix of any accepted word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.nerode_index(), 1);
+    /// }
+    /// ```
+    pub fn nerode_index(&self) -> usize {
+        let minimal = self.minimize();
+        if minimal.is_empty() {
+            return 1;
+        }
+        let stats = minimal.stats();
+        let alphabet_size = self.alphabet().len();
+        if stats.transition_count == stats.state_count*alphabet_size {
+            stats.state_count
+        } else {
+            stats.state_count + 1
+        }
+    }
+
+    /// Returns the number of the residual languages reachable from `state`, i.e.
+    /// the `nerode_index` of the DFA rerooted at `state`. As for `nerode_index`,
+    /// the alphabet is the one of the whole DFA and the class of the words
+    /// leading to no final state is counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab"]);
+    ///     assert_eq!(dfa.residual_complexity(0), 4);
+    ///     assert_eq!(dfa.residual_complexity(2), 2);
+    /// }
+    /// ```
+    pub fn residual_complexity(&self, state: usize) -> usize {
+        DFA::with_alphabet(self.transitions.clone(), state, self.finals.clone(), self.alphabet.clone()).nerode_index()
+    }
+
+    /// Returns a shortest word accepted from one of the states `a` and `b` but not
+    /// from the other, or `None` if the two states are equivalent. Among the
+    /// shortest words, the smallest in lexicographic order is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // ab|b
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 2)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.distinguishing_string(0, 1), Some("ab".to_string()));
+    /// }
+    /// ```
+    pub fn distinguishing_string(&self, a: usize, b: usize) -> Option<String> {
+        let mut alphabet : Vec<_> = self.alphabet().into_iter().collect();
+        alphabet.sort();
+        let is_final = |state: Option<usize>| state.map_or(false, |s| self.finals.contains(&s));
+        let next = |state: Option<usize>, symb: char| {
+            state.and_then(|s| self.transitions.get(&(symb,s)).cloned())
+        };
+        // BFS over the pairs of states, a missing transition leading to `None`
+        let start = (Some(a),Some(b));
+        let mut parents = HashMap::new();
+        parents.insert(start, None);
+        let mut pairs = vec![start];
+        let mut i = 0;
+        while i < pairs.len() {
+            let (s1,s2) = pairs[i];
+            if is_final(s1) != is_final(s2) {
+                let mut word = Vec::new();
+                let mut pair = pairs[i];
+                while let Some((symb,parent)) = parents[&pair] {
+                    word.push(symb);
+                    pair = parent;
+                }
+                return Some(word.into_iter().rev().collect());
+            }
+            for &symb in alphabet.iter() {
+                let dest = (next(s1, symb),next(s2, symb));
+                if dest != (None,None) && !parents.contains_key(&dest) {
+                    parents.insert(dest, Some((symb,pairs[i])));
+                    pairs.push(dest);
+                }
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Returns `true` if the states `a` and `b` agree on the acceptance of every
+    /// word of length at most `k`, i.e. if they are in the same class of the
+    /// `k`-th partition computed by Moore's algorithm. Two states are equivalent
+    /// if and only if they are `k`-equivalent for every `k`.
+    ///
+    /// The pairs of states reached by the same word from `a` and `b` are
+    /// explored by a BFS which stops at the depth `k`, so a small `k` only
+    /// explores the pairs reached by the short words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // ab|b
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 2)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa.k_equivalent(0, 1, 1));
+    ///     assert!(!dfa.k_equivalent(0, 1, 2));
+    /// }
+    /// ```
+    pub fn k_equivalent(&self, a: usize, b: usize, k: usize) -> bool {
+        let mut alphabet : Vec<_> = self.alphabet().into_iter().collect();
+        alphabet.sort();
+        let is_final = |state: Option<usize>| state.map_or(false, |s| self.finals.contains(&s));
+        let next = |state: Option<usize>, symb: char| {
+            state.and_then(|s| self.transitions.get(&(symb,s)).cloned())
+        };
+        // BFS over the pairs of states, a missing transition leading to `None`;
+        // a pair already visited is reached again by a longer word
+        let start = (Some(a),Some(b));
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut frontier = vec![start];
+        for depth in 0..k.saturating_add(1) {
+            if frontier.iter().any(|&(s1,s2)| is_final(s1) != is_final(s2)) {
+                return false;
+            }
+            if depth == k {
+                break;
+            }
+            let mut next_frontier = Vec::new();
+            for &(s1,s2) in frontier.iter() {
+                for &symb in alphabet.iter() {
+                    let dest = (next(s1, symb),next(s2, symb));
+                    if dest != (None,None) && visited.insert(dest) {
+                        next_frontier.push(dest);
+                    }
+                }
+            }
+            if next_frontier.is_empty() {
+                break;
+            }
+            frontier = next_frontier;
+        }
+        true
+    }
+
+    /// Returns an accepted word of minimal total cost along with its cost, or
+    /// `None` if the language is empty. The cost of a word is the sum of the
+    /// costs of its symbols, the symbols missing from `cost` costing 1. The
+    /// word is found by Dijkstra's algorithm from the starting state, the final
+    /// states being the targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    /// use std::collections::HashMap;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab", "c"]);
+    ///     let cost = [('c', 5)].iter().cloned().collect::<HashMap<_,_>>();
+    ///     assert_eq!(dfa.cheapest_word(&cost), Some(("ab".to_string(), 2)));
+    /// }
+    /// ```
+    pub fn cheapest_word(&self, cost: &HashMap<char,u64>) -> Option<(String,u64)> {
+        let successors = self.sorted_successors();
+        let mut distances = HashMap::new();
+        let mut parents = HashMap::new();
+        let mut settled = HashSet::new();
+        let mut heap = BinaryHeap::new();
+        distances.insert(self.start, 0);
+        heap.push(Reverse((0,self.start)));
+        while let Some(Reverse((distance,state))) = heap.pop() {
+            if !settled.insert(state) {
+                continue;
+            }
+            if self.finals.contains(&state) {
+                let mut word = Vec::new();
+                let mut state = state;
+                while let Some(&(symb,parent)) = parents.get(&state) {
+                    word.push(symb);
+                    state = parent;
+                }
+                return Some((word.into_iter().rev().collect(),distance));
+            }
+            for &(symb,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                let distance = distance.saturating_add(*cost.get(&symb).unwrap_or(&1));
+                if !settled.contains(&dest) && distances.get(&dest).map_or(true, |&d| distance < d) {
+                    distances.insert(dest, distance);
+                    parents.insert(dest, (symb,state));
+                    heap.push(Reverse((distance,dest)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the DFA where each symbol of the transitions is replaced by its
+    /// image through `map`, the symbols missing from `map` being kept unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Return a `DFAError::DuplicatedTransition` if two transitions leaving the
+    /// same state are mapped to the same symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    /// use std::collections::HashMap;
+    ///
+    /// fn main() {
+    ///     // ab
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let map : HashMap<_,_> = [('a', 'x')].iter().cloned().collect();
+    ///     assert!(dfa.compose_with_map(&map).unwrap().test("xb"));
+    /// }
+    /// ```
+    pub fn compose_with_map(&self, map: &HashMap<char,char>) -> Result<DFA> {
+        let mut transitions = HashMap::new();
+        for (symb,src,dest) in self.transitions() {
+            let symb = map.get(&symb).cloned().unwrap_or(symb);
+            if let Some(existing) = transitions.insert((symb,src), dest) {
+                return Err(DFAError::DuplicatedTransition{symb, src, existing, attempted: dest});
+            }
+        }
+        let alphabet = self.alphabet.as_ref().map(|declared| {
+            declared.iter().map(|symb| map.get(symb).cloned().unwrap_or(*symb)).collect()
+        });
+        Ok(DFA::with_alphabet(transitions, self.start, self.finals.clone(), alphabet))
+    }
+
+    /// Returns a DFA accepting the words which have a prefix accepted by the DFA.
+    /// Once a final state is reached, the rest of the input is consumed by an
+    /// accepting sink which loops over `alphabet` and the symbols of the DFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab"]).with_suffix_anything(&['x']);
+    ///     assert!(dfa.test("abxab"));
+    ///     assert!(!dfa.test("xab"));
+    /// }
+    /// ```
+    pub fn with_suffix_anything(&self, alphabet: &[char]) -> DFA {
+        let mut alphabet : HashSet<_> = alphabet.iter().cloned().collect();
+        alphabet.extend(self.alphabet());
+        let sink = self.states().iter().max().unwrap() + 1;
+        let mut transitions : HashMap<_,_> = self.transitions
+            .iter()
+            .filter(|&(&(_,src),_)| !self.finals.contains(&src))
+            .map(|(&tr,&dest)| (tr,dest))
+            .collect();
+        let mut finals = self.finals.clone();
+        finals.insert(sink);
+        for &state in finals.iter() {
+            for &symb in alphabet.iter() {
+                transitions.insert((symb,state), sink);
+            }
+        }
+        DFA::with_alphabet(transitions, self.start, finals, self.alphabet.clone())
+    }
+
+    /// Returns `true` if every factor (substring) of every word accepted by
+    /// `other` is accepted by the DFA.
+    ///
+    /// The factors of `other` are recognized by an NFA where every useful state
+    /// of `other` is both initial and final. This NFA is determinized, which may
+    /// take a time and a space exponential in the number of states of `other`,
+    /// and its intersection with the complement of the DFA is checked to be
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a*b*
+    ///     let fragments = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 1)
+    ///         .add_transition('b', 1, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(fragments.accepts_all_substrings_of(&DFA::from_words(&["aab", "abb"])));
+    ///     assert!(!fragments.accepts_all_substrings_of(&DFA::from_words(&["aba"])));
+    /// }
+    /// ```
+    pub fn accepts_all_substrings_of(&self, other: &DFA) -> bool {
+        let alphabet : Vec<_> = self.alphabet().union(&other.alphabet()).cloned().collect();
+        let factors = other.factors();
+        intersect_all(&[factors, self.complement(&alphabet)]).is_empty()
+    }
+
+    // Returns a DFA accepting the factors of the words of the DFA.
+    fn factors(&self) -> DFA {
+        let trimmed = self.trim();
+        if trimmed.is_empty() {
+            return trimmed;
+        }
+        let states = trimmed.states();
+        // a new starting state reads the first symbol of a factor from any state
+        let start = states.iter().max().unwrap() + 1;
+        let mut nfa = NFABuilder::new().add_start(start).add_final(start);
+        for &state in states.iter() {
+            nfa = nfa.add_final(state);
+        }
+        for (&(symb,src),&dest) in trimmed.transitions.iter() {
+            nfa = nfa
+                .add_transition(symb, src, dest)
+                .add_transition(symb, start, dest);
+        }
+        // can't fail because the starting state and a final state are specified
+        nfa.finalize().unwrap().to_dfa()
+    }
+
+    /// Returns a 64-bit hash of the language of the DFA, which is stable across
+    /// runs, platforms and versions of Rust.
+    ///
+    /// The hash is the 64-bit FNV-1a hash of the transitions and the final states
+    /// of the minimal DFA (see `minimize`), so two DFAs accepting the same
+    /// language have the same fingerprint. Two different languages may collide,
+    /// which is unlikely but possible: equal fingerprints are not a proof of
+    /// equivalence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa1 = DFA::from_words(&["ab", "cb"]);
+    ///     let dfa2 = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('c', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa1.language_fingerprint(), dfa2.language_fingerprint());
+    /// }
+    /// ```
+    pub fn language_fingerprint(&self) -> u64 {
+        const OFFSET_BASIS : u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME : u64 = 0x0000_0100_0000_01b3;
+        let minimal = self.minimize();
+        let mut finals : Vec<_> = minimal.finals.iter().cloned().collect();
+        finals.sort();
+        let transitions = minimal.transitions();
+        let mut values = vec![finals.len() as u64];
+        values.extend(finals.into_iter().map(|state| state as u64));
+        values.push(transitions.len() as u64);
+        for (symb,src,dest) in transitions {
+            values.extend_from_slice(&[symb as u64, src as u64, dest as u64]);
+        }
+        values
+            .into_iter()
+            .flat_map(|value| (0..8).map(move |i| (value >> (8*i)) as u8))
+            .fold(OFFSET_BASIS, |hash,byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+    }
+
+    /// Returns the minimal DFA accepting the words `w` such that `w` followed by
+    /// `suffix` is accepted by the DFA. The final states of the quotient are the
+    /// states from which `suffix` leads to a final state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["abc", "bc", "c"]);
+    ///     assert!(dfa.right_quotient("bc").accepts_exactly(&["a", ""]));
+    /// }
+    /// ```
+    pub fn right_quotient(&self, suffix: &str) -> DFA {
+        let finals = self.states()
+            .into_iter()
+            .filter(|&state| self.test_from(state, suffix).map_or(false, |dest| self.finals.contains(&dest)))
+            .collect();
+        DFA::with_alphabet(self.transitions.clone(), self.start, finals, self.alphabet.clone()).minimize()
+    }
+
+    /// Returns the minimal DFA accepting the concatenations of a word of the DFA
+    /// with a word of `other`. The concatenation is built as an ENFA where the
+    /// final states of the DFA are linked to the starting state of `other` by
+    /// epsilon transitions, and then determinized and minimized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["a", "b"]).concat_dfa(&DFA::from_words(&["c"]));
+    ///     assert!(dfa.accepts_exactly(&["ac", "bc"]));
+    /// }
+    /// ```
+    pub fn concat_dfa(&self, other: &DFA) -> DFA {
+        if self.is_empty() || other.is_empty() {
+            return DFA::with_alphabet(HashMap::new(), 0, HashSet::new(), declared_alphabet([self, other]));
+        }
+        // the states of `other` are shifted after the states of the DFA
+        let offset = self.states().iter().max().unwrap() + 1;
+        let mut nfa = ENFABuilder::new().add_start(self.start);
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            nfa = nfa.add_transition(symb, src, dest);
+        }
+        for (&(symb,src),&dest) in other.transitions.iter() {
+            nfa = nfa.add_transition(symb, src+offset, dest+offset);
+        }
+        for &state in self.finals.iter() {
+            nfa = nfa.add_e_transition(state, other.start+offset);
+        }
+        for &state in other.finals.iter() {
+            nfa = nfa.add_final(state+offset);
+        }
+        // can't fail because the starting state and a final state are specified
+        nfa.finalize().unwrap().to_dfa().minimize().declaring(declared_alphabet([self, other]))
+    }
+
+    /// Returns the minimal DFA accepting the Kleene star of the language of the
+    /// DFA, i.e. the concatenations of any number of its words, the empty word
+    /// included. The star is built as an ENFA with a new final starting state
+    /// linked to the starting state of the DFA, the final states being linked
+    /// back to it by epsilon transitions, and then determinized and minimized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab"]).star_dfa();
+    ///     assert!(dfa.test(""));
+    ///     assert!(dfa.test("abab"));
+    ///     assert!(!dfa.test("aba"));
+    /// }
+    /// ```
+    pub fn star_dfa(&self) -> DFA {
+        // a new starting state, so that the empty word is accepted without
+        // accepting the words leading back to the starting state of the DFA
+        let start = self.states().iter().max().unwrap() + 1;
+        let mut nfa = ENFABuilder::new()
+            .add_start(start)
+            .add_final(start)
+            .add_e_transition(start, self.start);
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            nfa = nfa.add_transition(symb, src, dest);
+        }
+        for &state in self.finals.iter() {
+            nfa = nfa.add_final(state).add_e_transition(state, self.start);
+        }
+        // can't fail because the starting state and a final state are specified
+        nfa.finalize().unwrap().to_dfa().minimize().declaring(self.alphabet.clone())
+    }
+
+    /// Returns the minimal DFA accepting the concatenations of exactly `n` words
+    /// of the DFA. The power is built by `n-1` successive calls to `concat_dfa`,
+    /// which minimizes each intermediate DFA. The power 0 only accepts the empty
+    /// word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["a", "bb"]).power(2);
+    ///     assert!(dfa.accepts_exactly(&["aa", "abb", "bba", "bbbb"]));
+    /// }
+    /// ```
+    pub fn power(&self, n: usize) -> DFA {
+        if n == 0 {
+            return DFA::epsilon_only().declaring(self.alphabet.clone());
+        }
+        let minimal = self.minimize();
+        (1..n).fold(minimal.clone(), |power,_| power.concat_dfa(&minimal))
+    }
+
+    /// Returns the minimal DFA accepting the reverse of the words accepted by the
+    /// DFA. This is a shortcut for `reverse().to_dfa().minimize()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // abc
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(3)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let reversed = dfa.reverse_dfa();
+    ///     assert!(reversed.test("cba"));
+    ///     assert!(!reversed.test("abc"));
+    /// }
+    /// ```
+    pub fn reverse_dfa(&self) -> DFA {
+        self.reverse().to_dfa().minimize().declaring(self.alphabet.clone())
+    }
+
+    /// Returns a DFA over `alphabet` accepting the words containing `pattern` as
+    /// a substring. The DFA is the Knuth-Morris-Pratt automaton of the pattern:
+    /// the state `i` tells that the `i` first symbols of the pattern are the
+    /// longest ones ending the input read so far, and the failure links are
+    /// folded in the transitions so that the DFA is complete over `alphabet`. It
+    /// has `pattern.chars().count() + 1` states, the last one being final. The
+    /// language is empty if the pattern uses a symbol which is not in `alphabet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::contains_substring("aba", &['a', 'b']);
+    ///     assert!(dfa.test("ababb"));
+    ///     assert!(dfa.test("bbaaba"));
+    ///     assert!(!dfa.test("abbab"));
+    ///     assert_eq!(dfa.stats().state_count, 4);
+    /// }
+    /// ```
+    pub fn contains_substring(pattern: &str, alphabet: &[char]) -> DFA {
+        let pattern : Vec<_> = pattern.chars().collect();
+        let last = pattern.len();
+        let mut transitions = HashMap::new();
+        // the state reached by the pattern deprived of its first symbol
+        let mut failure = 0;
+        for (state,&expected) in pattern.iter().enumerate() {
+            for &symb in alphabet.iter() {
+                let dest = if symb == expected {
+                    state + 1
+                } else if state == 0 {
+                    0
+                } else {
+                    transitions[&(symb,failure)]
+                };
+                transitions.insert((symb,state), dest);
+            }
+            if state > 0 {
+                failure = transitions.get(&(expected,failure)).cloned().unwrap_or(0);
+            }
+        }
+        for &symb in alphabet.iter() {
+            transitions.insert((symb,last), last);
+        }
+        let finals = Some(last).into_iter().collect();
+        DFA::from_parts(transitions, 0, finals)
+    }
+
+    /// Returns a DFA over `alphabet` accepting the words containing at least one
+    /// of the patterns as a substring, along with the indices of the patterns
+    /// matched at each final state. The DFA is the Aho-Corasick automaton of the
+    /// patterns: the trie of the patterns whose failure links are folded in the
+    /// transitions so that it is complete over `alphabet`. The final states are
+    /// absorbing, so a final state is reached at the end of the first match and
+    /// its indices, in increasing order, are the patterns ending there. The
+    /// patterns using a symbol which is not in `alphabet` are never matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let (dfa, matches) = DFA::contains_any(&["he", "she", "hers"], &['e', 'h', 'r', 's']);
+    ///     assert!(dfa.test("rshers"));
+    ///     assert!(!dfa.test("hhrs"));
+    ///     let state = dfa.test_from(dfa.start(), "ssh").unwrap();
+    ///     let state = dfa.test_from(state, "e").unwrap();
+    ///     assert_eq!(matches[&state], vec![0, 1]);
+    /// }
+    /// ```
+    pub fn contains_any(patterns: &[&str], alphabet: &[char]) -> (DFA, HashMap<usize,Vec<usize>>) {
+        // the trie of the patterns, with the patterns ending at each state
+        let mut trie = HashMap::new();
+        let mut outputs = vec![Vec::new()];
+        for (i,pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for symb in pattern.chars() {
+                let next = outputs.len();
+                state = *trie.entry((symb,state)).or_insert(next);
+                if state == next {
+                    outputs.push(Vec::new());
+                }
+            }
+            outputs[state].push(i);
+        }
+        let mut alphabet = alphabet.to_vec();
+        alphabet.sort();
+        alphabet.dedup();
+        // the states are visited by increasing depth, so that the transitions of
+        // the failure state of a state are known before the state itself
+        let mut failures = vec![0; outputs.len()];
+        let mut visited = vec![0];
+        let mut transitions = HashMap::new();
+        let mut matches = HashMap::new();
+        let mut i = 0;
+        while i < visited.len() {
+            let state = visited[i];
+            i += 1;
+            if state != 0 {
+                let inherited = outputs[failures[state]].clone();
+                outputs[state].extend(inherited);
+                outputs[state].sort();
+                outputs[state].dedup();
+            }
+            if !outputs[state].is_empty() {
+                // the failure state of a non-final state is never final
+                for &symb in alphabet.iter() {
+                    transitions.insert((symb,state), state);
+                }
+                matches.insert(state, outputs[state].clone());
+                continue;
+            }
+            for &symb in alphabet.iter() {
+                let fallback = if state == 0 { 0 } else { transitions[&(symb,failures[state])] };
+                let dest = match trie.get(&(symb,state)) {
+                    Some(&child) => {
+                        failures[child] = fallback;
+                        visited.push(child);
+                        child
+                    },
+                    None => fallback,
+                };
+                transitions.insert((symb,state), dest);
+            }
+        }
+        let finals = matches.keys().cloned().collect();
+        (DFA::from_parts(transitions, 0, finals), matches)
+    }
+
+    /// Returns a DFA accepting exactly the given words. The DFA is the trie of the
+    /// words: it is acyclic but not necessarily minimal. The language of the DFA
+    /// is empty if there is no word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["then", "the", "this"]);
+    ///     assert!(dfa.test("the"));
+    ///     assert!(!dfa.test("th"));
+    /// }
+    /// ```
+    pub fn from_words(words: &[&str]) -> DFA {
+        let mut transitions = HashMap::new();
+        let mut finals = HashSet::new();
+        let mut next = 1;
+        for word in words {
+            let mut state = 0;
+            for symb in word.chars() {
+                state = *transitions.entry((symb,state)).or_insert(next);
+                if state == next {
+                    next += 1;
+                }
+            }
+            finals.insert(state);
+        }
+        DFA::from_parts(transitions, 0, finals)
+    }
+
+    /// Returns the one-state DFA accepting every word over `alphabet`, the empty
+    /// word included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::sigma_star(&['a', 'b']);
+    ///     assert!(dfa.test(""));
+    ///     assert!(dfa.test("abba"));
+    ///     assert!(!dfa.test("abc"));
+    /// }
+    /// ```
+    pub fn sigma_star(alphabet: &[char]) -> DFA {
+        let transitions = alphabet.iter().map(|&symb| ((symb,0),0)).collect();
+        let finals = Some(0).into_iter().collect();
+        DFA::from_parts(transitions, 0, finals)
+    }
+
+    /// Returns a DFA accepting no word. Its single state is not final and loops
+    /// on every symbol of `alphabet`, so that the alphabet of the DFA is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::empty_language(&['a', 'b']);
+    ///     assert!(dfa.is_empty());
+    ///     assert!(!dfa.test(""));
+    ///     assert!(!dfa.test("ab"));
+    /// }
+    /// ```
+    pub fn empty_language(alphabet: &[char]) -> DFA {
+        let transitions = alphabet.iter().map(|&symb| ((symb,0),0)).collect();
+        DFA::from_parts(transitions, 0, HashSet::new())
+    }
+
+    /// Returns the DFA accepting only the empty word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::epsilon_only();
+    ///     assert!(dfa.test(""));
+    ///     assert!(!dfa.test("a"));
+    /// }
+    /// ```
+    pub fn epsilon_only() -> DFA {
+        let finals = Some(0).into_iter().collect();
+        DFA::from_parts(HashMap::new(), 0, finals)
+    }
+
+    /// Returns a DFA accepting the words of the DFA and `word`, without
+    /// rebuilding the DFA (Daciuk's incremental construction). The states on the
+    /// path of the longest prefix of `word` are cloned from the first state with
+    /// several incoming transitions on, so that the other words are unchanged,
+    /// and the rest of `word` is added as a new path. The states of the path are
+    /// then replaced, from the end of `word` back to the starting state, by the
+    /// equivalent states with the same finality and the same transitions.
+    ///
+    /// If the DFA is minimal and acyclic, as a dictionary built by `from_words`
+    /// and minimized, the result is the minimal DFA of the extended dictionary.
+    /// Otherwise the language is extended the same way but the result may not be
+    /// minimal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["tap", "top"]).minimize();
+    ///     let dfa = dfa.insert_word("taps");
+    ///     assert!(dfa.accepts_exactly(&["tap", "top", "taps"]));
+    /// }
+    /// ```
+    pub fn insert_word(&self, word: &str) -> DFA {
+        let trimmed = self.trim();
+        let mut successors : HashMap<usize,BTreeMap<char,usize>> = HashMap::new();
+        let mut in_degree : HashMap<usize,usize> = HashMap::new();
+        for (&(symb,src),&dest) in trimmed.transitions.iter() {
+            successors.entry(src).or_default().insert(symb, dest);
+            *in_degree.entry(dest).or_insert(0) += 1;
+        }
+        let mut finals = trimmed.finals.clone();
+        let signature = |state: usize, successors: &HashMap<usize,BTreeMap<char,usize>>, finals: &HashSet<usize>| {
+            let nexts : Vec<_> = successors
+                .get(&state)
+                .map(|nexts| nexts.iter().map(|(&symb,&dest)| (symb,dest)).collect())
+                .unwrap_or_default();
+            (finals.contains(&state),nexts)
+        };
+        let states = trimmed.states();
+        let mut register : HashMap<_,_> = states
+            .iter()
+            .map(|&state| (signature(state, &successors, &finals),state))
+            .collect();
+        let mut next_id = states.iter().max().unwrap() + 1;
+        // Clones `state`, the clone being a new state with the same finality
+        // and the same transitions.
+        let mut clone = |state: usize, successors: &mut HashMap<usize,BTreeMap<_,_>>, finals: &mut HashSet<_>| {
+            let id = next_id;
+            next_id += 1;
+            let nexts = successors.get(&state).cloned().unwrap_or_default();
+            successors.insert(id, nexts);
+            if finals.contains(&state) {
+                finals.insert(id);
+            }
+            id
+        };
+        // the path of the longest prefix of `word`, the states of the path being
+        // unregistered as they are going to change; a starting state with
+        // incoming transitions is cloned as the loops lead back to it
+        let symbols : Vec<_> = word.chars().collect();
+        let mut confluent = in_degree.contains_key(&trimmed.start);
+        let mut path = vec![if confluent {
+            clone(trimmed.start, &mut successors, &mut finals)
+        } else {
+            register.remove(&signature(trimmed.start, &successors, &finals));
+            trimmed.start
+        }];
+        for &symb in symbols.iter() {
+            let src = path[path.len()-1];
+            let dest = match successors.get(&src).and_then(|nexts| nexts.get(&symb)) {
+                Some(&dest) => dest,
+                None => break,
+            };
+            confluent = confluent || in_degree[&dest] > 1;
+            let dest = if confluent {
+                let id = clone(dest, &mut successors, &mut finals);
+                // can't fail because `src` has a transition on `symb`
+                successors.get_mut(&src).unwrap().insert(symb, id);
+                id
+            } else {
+                register.remove(&signature(dest, &successors, &finals));
+                dest
+            };
+            path.push(dest);
+        }
+        // the rest of `word`
+        for &symb in symbols[path.len()-1..].iter() {
+            let src = path[path.len()-1];
+            let id = next_id;
+            next_id += 1;
+            successors.entry(src).or_default().insert(symb, id);
+            path.push(id);
+        }
+        finals.insert(path[path.len()-1]);
+        for i in (1..path.len()).rev() {
+            let state = path[i];
+            let key = signature(state, &successors, &finals);
+            match register.get(&key).cloned() {
+                Some(equivalent) => {
+                    // can't fail because `path[i-1]` has a transition to `state`
+                    successors.get_mut(&path[i-1]).unwrap().insert(symbols[i-1], equivalent);
+                    successors.remove(&state);
+                    finals.remove(&state);
+                },
+                None => {
+                    register.insert(key, state);
+                },
+            }
+        }
+        let transitions = successors
+            .into_iter()
+            .flat_map(|(src,nexts)| nexts.into_iter().map(move |(symb,dest)| ((symb,src),dest)))
+            .collect();
+        DFA::with_alphabet(transitions, path[0], finals, self.alphabet.clone()).renumbered()
+    }
+
+    // Builds a DFA from its parts, without declared alphabet. No final state is
+    // required, the language may be empty.
+    pub(crate) fn from_parts(transitions: HashMap<(char,usize),usize>, start: usize, finals: HashSet<usize>) -> DFA {
+        DFA::with_alphabet(transitions, start, finals, None)
+    }
+
+    // Builds a DFA from its parts and its declared alphabet. This is the only
+    // place where a DFA is built, the caches being empty.
+    fn with_alphabet(transitions: HashMap<(char,usize),usize>,
+                     start: usize,
+                     finals: HashSet<usize>,
+                     alphabet: Option<HashSet<char>>) -> DFA {
+        DFA{transitions, start, finals, alphabet, complete: OnceCell::new(), reversed: OnceCell::new()}
+    }
+
+    // Returns the DFA with `alphabet` as declared alphabet, the caches being
+    // emptied since the completed form depends on the alphabet.
+    fn declaring(self, alphabet: Option<HashSet<char>>) -> DFA {
+        DFA::with_alphabet(self.transitions, self.start, self.finals, alphabet)
+    }
+
+    /// Writes the DFA in the Graphviz DOT format. The states and the transitions
+    /// are written in increasing order, so the output is deterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let mut out = Vec::new();
+    ///     dfa.write_dot(&mut out).unwrap();
+    ///     assert!(String::from_utf8(out).unwrap().contains("0 -> 1 [label=\"a\"];"));
+    /// }
+    /// ```
+    pub fn write_dot<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        dot::write_dot(w, self.start, &self.finals, self.states(), self.edges())
+    }
+
+    /// Returns the DFA in the Graphviz DOT format (see `write_dot`).
+    pub fn to_dot(&self) -> String {
+        let mut out = Vec::new();
+        // can't fail because writing in a vector never fails
+        self.write_dot(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Returns `true` if the DFA written by `to_dot` and read back by
+    /// `DFAReader::new_from_dot` accepts the same language. Returns `false` if
+    /// the reader rejects the output, e.g. for a DFA without final state which
+    /// the builder does not accept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     assert!(DFA::from_words(&["a,b", "a-c", "\\\"\n"]).reparse_roundtrip_ok());
+    ///     assert!(!DFA::empty_language(&['a']).reparse_roundtrip_ok());
+    /// }
+    /// ```
+    pub fn reparse_roundtrip_ok(&self) -> bool {
+        match DFAReader::new_from_dot(&self.to_dot()) {
+            // the minimal DFAs of two equivalent DFAs are isomorphic
+            Ok(dfa) => dfa.minimize().is_isomorphic(&self.minimize()),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the DFA as a Mermaid `stateDiagram-v2`, which can be embedded in
+    /// Markdown documents. The states are named `s0`, `s1`,... and labelled by
+    /// their number. The starting state is marked by `[*] -->` and the final
+    /// states have the class `final`, drawn with a thick border. The symbols of
+    /// the transitions between the same states are coalesced into ranges as in
+    /// `to_dot`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // [a-c]x
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 1)
+    ///         .add_transition('c', 0, 1)
+    ///         .add_transition('x', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let mermaid = dfa.to_mermaid();
+    ///     assert!(mermaid.starts_with("stateDiagram-v2\n"));
+    ///     assert!(mermaid.contains("[*] --> s0\n"));
+    ///     assert!(mermaid.contains("s0 --> s1: a-c\n"));
+    ///     assert!(mermaid.contains("class s2 final\n"));
+    /// }
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        render::mermaid(self.start, &self.finals, self.states(), self.edges())
+    }
+
+    /// Returns the transitions of the DFA in the CSV format, with the header
+    /// `src,symbol,dest` and one row per transition, sorted by source state and
+    /// symbol. The symbol is always quoted, an inner quote being doubled, so that
+    /// commas, quotes and line breaks are read back as symbols. See
+    /// `states_to_csv` for the starting and final states.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition(',', 1, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.to_csv(), "src,symbol,dest\n0,\"a\",1\n1,\",\",1\n");
+    /// }
+    /// ```
+    pub fn to_csv(&self) -> String {
+        render::csv(self.edges())
+    }
+
+    /// Returns the states of the DFA in the CSV format, with the header
+    /// `state,start,final` and one row per state in increasing order, telling
+    /// whether the state is the starting state and whether it is final. This is
+    /// the companion of `to_csv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["a"]);
+    ///     assert_eq!(dfa.states_to_csv(), "state,start,final\n0,true,false\n1,false,true\n");
+    /// }
+    /// ```
+    pub fn states_to_csv(&self) -> String {
+        let mut states : Vec<_> = self.states().into_iter().collect();
+        states.sort();
+        let mut csv = "state,start,final\n".to_string();
+        for state in states {
+            csv.push_str(&format!("{},{},{}\n", state, state == self.start, self.finals.contains(&state)));
+        }
+        csv
+    }
+
+    /// Returns a regular expression describing the language of the DFA, built by
+    /// state elimination on the trimmed DFA. The expression uses `|` for the
+    /// union, the juxtaposition for the concatenation and `*` for the Kleene
+    /// star; `ε` denotes the empty word and `∅` the empty language. A symbol among
+    /// `|*()\ε∅` is escaped by a backslash. The expression is correct but not
+    /// minimal: its size depends on the order in which the states are eliminated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 1, 2)
+    ///         .add_transition('d', 2, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.to_regex(), "a(b|c)d*");
+    ///     assert_eq!(DFA::epsilon_only().to_regex(), "ε");
+    ///     assert_eq!(DFA::empty_language(&['a']).to_regex(), "∅");
+    /// }
+    /// ```
+    pub fn to_regex(&self) -> String {
+        if self.is_empty() {
+            return "∅".to_string();
+        }
+        let dfa = self.trim();
+        let mut states : Vec<_> = dfa.states().into_iter().collect();
+        states.sort();
+        // new starting and final states, linked to the DFA by epsilon edges
+        let start = states[states.len()-1] + 1;
+        let end = start + 1;
+        let mut edges = HashMap::new();
+        edges.insert((start,dfa.start), Regex::Epsilon);
+        for &state in dfa.finals.iter() {
+            edges.insert((state,end), Regex::Epsilon);
+        }
+        let mut transitions : Vec<_> = dfa.transitions
+            .iter()
+            .map(|(&(symb,src),&dest)| (src,dest,symb))
+            .collect();
+        transitions.sort();
+        for (src,dest,symb) in transitions {
+            add_edge(&mut edges, src, dest, Regex::Symbol(symb));
+        }
+        for &state in states.iter() {
+            let self_loop = edges
+                .remove(&(state,state))
+                .map(Regex::star)
+                .unwrap_or(Regex::Epsilon);
+            let mut incoming : Vec<_> = edges
+                .keys()
+                .filter(|&&(_,dest)| dest == state)
+                .map(|&(src,_)| src)
+                .collect();
+            incoming.sort();
+            let mut outgoing : Vec<_> = edges
+                .keys()
+                .filter(|&&(src,_)| src == state)
+                .map(|&(_,dest)| dest)
+                .collect();
+            outgoing.sort();
+            let incoming : Vec<_> = incoming
+                .into_iter()
+                .map(|src| (src,edges.remove(&(src,state)).unwrap()))
+                .collect();
+            let outgoing : Vec<_> = outgoing
+                .into_iter()
+                .map(|dest| (dest,edges.remove(&(state,dest)).unwrap()))
+                .collect();
+            for &(src,ref left) in incoming.iter() {
+                for &(dest,ref right) in outgoing.iter() {
+                    let path = left.clone().concat(self_loop.clone()).concat(right.clone());
+                    add_edge(&mut edges, src, dest, path);
+                }
+            }
+        }
+        // can't fail because a final state is reachable from the starting state
+        edges[&(start,end)].to_string()
+    }
+
+    /// Returns the destination of the transition from `state` with `symb`, if the
+    /// transition exists.
+    pub fn next_state(&self, state: usize, symb: char) -> Option<usize> {
+        self.transitions.get(&(symb,state)).cloned()
+    }
+
+    /// Returns the starting state of the DFA.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the final states of the DFA.
+    pub fn finals(&self) -> &HashSet<usize> {
+        &self.finals
+    }
+
+    /// Returns the alphabet declared at build time, if any (see
+    /// `DFABuilding::declare_alphabet`).
+    pub fn declared_alphabet(&self) -> Option<&HashSet<char>> {
+        self.alphabet.as_ref()
+    }
+
+    /// Returns the final states of the DFA in increasing order.
+    pub fn finals_sorted(&self) -> Vec<usize> {
+        let mut finals : Vec<_> = self.finals.iter().cloned().collect();
+        finals.sort();
+        finals
+    }
+
+    /// Returns the transitions of the DFA as `(symb,src,dest)` triples, sorted by
+    /// source state and then by symbol.
+    pub fn transitions(&self) -> Vec<(char,usize,usize)> {
+        let mut transitions : Vec<_> = self.transitions
+            .iter()
+            .map(|(&(symb,src),&dest)| (symb,src,dest))
+            .collect();
+        transitions.sort_by_key(|&(symb,src,_)| (src,symb));
+        transitions
+    }
+
+    /// Calls `f` with the symbol, the source state and the destination state of
+    /// each transition, in the order of `transitions`. The transitions are not
+    /// collected: only the source states and the symbols are sorted, and the
+    /// transition of each pair is looked up in the table. The hashtable of the
+    /// transitions has no order, so the deterministic order can't be free of
+    /// allocation, but the memory used is in the number of states and symbols
+    /// instead of the number of transitions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab", "b"]);
+    ///     let mut symbols = String::new();
+    ///     dfa.visit_transitions(|symb, _, _| symbols.push(symb));
+    ///     assert_eq!(symbols.len(), dfa.transitions().len());
+    /// }
+    /// ```
+    pub fn visit_transitions<F: FnMut(char,usize,usize)>(&self, mut f: F) {
+        let mut sources : Vec<_> = self.transitions
+            .keys()
+            .map(|&(_,src)| src)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        sources.sort();
+        let mut alphabet : Vec<_> = self.alphabet().into_iter().collect();
+        alphabet.sort();
+        for &src in sources.iter() {
+            for &symb in alphabet.iter() {
+                if let Some(&dest) = self.transitions.get(&(symb,src)) {
+                    f(symb, src, dest);
+                }
+            }
+        }
+    }
+
+    /// Returns the number of transitions leaving `state`.
+    pub fn out_degree(&self, state: usize) -> usize {
+        self.transitions.keys().filter(|&&(_,src)| src == state).count()
+    }
+
+    /// Returns the number of transitions entering `state`.
+    pub fn in_degree(&self, state: usize) -> usize {
+        self.transitions.values().filter(|&&dest| dest == state).count()
+    }
+
+    /// Returns the transitions entering `state` as `(symb,src)` pairs, sorted by
+    /// symbol and then by source state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)c
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(3)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 2)
+    ///         .add_transition('c', 1, 3)
+    ///         .add_transition('c', 2, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.predecessors(3), vec![('c', 1), ('c', 2)]);
+    /// }
+    /// ```
+    pub fn predecessors(&self, state: usize) -> Vec<(char,usize)> {
+        let mut predecessors : Vec<_> = self.transitions.iter()
+            .filter(|&(_, &dest)| dest == state)
+            .map(|(&(symb,src), _)| (symb,src))
+            .collect();
+        predecessors.sort();
+        predecessors
+    }
+
+    /// Returns the transitions whose source state is also the destination state
+    /// as `(state,symb)` pairs, sorted by state and then by symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a*b
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.self_loops(), vec![(0, 'a')]);
+    /// }
+    /// ```
+    pub fn self_loops(&self) -> Vec<(usize,char)> {
+        let mut loops : Vec<_> = self.transitions
+            .iter()
+            .filter(|&(&(_,src),&dest)| src == dest)
+            .map(|(&(symb,src),_)| (src,symb))
+            .collect();
+        loops.sort();
+        loops
+    }
+
+    /// Returns the symbols of the transitions between each pair of states, keyed
+    /// by `(src,dest)` pairs. The symbols of each pair are sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b|c)d
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('c', 0, 1)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 1)
+    ///         .add_transition('d', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let groups = dfa.grouped_transitions();
+    ///     assert_eq!(groups[&(0, 1)], vec!['a', 'b', 'c']);
+    ///     assert_eq!(groups[&(1, 2)], vec!['d']);
+    /// }
+    /// ```
+    pub fn grouped_transitions(&self) -> HashMap<(usize,usize),Vec<char>> {
+        let mut groups = HashMap::new();
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            groups.entry((src,dest)).or_insert_with(Vec::new).push(symb);
+        }
+        for symbs in groups.values_mut() {
+            symbs.sort();
+        }
+        groups
+    }
+
+    /// Returns the strongly connected components of the transition graph and the
+    /// edges of the condensation, i.e. the acyclic graph whose vertices are the
+    /// components. An edge `(i,j)` means that a transition goes from a state of
+    /// the `i`-th component to a state of the `j`-th one. The components are
+    /// computed with Tarjan's algorithm and are listed in topological order,
+    /// the edges are sorted. A component contains a cycle if it has several
+    /// states or if its single state has a self loop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a(bc)*d
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(3)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 1)
+    ///         .add_transition('d', 1, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let (components,edges) = dfa.condensation();
+    ///     assert_eq!(components.len(), 3);
+    ///     assert!(components[1].contains(&1) && components[1].contains(&2));
+    ///     assert_eq!(edges, vec![(0, 1), (1, 2)]);
+    /// }
+    /// ```
+    pub fn condensation(&self) -> (Vec<HashSet<usize>>,Vec<(usize,usize)>) {
+        let successors = self.sorted_successors();
+        let no_successor = Vec::new();
+        let mut states : Vec<_> = self.states().into_iter().collect();
+        states.sort();
+        let mut index = HashMap::new();
+        let mut lowlink = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut components = Vec::new();
+        for &root in states.iter() {
+            if index.contains_key(&root) {
+                continue;
+            }
+            // iterative DFS: each call is a state and the position of its next
+            // successor to visit
+            let mut calls = vec![(root,0)];
+            while let Some((state,next)) = calls.pop() {
+                if next == 0 {
+                    let id = index.len();
+                    index.insert(state, id);
+                    lowlink.insert(state, id);
+                    stack.push(state);
+                    on_stack.insert(state);
+                }
+                if let Some(&(_,dest)) = successors.get(&state).unwrap_or(&no_successor).get(next) {
+                    calls.push((state,next+1));
+                    if !index.contains_key(&dest) {
+                        calls.push((dest,0));
+                    } else if on_stack.contains(&dest) && index[&dest] < lowlink[&state] {
+                        lowlink.insert(state, index[&dest]);
+                    }
+                    continue;
+                }
+                if lowlink[&state] == index[&state] {
+                    let mut component = HashSet::new();
+                    loop {
+                        let top = stack.pop().unwrap();
+                        on_stack.remove(&top);
+                        component.insert(top);
+                        if top == state {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+                if let Some(&(parent,_)) = calls.last() {
+                    if lowlink[&state] < lowlink[&parent] {
+                        lowlink.insert(parent, lowlink[&state]);
+                    }
+                }
+            }
+        }
+        // Tarjan's algorithm finds the components in reverse topological order
+        components.reverse();
+        let component_of : HashMap<_,_> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i,component)| component.iter().map(move |&state| (state,i)))
+            .collect();
+        let mut edges : Vec<_> = self.transitions
+            .iter()
+            .map(|(&(_,src),dest)| (component_of[&src],component_of[dest]))
+            .filter(|&(src,dest)| src != dest)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        edges.sort();
+        (components,edges)
+    }
+
+    /// Returns at most `max_paths` simple paths, i.e. without repeated state,
+    /// from the starting state to a final state. A path is the sequence of its
+    /// states, the starting state included. The paths are found by a DFS that
+    /// follows the transitions by increasing symbol, so they come in this order.
+    /// The DFS only visits the useful states (see `trim`), so it does not wander
+    /// in the parts of the DFA that can't reach a final state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (ab|c)+
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 0, 2)
+    ///         .add_transition('a', 2, 1)
+    ///         .add_transition('c', 2, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.simple_accepting_paths(10), vec![vec![0, 1, 2], vec![0, 2]]);
+    /// }
+    /// ```
+    pub fn simple_accepting_paths(&self, max_paths: usize) -> Vec<Vec<usize>> {
+        // the distinct useful destinations of each state, by increasing symbol
+        let targets : HashMap<_,Vec<_>> = self.trim().sorted_successors()
+            .into_iter()
+            .map(|(src,nexts)| {
+                let mut dests = Vec::new();
+                for (_,dest) in nexts {
+                    if !dests.contains(&dest) {
+                        dests.push(dest);
+                    }
+                }
+                (src,dests)
+            })
+            .collect();
+        let no_target = Vec::new();
+        let mut paths = Vec::new();
+        let mut path = vec![self.start];
+        // position of the next destination to visit for each state of the path
+        let mut positions = vec![0];
+        if self.finals.contains(&self.start) {
+            paths.push(path.clone());
+        }
+        while paths.len() < max_paths {
+            let state = match path.last() {
+                Some(&state) => state,
+                None => break,
+            };
+            let dests = targets.get(&state).unwrap_or(&no_target);
+            let position = positions.last_mut().unwrap();
+            if *position == dests.len() {
+                path.pop();
+                positions.pop();
+                continue;
+            }
+            let dest = dests[*position];
+            *position += 1;
+            if path.contains(&dest) {
+                continue;
+            }
+            path.push(dest);
+            positions.push(0);
+            if self.finals.contains(&dest) {
+                paths.push(path.clone());
+            }
+        }
+        paths.truncate(max_paths);
+        paths
+    }
+
+    /// Returns the number of the states by role, as a tuple
+    /// `(non_final_reachable,final_reachable,unreachable)`. The reachable states
+    /// are the states reachable from the starting state, which is itself
+    /// reachable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 2, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.role_counts(), (1, 1, 1));
+    /// }
+    /// ```
+    pub fn role_counts(&self) -> (usize,usize,usize) {
+        let reachable = self.reachable_states();
+        let finals = reachable.iter().filter(|state| self.finals.contains(state)).count();
+        (reachable.len() - finals, finals, self.states().len() - reachable.len())
+    }
+
+    /// Returns a summary of the size of the DFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (abc)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 2, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let stats = dfa.stats();
+    ///     assert_eq!(stats.state_count, 3);
+    ///     assert_eq!(stats.transition_count, 3);
+    ///     assert_eq!(stats.final_count, 1);
+    ///     assert_eq!(stats.alphabet_size, 3);
+    ///     assert!(!stats.is_complete);
+    ///     assert_eq!(format!("{}", stats), "states: 3, transitions: 3, finals: 1, alphabet: 3, complete: false");
+    /// }
+    /// ```
+    pub fn stats(&self) -> DFAStats {
+        let states = self.states();
+        let alphabet = self.alphabet();
+        DFAStats {
+            state_count      : states.len(),
+            transition_count : self.transitions.len(),
+            final_count      : self.finals.len(),
+            alphabet_size    : alphabet.len(),
+            is_complete      : self.transitions.len() == states.len()*alphabet.len(),
+        }
+    }
+
+    /// Returns an equivalent DFA that only keeps the useful states, i.e. the
+    /// states that are reachable from the starting state and from which a final
+    /// state is reachable. The starting state is always kept, even when the
+    /// language of the DFA is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 0, 2)
+    ///         .add_transition('a', 3, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(!dfa.is_trim());
+    ///     let trimmed = dfa.trim();
+    ///     assert!(trimmed.is_trim());
+    ///     assert_eq!(trimmed.transitions(), vec![('a', 0, 1)]);
+    /// }
+    /// ```
+    pub fn trim(&self) -> DFA {
+        let useful = self.useful_states();
+        let transitions = self.transitions
+            .iter()
+            .filter(|&(&(_,src),dest)| useful.contains(&src) && useful.contains(dest))
+            .map(|(&tr,&dest)| (tr,dest))
+            .collect();
+        let finals = self.finals.intersection(&useful).cloned().collect();
+        DFA::with_alphabet(transitions, self.start, finals, self.alphabet.clone())
+    }
+
+    /// Returns `true` if every state of the DFA is reachable from the starting
+    /// state and can reach a final state, i.e. if `trim` would return the same
+    /// DFA.
+    pub fn is_trim(&self) -> bool {
+        let useful = self.useful_states();
+        self.states().iter().all(|state| useful.contains(state))
+    }
+
+    /// Returns `true` if the two DFAs have the same structure up to a renaming of
+    /// their states. Only the states reachable from the starting states are
+    /// considered. Unlike a language equivalence, this checks that the two DFAs
+    /// are the same graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (ab)*
+    ///     let dfa1 = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let dfa2 = DFABuilder::new()
+    ///         .add_start(7)
+    ///         .add_final(7)
+    ///         .add_transition('a', 7, 3)
+    ///         .add_transition('b', 3, 7)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa1.is_isomorphic(&dfa2));
+    /// }
+    /// ```
+    pub fn is_isomorphic(&self, other: &DFA) -> bool {
+        self.canonical_form() == other.canonical_form()
+    }
+
+    /// Returns `true` if the language of the DFA is exactly the given finite set
+    /// of words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a(b|c)
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa.accepts_exactly(&["ac", "ab"]));
+    ///     assert!(!dfa.accepts_exactly(&["ab"]));
+    /// }
+    /// ```
+    pub fn accepts_exactly(&self, words: &[&str]) -> bool {
+        // the minimal DFAs of two equivalent DFAs are equal
+        self.minimize() == DFA::from_words(words).minimize()
+    }
+
+    // Numbers the reachable states in BFS order, visiting the transitions of a
+    // state by increasing symbol. For each state, in this order, the finality and
+    // the sorted transitions are returned with the renumbered destinations.
+    fn canonical_form(&self) -> Vec<(bool,Vec<(char,usize)>)> {
+        let successors = self.sorted_successors();
+        let (order,ids) = self.bfs_order();
+        order
+            .iter()
+            .map(|state| {
+                let nexts = successors
+                    .get(state)
+                    .map(|nexts| nexts.iter().map(|&(symb,dest)| (symb,ids[&dest])).collect())
+                    .unwrap_or_default();
+                (self.finals.contains(state),nexts)
+            })
+            .collect()
+    }
+
+    /// Returns `Some(word)` if the language of the DFA contains exactly one word,
+    /// and `None` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .add_transition('c', 1, 3)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.single_word(), Some("ab".to_string()));
+    /// }
+    /// ```
+    pub fn single_word(&self) -> Option<String> {
+        // The useful part of the DFA must be a single path from the starting
+        // state to a final state.
+        let trimmed = self.trim();
+        let mut word = String::new();
+        let mut visited = HashSet::new();
+        let mut state = trimmed.start;
+        loop {
+            if !visited.insert(state) {
+                return None;
+            }
+            let mut nexts = trimmed.transitions.iter().filter(|&(&(_,src),_)| src == state);
+            match (nexts.next(),nexts.next()) {
+                (None,_) => {
+                    return if trimmed.finals.contains(&state) { Some(word) } else { None };
+                },
+                (Some((&(symb,_),&dest)),None) if !trimmed.finals.contains(&state) => {
+                    word.push(symb);
+                    state = dest;
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// Returns `true` if the language of the DFA is its own reverse, i.e. if the
+    /// reverse of each accepted word is accepted. The minimal DFAs being
+    /// canonical, the DFA is compared with `reverse_dfa` once minimized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     assert!(DFA::from_words(&["ab", "ba", "aba"]).is_reversal_closed());
+    ///     assert!(!DFA::from_words(&["ab"]).is_reversal_closed());
+    /// }
+    /// ```
+    pub fn is_reversal_closed(&self) -> bool {
+        // the minimal DFA of the empty language keeps the loops of its
+        // starting state, unlike the reverse DFA
+        self.is_empty() || self.minimize() == self.reverse_dfa()
+    }
+
+    /// Returns `Some(n)` if every accepted word has the length `n`, and `None`
+    /// otherwise or if the language is empty. Once the DFA is trimmed, the
+    /// words have the same length iff each state is at a fixed distance from
+    /// the starting state, i.e. each transition goes from a level to the next
+    /// one, and all the final states are on the same level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     assert_eq!(DFA::from_words(&["abc", "bca"]).fixed_length(), Some(3));
+    ///     assert_eq!(DFA::from_words(&["abc", "bc"]).fixed_length(), None);
+    /// }
+    /// ```
+    pub fn fixed_length(&self) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let trimmed = self.trim();
+        let successors = trimmed.sorted_successors();
+        let mut levels = HashMap::new();
+        levels.insert(trimmed.start, 0);
+        let mut stack = vec![trimmed.start];
+        while let Some(state) = stack.pop() {
+            let level = levels[&state] + 1;
+            for &(_,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                match levels.get(&dest) {
+                    Some(&l) if l != level => return None,
+                    Some(_) => (),
+                    None => {
+                        levels.insert(dest, level);
+                        stack.push(dest);
+                    },
+                }
+            }
+        }
+        let mut lengths = trimmed.finals.iter().map(|state| levels[state]);
+        let length = lengths.next()?;
+        if lengths.all(|l| l == length) { Some(length) } else { None }
+    }
+
+    /// Returns `true` if no accepted word is a proper prefix of another accepted
+    /// word, i.e. if no final state reaches a final state through a non-empty
+    /// path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     assert!(DFA::from_words(&["0", "10", "11"]).is_prefix_free());
+    ///     assert!(!DFA::from_words(&["1", "10"]).is_prefix_free());
+    /// }
+    /// ```
+    pub fn is_prefix_free(&self) -> bool {
+        let trimmed = self.trim();
+        let mut successors = HashMap::new();
+        for (&(_,src),&dest) in trimmed.transitions.iter() {
+            successors.entry(src).or_insert_with(Vec::new).push(dest);
+        }
+        trimmed.finals.iter().all(|state| {
+            let nexts = successors.get(state).cloned().unwrap_or_default();
+            closure(nexts, &successors).is_disjoint(&trimmed.finals)
+        })
+    }
+
+    /// Returns the words of length `len`, in characters, accepted by the DFA, in
+    /// increasing order. Unlike `words`, the shorter words are not enumerated:
+    /// the states which can reach a final state in exactly `k` steps are first
+    /// computed for each `k` up to `len`, so that only the prefixes of accepted
+    /// words are extended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["a", "ba", "ab", "abc"]);
+    ///     assert_eq!(dfa.words_of_length(2), vec!["ab", "ba"]);
+    ///     assert!(dfa.words_of_length(4).is_empty());
+    /// }
+    /// ```
+    pub fn words_of_length(&self, len: usize) -> Vec<String> {
+        let successors = self.sorted_successors();
+        // alive[k] holds the states reaching a final state in exactly k steps
+        let mut alive = vec![self.finals.clone()];
+        for k in 0..len {
+            let states = self.states()
+                .into_iter()
+                .filter(|state| successors
+                    .get(state)
+                    .into_iter()
+                    .flat_map(|nexts| nexts.iter())
+                    .any(|&(_,dest)| alive[k].contains(&dest)))
+                .collect();
+            alive.push(states);
+        }
+        let mut words = Vec::new();
+        if !alive[len].contains(&self.start) {
+            return words;
+        }
+        // depth-first, the symbols being pushed in decreasing order so that the
+        // words are found in increasing order
+        let mut stack = vec![(String::new(),self.start,len)];
+        while let Some((word,state,remaining)) = stack.pop() {
+            if remaining == 0 {
+                words.push(word);
+                continue;
+            }
+            for &(symb,dest) in successors[&state].iter().rev() {
+                if alive[remaining-1].contains(&dest) {
+                    let mut word = word.clone();
+                    word.push(symb);
+                    stack.push((word,dest,remaining-1));
+                }
+            }
+        }
+        words
+    }
+
+    /// Returns the words accepted by the DFA whose length is at most `max_len`, in
+    /// shortlex order (by length, then by lexicographic order).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (ab)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.words(4), vec!["", "ab", "abab"]);
+    /// }
+    /// ```
+    pub fn words(&self, max_len: usize) -> Vec<String> {
+        // can't fail because the frontier is not bounded
+        self.words_bfs_config(max_len, usize::MAX).unwrap()
+    }
+
+    /// Returns an iterator over the words accepted by the DFA in shortlex order,
+    /// the symbols being compared by their position in `order` instead of
+    /// their natural order. The iterator is lazy and never ends if the
+    /// language is infinite. The symbols repeated in `order` keep their first
+    /// position.
+    ///
+    /// # Errors
+    ///
+    /// Return an `EnumError::MissingSymbol` with the smallest symbol of the
+    /// alphabet of the DFA which is not in `order`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|1)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('1', 0, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let words : Vec<_> = dfa.iter_language_ordered(&['a', '1']).unwrap().take(4).collect();
+    ///     assert_eq!(words, vec!["", "a", "1", "aa"]);
+    ///     assert!(dfa.iter_language_ordered(&['a']).is_err());
+    /// }
+    /// ```
+    pub fn iter_language_ordered(&self, order: &[char]) -> result::Result<impl Iterator<Item=String>,EnumError> {
+        let mut ranks = HashMap::new();
+        for (rank,&symb) in order.iter().enumerate() {
+            ranks.entry(symb).or_insert(rank);
+        }
+        let mut missing : Vec<_> = self.alphabet().into_iter().filter(|symb| !ranks.contains_key(symb)).collect();
+        missing.sort();
+        if let Some(&symb) = missing.first() {
+            return Err(EnumError::MissingSymbol(symb));
+        }
+        let trimmed = self.trim();
+        let mut successors = trimmed.sorted_successors();
+        for nexts in successors.values_mut() {
+            nexts.sort_by_key(|&(symb,_)| ranks[&symb]);
+        }
+        // the words of the current length, in order, and the position of the
+        // next one to look at; the prefixes of the trim DFA all lead to a word,
+        // unless the language is empty
+        let mut frontier = if trimmed.finals.is_empty() {
+            Vec::new()
+        } else {
+            vec![(String::new(),trimmed.start)]
+        };
+        let mut i = 0;
+        let finals = trimmed.finals;
+        Ok(::std::iter::from_fn(move || loop {
+            if i == frontier.len() {
+                let mut next = Vec::new();
+                for (word,state) in frontier.drain(..) {
+                    for &(symb,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                        let mut word = word.clone();
+                        word.push(symb);
+                        next.push((word,dest));
+                    }
+                }
+                if next.is_empty() {
+                    return None;
+                }
+                frontier = next;
+                i = 0;
+            }
+            i += 1;
+            if finals.contains(&frontier[i-1].1) {
+                return Some(frontier[i-1].0.clone());
+            }
+        }))
+    }
+
+    /// Same as `words` but the enumeration fails with
+    /// `EnumError::FrontierExceeded` as soon as more than `max_frontier` words of
+    /// the same length are pending during the BFS. The prefixes which can not
+    /// lead to a final state are never pending.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(dfa.words_bfs_config(2, 4).unwrap().len(), 7);
+    ///     assert_eq!(dfa.words_bfs_config(3, 4), Err(EnumError::FrontierExceeded(8)));
+    /// }
+    /// ```
+    pub fn words_bfs_config(&self, max_len: usize, max_frontier: usize) -> result::Result<Vec<String>,EnumError> {
+        let trimmed = self.trim();
+        let successors = trimmed.sorted_successors();
+        let mut words = Vec::new();
+        let mut frontier = vec![(String::new(),trimmed.start)];
+        let mut len = 0;
+        loop {
+            if frontier.len() > max_frontier {
+                return Err(EnumError::FrontierExceeded(frontier.len()));
+            }
+            words.extend(frontier
+                .iter()
+                .filter(|&&(_,state)| trimmed.finals.contains(&state))
+                .map(|(word,_)| word.clone()));
+            if len == max_len || frontier.is_empty() {
+                return Ok(words);
+            }
+            let mut next = Vec::new();
+            for (word,state) in frontier {
+                for &(symb,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                    let mut word = word.clone();
+                    word.push(symb);
+                    next.push((word,dest));
+                }
+            }
+            frontier = next;
+            len += 1;
+        }
+    }
+
+    /// Returns the structural differences between the DFA and `other`, which is
+    /// a debugging aid rather than a comparison of the languages.
+    ///
+    /// The states of the two DFAs are first aligned, on a best-effort basis, by
+    /// running both DFAs from their starting states on the common symbols: two
+    /// states reached by the same word are aligned unless one of them is already
+    /// aligned with another state. A transition is then common to both DFAs if
+    /// its source and destination states are aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa1 = DFA::from_words(&["ab", "c"]);
+    ///     let dfa2 = DFA::from_words(&["c", "ab", "ad"]);
+    ///     let diff = dfa1.diff(&dfa2);
+    ///     assert!(diff.removed_transitions.is_empty());
+    ///     assert_eq!(diff.added_transitions, vec![('d', 2, 4)]);
+    ///     assert!(diff.removed_finals.is_empty());
+    ///     assert_eq!(diff.added_finals, vec![4]);
+    /// }
+    /// ```
+    pub fn diff(&self, other: &DFA) -> AutomatonDiff {
+        let successors = self.sorted_successors();
+        // states of `other` aligned with the states of the DFA, and conversely
+        let mut aligned = HashMap::new();
+        let mut reverse = HashMap::new();
+        aligned.insert(other.start, self.start);
+        reverse.insert(self.start, other.start);
+        let mut stack = vec![(self.start,other.start)];
+        while let Some((state1,state2)) = stack.pop() {
+            for &(symb,dest1) in successors.get(&state1).into_iter().flat_map(|nexts| nexts.iter()) {
+                if let Some(&dest2) = other.transitions.get(&(symb,state2)) {
+                    if !aligned.contains_key(&dest2) && !reverse.contains_key(&dest1) {
+                        aligned.insert(dest2, dest1);
+                        reverse.insert(dest1, dest2);
+                        stack.push((dest1,dest2));
+                    }
+                }
+            }
+        }
+        let translated : HashSet<_> = other.transitions
+            .iter()
+            .filter_map(|(&(symb,src),dest)| match (aligned.get(&src),aligned.get(dest)) {
+                (Some(&src),Some(&dest)) => Some((symb,src,dest)),
+                _ => None,
+            })
+            .collect();
+        let removed_transitions = self.transitions()
+            .into_iter()
+            .filter(|tr| !translated.contains(tr))
+            .collect();
+        let added_transitions = other.transitions()
+            .into_iter()
+            .filter(|&(symb,src,dest)| match (aligned.get(&src),aligned.get(&dest)) {
+                (Some(&src),Some(&dest)) => self.transitions.get(&(symb,src)) != Some(&dest),
+                _ => true,
+            })
+            .collect();
+        let mut removed_finals : Vec<_> = self.finals
+            .iter()
+            .filter(|state| !reverse.get(state).map_or(false, |s| other.finals.contains(s)))
+            .cloned()
+            .collect();
+        removed_finals.sort();
+        let mut added_finals : Vec<_> = other.finals
+            .iter()
+            .filter(|state| !aligned.get(state).map_or(false, |s| self.finals.contains(s)))
+            .cloned()
+            .collect();
+        added_finals.sort();
+        AutomatonDiff {
+            removed_transitions,
+            added_transitions,
+            removed_finals,
+            added_finals,
+        }
+    }
+
+    /// Returns the product of the two DFAs, in which a transition advances both
+    /// DFAs on the same symbol. The product is built over the union of the two
+    /// alphabets, a missing transition of one of the DFAs leading to a trap
+    /// state of the product, so the product is complete. Only the reachable
+    /// pairs of states are built and `finality` tells which pairs are final.
+    /// The product is not minimized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let a = DFA::from_words(&["a", "b"]);
+    ///     let b = DFA::from_words(&["b", "c"]);
+    ///     let both = a.synchronized_product(&b, ProductFinality::Both);
+    ///     assert!(both.minimize().accepts_exactly(&["b"]));
+    ///     let either = a.synchronized_product(&b, ProductFinality::Either);
+    ///     assert!(either.minimize().accepts_exactly(&["a", "b", "c"]));
+    /// }
+    /// ```
+    pub fn synchronized_product(&self, other: &DFA, finality: ProductFinality) -> DFA {
+        let alphabet = self.alphabet().union(&other.alphabet()).cloned().collect();
+        match finality {
+            ProductFinality::Both => self.product(other, &alphabet, |a,b| a && b),
+            ProductFinality::Either => self.product(other, &alphabet, |a,b| a || b),
+            ProductFinality::First => self.product(other, &alphabet, |a,_| a),
+            ProductFinality::Second => self.product(other, &alphabet, |_,b| b),
+        }
+    }
+
+    /// Returns a trim DFA accepting the words of the DFA whose length, in
+    /// characters, lies between `min` and `max` included. The DFA is intersected
+    /// with a DFA counting the symbols up to `max`, which has `max + 1` states:
+    /// the cost of the construction grows with `max` even if the words of the DFA
+    /// are shorter. The language is empty if `min` is greater than `max`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::sigma_star(&['a', 'b']).with_length_between(1, 2);
+    ///     assert!(dfa.test("b"));
+    ///     assert!(dfa.test("ab"));
+    ///     assert!(!dfa.test(""));
+    ///     assert!(!dfa.test("aba"));
+    /// }
+    /// ```
+    pub fn with_length_between(&self, min: usize, max: usize) -> DFA {
+        let alphabet = self.alphabet();
+        let mut transitions = HashMap::new();
+        for len in 0..max {
+            for &symb in alphabet.iter() {
+                transitions.insert((symb,len), len+1);
+            }
+        }
+        let finals = (min..max.saturating_add(1)).collect();
+        let counter = DFA::from_parts(transitions, 0, finals);
+        self.product(&counter, &alphabet, |a,b| a && b).trim()
+    }
+
+    /// Returns an NFA accepting the words of the DFA from which every occurrence
+    /// of `symbol` is erased. The transitions on `symbol` are turned into
+    /// epsilon transitions, which are then eliminated: a state gets the
+    /// transitions of the states of its epsilon closure, and is final if its
+    /// closure contains a final state. The states are those of the DFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let nfa = DFA::from_words(&["a#b", "#c#"]).project_out('#');
+    ///     assert!(nfa.test("ab"));
+    ///     assert!(nfa.test("c"));
+    ///     assert!(!nfa.test("a#b"));
+    /// }
+    /// ```
+    pub fn project_out(&self, symbol: char) -> NFA {
+        let mut erased = HashMap::new();
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            if symb == symbol {
+                erased.entry(src).or_insert_with(Vec::new).push(dest);
+            }
+        }
+        let mut transitions = HashMap::new();
+        let mut finals = HashSet::new();
+        for state in self.states() {
+            for reached in closure(vec![state], &erased) {
+                if self.finals.contains(&reached) {
+                    finals.insert(state);
+                }
+                for (&(symb,src),&dest) in self.transitions.iter() {
+                    if src == reached && symb != symbol {
+                        transitions.entry((symb,state)).or_insert_with(HashSet::new).insert(dest);
+                    }
+                }
+            }
+        }
+        NFA::from_parts(transitions, self.start, finals)
+    }
+
+    /// Returns an ENFA accepting the words of the DFA in which each occurrence of
+    /// `symbol` is replaced by a word of `replacement`. Each transition on
+    /// `symbol` is replaced by a copy of `replacement`, entered from the source
+    /// of the transition and left from its final states to the destination of
+    /// the transition by epsilon transitions. The states of the copies are
+    /// shifted after the states of the DFA, one copy after the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["<x>"]);
+    ///     let enfa = dfa.substitute('x', &DFA::from_words(&["a", "bc"]));
+    ///     assert!(enfa.test("<a>"));
+    ///     assert!(enfa.test("<bc>"));
+    ///     assert!(!enfa.test("<x>"));
+    /// }
+    /// ```
+    pub fn substitute(&self, symbol: char, replacement: &DFA) -> ENFA {
+        let offset = self.states().iter().max().unwrap() + 1;
+        let size = replacement.states().iter().max().unwrap() + 1;
+        let mut substituted : Vec<_> = self.transitions
+            .iter()
+            .filter(|&(&(symb,_),_)| symb == symbol)
+            .map(|(&(_,src),&dest)| (src,dest))
+            .collect();
+        substituted.sort();
+        let mut transitions = HashMap::new();
+        let mut e_transitions = HashMap::new();
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            if symb != symbol {
+                transitions.entry((symb,src)).or_insert_with(HashSet::new).insert(dest);
+            }
+        }
+        for (copy,(src,dest)) in substituted.into_iter().enumerate() {
+            let shift = offset + copy*size;
+            for (&(symb,rsrc),&rdest) in replacement.transitions.iter() {
+                transitions.entry((symb,rsrc+shift)).or_insert_with(HashSet::new).insert(rdest+shift);
+            }
+            e_transitions.entry(src).or_insert_with(HashSet::new).insert(replacement.start+shift);
+            for &state in replacement.finals.iter() {
+                e_transitions.entry(state+shift).or_insert_with(HashSet::new).insert(dest);
+            }
+        }
+        ENFA::from_parts(transitions, e_transitions, self.start, self.finals.clone())
+    }
+
+    /// Returns an NFA accepting the shuffle of the languages of the two DFAs, i.e.
+    /// the interleavings of a word of the DFA with a word of `other`. The states
+    /// of the NFA are the reachable pairs of states, numbered in BFS order: on
+    /// each symbol, either component advances while the other one stays. A pair
+    /// is final if both of its components are final.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let nfa = DFA::from_words(&["ab"]).shuffle(&DFA::from_words(&["c"]));
+    ///     assert!(nfa.test("abc"));
+    ///     assert!(nfa.test("acb"));
+    ///     assert!(nfa.test("cab"));
+    ///     assert!(!nfa.test("bac"));
+    /// }
+    /// ```
+    pub fn shuffle(&self, other: &DFA) -> NFA {
+        let successors = self.sorted_successors();
+        let other_successors = other.sorted_successors();
+        let start = (self.start,other.start);
+        let mut ids = HashMap::new();
+        ids.insert(start, 0);
+        let mut pairs = vec![start];
+        let mut transitions = HashMap::new();
+        let mut finals = HashSet::new();
+        let mut i = 0;
+        while i < pairs.len() {
+            let (s1,s2) = pairs[i];
+            if self.finals.contains(&s1) && other.finals.contains(&s2) {
+                finals.insert(i);
+            }
+            let firsts = successors
+                .get(&s1)
+                .into_iter()
+                .flat_map(|nexts| nexts.iter())
+                .map(|&(symb,dest)| (symb,(dest,s2)));
+            let seconds = other_successors
+                .get(&s2)
+                .into_iter()
+                .flat_map(|nexts| nexts.iter())
+                .map(|&(symb,dest)| (symb,(s1,dest)));
+            for (symb,dest) in firsts.chain(seconds) {
+                let id = pairs.len();
+                let id = *ids.entry(dest).or_insert(id);
+                if id == pairs.len() {
+                    pairs.push(dest);
+                }
+                transitions.entry((symb,i)).or_insert_with(HashSet::new).insert(id);
+            }
+            i += 1;
+        }
+        NFA::from_parts(transitions, 0, finals)
+    }
+
+    /// Returns the intersection of the two DFAs completed over `alphabet`: a
+    /// missing transition of either DFA on a symbol of `alphabet` leads to a
+    /// common trap state, and the transitions on symbols that are not in
+    /// `alphabet` are removed. The words using a symbol outside of `alphabet`
+    /// are thus rejected, whatever the alphabets of the two DFAs. The product is
+    /// complete over `alphabet` and is not minimized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let a = DFA::from_words(&["ab", "ac"]);
+    ///     let b = DFA::from_words(&["ab", "ac", "ad"]);
+    ///     let product = a.intersection_over(&b, &['a', 'b', 'd']);
+    ///     assert!(product.test("ab"));
+    ///     assert!(!product.test("ac"));
+    ///     assert!(!product.test("ad"));
+    /// }
+    /// ```
+    pub fn intersection_over(&self, other: &DFA, alphabet: &[char]) -> DFA {
+        self.product(other, &alphabet.iter().cloned().collect(), |a,b| a && b)
+    }
+
+    /// Returns `true` if the languages of the two DFAs partition the set of the
+    /// words over `alphabet`, i.e. if each word over `alphabet` is accepted by
+    /// exactly one of the two DFAs. The transitions on symbols that are not in
+    /// `alphabet` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // words with an even number of a
+    ///     let even = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     // words with an odd number of a
+    ///     let odd = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(even.is_complement_of(&odd, &['a']));
+    ///     assert!(!even.is_complement_of(&odd, &['a','b']));
+    /// }
+    /// ```
+    pub fn is_complement_of(&self, other: &DFA, alphabet: &[char]) -> bool {
+        let symbols : HashSet<_> = alphabet.iter().cloned().collect();
+        self.product(other, &symbols, |a,b| a && b).is_empty()
+            && self.product(other, &symbols, |a,b| a || b).is_universal(alphabet)
+    }
+
+    /// Checks that no word is accepted by both DFAs.
+    ///
+    /// # Errors
+    ///
+    /// Return a shortest word accepted by both DFAs if their languages overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let a = DFA::from_words(&["user.name", "user.id"]);
+    ///     let b = DFA::from_words(&["group.name", "group.id"]);
+    ///     let c = DFA::from_words(&["user.id", "group.id"]);
+    ///     assert_eq!(a.disjoint_from(&b), Ok(()));
+    ///     assert_eq!(a.disjoint_from(&c), Err("user.id".to_string()));
+    /// }
+    /// ```
+    pub fn disjoint_from(&self, other: &DFA) -> result::Result<(),String> {
+        let alphabet = self.alphabet().union(&other.alphabet()).cloned().collect();
+        // each symbol costs 1 so the cheapest word is a shortest one
+        match self.product(other, &alphabet, |a,b| a && b).cheapest_word(&HashMap::new()) {
+            Some((word,_)) => Err(word),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the language of the DFA is empty, i.e. if no final state
+    /// is reachable from the starting state.
+    pub fn is_empty(&self) -> bool {
+        self.reachable_states().is_disjoint(&self.finals)
+    }
+
+    /// Returns the DFA completed over its alphabet with a new trap state, if it is
+    /// not already complete. The completed form is computed on the first call
+    /// and memoized, so that the repeated complements of the DFA over its own
+    /// alphabet (see `complement`) share it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // ab
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let complete = dfa.as_complete();
+    ///     assert_eq!(complete.trap(), Some(3));
+    ///     assert!(complete.dfa().stats().is_complete);
+    ///     assert!(complete.complement().test("ba"));
+    /// }
+    /// ```
+    pub fn as_complete(&self) -> &CompleteDFA {
+        self.complete.get_or_init(|| {
+            #[cfg(test)]
+            COMPLETIONS.with(|completions| completions.set(completions.get()+1));
+            let trap = self.states().iter().max().unwrap() + 1;
+            let dfa = self.completed(&self.alphabet(), trap);
+            let trap = if dfa.has_state(trap) { Some(trap) } else { None };
+            Box::new(CompleteDFA{dfa, trap})
+        })
+    }
+
+    /// Returns `true` if the language of the DFA is star-free, i.e. if it can be
+    /// described from the letters by the boolean operations and the
+    /// concatenation, without the star. By Schützenberger's theorem, this is the
+    /// case if and only if the transition monoid of the minimal DFA is aperiodic:
+    /// each transformation `f` of the monoid satisfies `f^n = f^(n+1)` for some
+    /// `n`, the number of states being a valid `n`. The size of the monoid may be
+    /// exponential in the number of states.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (aa)*
+    ///     let even = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(!even.is_star_free());
+    ///     assert!(DFA::contains_substring("ab", &['a', 'b']).is_star_free());
+    /// }
+    /// ```
+    pub fn is_star_free(&self) -> bool {
+        self.syntactic_monoid().is_aperiodic()
+    }
+
+    /// Returns the syntactic monoid of the language of the DFA, i.e. the
+    /// transition monoid of its minimal complete DFA: the transformations of the
+    /// states induced by the words, under composition. The size of the monoid
+    /// may be exponential in the number of states.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (aa)*
+    ///     let even = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let monoid = even.syntactic_monoid();
+    ///     assert_eq!(monoid.size(), 2);
+    ///     assert!(monoid.is_group());
+    ///     assert!(!monoid.is_aperiodic());
+    /// }
+    /// ```
+    pub fn syntactic_monoid(&self) -> SyntacticMonoid {
+        SyntacticMonoid{elements: self.transition_monoid()}
+    }
+
+    /// Returns `true` if the DFA accepts every word over `alphabet`, i.e. if its
+    /// complement over `alphabet` is empty. The declared alphabet of the DFA, if
+    /// any, is added to `alphabet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(dfa.is_universal(&['a','b']));
+    ///     assert!(!dfa.is_universal(&['a','b','c']));
+    /// }
+    /// ```
+    pub fn is_universal(&self, alphabet: &[char]) -> bool {
+        self.complement(alphabet).is_empty()
+    }
+
+    /// Returns a DFA accepting the words over `alphabet` that are not accepted by
+    /// the DFA. The DFA is completed over `alphabet` with a new trap state if
+    /// needed, and the transitions on symbols that are not in `alphabet` are
+    /// removed. The declared alphabet of the DFA, if any, is added to
+    /// `alphabet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (ab)*
+    ///     let dfa = DFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let complement = dfa.complement(&['a','b']);
+    ///     assert!(!complement.test("abab"));
+    ///     assert!(complement.test("aba"));
+    ///     assert!(complement.test("bb"));
+    /// }
+    /// ```
+    pub fn complement(&self, alphabet: &[char]) -> DFA {
+        let mut alphabet : HashSet<_> = alphabet.iter().cloned().collect();
+        alphabet.extend(self.alphabet.iter().flat_map(|declared| declared.iter().cloned()));
+        if alphabet == self.alphabet() {
+            return self.as_complete().complement();
+        }
+        let trap = self.states().iter().max().unwrap() + 1;
+        let completed = self.completed(&alphabet, trap);
+        let finals = completed.states().difference(&completed.finals).cloned().collect();
+        DFA::with_alphabet(completed.transitions, completed.start, finals, completed.alphabet)
+    }
+
+    /// Returns the DFA completed over its alphabet, the missing transitions
+    /// leading to the new non final state `trap_id`, which loops on every symbol.
+    /// The DFA is returned unchanged if it is already complete.
+    ///
+    /// # Errors
+    ///
+    /// Return a DFAError::ExistingState if `trap_id` is already a state of the
+    /// DFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFA::from_words(&["ab"]).complete_with_trap(100).unwrap();
+    ///     assert_eq!(dfa.next_state(0, 'b'), Some(100));
+    ///     assert_eq!(dfa.next_state(100, 'a'), Some(100));
+    ///     assert!(DFA::from_words(&["ab"]).complete_with_trap(1).is_err());
+    /// }
+    /// ```
+    pub fn complete_with_trap(&self, trap_id: usize) -> Result<DFA> {
+        if self.has_state(trap_id) {
+            return Err(DFAError::ExistingState(trap_id));
+        }
+        Ok(self.completed(&self.alphabet(), trap_id))
+    }
+
+    // Completes the DFA over `alphabet`, the missing transitions leading to the
+    // new trap state `trap`. The transitions on symbols that are not in `alphabet`
+    // are removed.
+    fn completed(&self, alphabet: &HashSet<char>, trap: usize) -> DFA {
+        let states = self.states();
+        let mut transitions : HashMap<_,_> = self.transitions
+            .iter()
+            .filter(|&(&(symb,_),_)| alphabet.contains(&symb))
+            .map(|(&tr,&dest)| (tr,dest))
+            .collect();
+        if transitions.len() < states.len()*alphabet.len() {
+            for &state in states.iter().chain(Some(trap).iter()) {
+                for &symb in alphabet.iter() {
+                    transitions.entry((symb,state)).or_insert(trap);
+                }
+            }
+        }
+        DFA::with_alphabet(transitions, self.start, self.finals.clone(), self.alphabet.clone())
+    }
+
+    // Builds the product of the two DFAs completed over `alphabet`, a missing
+    // transition leading to an implicit trap state (`None`). Only the reachable
+    // pairs of states are built and a pair is final if `accept` returns `true`
+    // for the finality of its two components.
+    fn product<F>(&self, other: &DFA, alphabet: &HashSet<char>, accept: F) -> DFA
+        where F: Fn(bool,bool) -> bool {
+        let mut alphabet : Vec<_> = alphabet.iter().cloned().collect();
+        alphabet.sort();
+        let is_final = |dfa: &DFA, state: Option<usize>| state.map_or(false, |s| dfa.finals.contains(&s));
+        let next = |dfa: &DFA, state: Option<usize>, symb: char| {
+            state.and_then(|s| dfa.transitions.get(&(symb,s)).cloned())
+        };
+        let start = (Some(self.start),Some(other.start));
+        let mut ids = HashMap::new();
+        ids.insert(start, 0);
+        let mut pairs = vec![start];
+        let mut transitions = HashMap::new();
+        let mut finals = HashSet::new();
+        let mut i = 0;
+        while i < pairs.len() {
+            let (s1,s2) = pairs[i];
+            telemetry!("product", pairs.len(), pairs.len()-i-1);
+            if accept(is_final(self, s1), is_final(other, s2)) {
+                finals.insert(i);
+            }
+            for &symb in alphabet.iter() {
+                let dest = (next(self, s1, symb),next(other, s2, symb));
+                let id = pairs.len();
+                let id = *ids.entry(dest).or_insert(id);
+                if id == pairs.len() {
+                    pairs.push(dest);
+                }
+                transitions.insert((symb,i), id);
+            }
+            i += 1;
+        }
+        DFA::with_alphabet(transitions, 0, finals, declared_alphabet([self, other]))
+    }
+
+    // Returns the transitions of each state sorted by symbol.
+    fn sorted_successors(&self) -> HashMap<usize,Vec<(char,usize)>> {
+        let mut successors = HashMap::new();
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            successors.entry(src).or_insert_with(Vec::new).push((symb,dest));
+        }
+        for nexts in successors.values_mut() {
+            nexts.sort();
+        }
+        successors
+    }
+
+    // Returns the reachable states in BFS order, the transitions of a state being
+    // visited by increasing symbol, along with the index of each state in this order.
+    fn bfs_order(&self) -> (Vec<usize>,HashMap<usize,usize>) {
+        let successors = self.sorted_successors();
+        let mut ids = HashMap::new();
+        let mut order = vec![self.start];
+        ids.insert(self.start, 0);
+        let mut i = 0;
+        while i < order.len() {
+            let state = order[i];
+            for &(_,dest) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                let id = order.len();
+                if *ids.entry(dest).or_insert(id) == id {
+                    order.push(dest);
+                }
+            }
+            i += 1;
+        }
+        (order,ids)
+    }
+
+    // Renumbers the reachable states in BFS order (see `bfs_order`), so that
+    // isomorphic DFAs are renumbered into the same DFA.
+    fn renumbered(&self) -> DFA {
+        let (_,ids) = self.bfs_order();
+        let transitions = self.transitions
+            .iter()
+            .filter(|&(&(_,src),_)| ids.contains_key(&src))
+            .map(|(&(symb,src),dest)| ((symb,ids[&src]),ids[dest]))
+            .collect();
+        let finals = self.finals.iter().filter_map(|state| ids.get(state).cloned()).collect();
+        DFA::with_alphabet(transitions, 0, finals, self.alphabet.clone())
+    }
+
+    fn reachable_states(&self) -> HashSet<usize> {
+        let mut successors = HashMap::new();
+        for (&(_,src),&dest) in self.transitions.iter() {
+            successors.entry(src).or_insert_with(Vec::new).push(dest);
+        }
+        closure(vec![self.start], &successors)
+    }
+
+    // Inverts the transitions: each state is mapped to the `(symb,src)` pairs of
+    // the transitions entering it.
+    fn predecessor_map(&self) -> HashMap<usize,Vec<(char,usize)>> {
+        let mut predecessors = HashMap::new();
+        for (&(symb,src),&dest) in self.transitions.iter() {
+            predecessors.entry(dest).or_insert_with(Vec::new).push((symb,src));
+        }
+        predecessors
+    }
+
+    fn coreachable_states(&self) -> HashSet<usize> {
+        let predecessors = self.predecessor_map()
+            .into_iter()
+            .map(|(dest,srcs)| (dest,srcs.into_iter().map(|(_,src)| src).collect()))
+            .collect();
+        closure(self.finals.iter().cloned().collect(), &predecessors)
+    }
+
+    // The starting state is considered useful even if it is not co-reachable.
+    fn useful_states(&self) -> HashSet<usize> {
+        let mut useful : HashSet<_> = self.reachable_states()
+            .intersection(&self.coreachable_states())
+            .cloned()
+            .collect();
+        useful.insert(self.start);
+        useful
+    }
+
+    fn has_state(&self, state: usize) -> bool {
+        state == self.start
+            || self.finals.contains(&state)
+            || self.transitions.iter().any(|(&(_,src),&dest)| src == state || dest == state)
+    }
+
+    fn edges(&self) -> Vec<(usize,Option<char>,usize)> {
+        self.transitions
+            .iter()
+            .map(|(&(symb,src),&dest)| (src,Some(symb),dest))
+            .collect()
+    }
+
+    fn states(&self) -> HashSet<usize> {
+        let mut states : HashSet<_> = self.finals.iter().cloned().collect();
+        states.insert(self.start);
+        for (&(_,src),&dest) in self.transitions.iter() {
+            states.insert(src);
+            states.insert(dest);
+        }
+        states
+    }
+
+    // Returns the transition monoid of the minimal complete DFA, the first
+    // element being the identity. The states are numbered from 0 and each
+    // transformation maps a state to its image.
+    fn transition_monoid(&self) -> Vec<Vec<usize>> {
+        let minimal = self.minimize();
+        let alphabet = minimal.alphabet();
+        let trap = minimal.states().iter().max().unwrap() + 1;
+        let complete = minimal.completed(&alphabet, trap);
+        let mut states : Vec<_> = complete.states().into_iter().collect();
+        states.sort();
+        let index : HashMap<_,_> = states.iter().enumerate().map(|(i,&state)| (state,i)).collect();
+        let mut alphabet : Vec<_> = alphabet.into_iter().collect();
+        alphabet.sort();
+        let generators : Vec<Vec<usize>> = alphabet
+            .iter()
+            .map(|&symb| states
+                .iter()
+                .map(|&state| index[&complete.transitions[&(symb,state)]])
+                .collect())
+            .collect();
+        let identity : Vec<_> = (0..states.len()).collect();
+        let mut known : HashSet<_> = Some(identity.clone()).into_iter().collect();
+        let mut monoid = vec![identity];
+        let mut i = 0;
+        while i < monoid.len() {
+            for generator in generators.iter() {
+                let next : Vec<_> = monoid[i].iter().map(|&state| generator[state]).collect();
+                if known.insert(next.clone()) {
+                    monoid.push(next);
+                }
+            }
+            i += 1;
+        }
+        monoid
+    }
+
+    fn alphabet(&self) -> HashSet<char> {
+        let mut alphabet : HashSet<_> = self.transitions.keys().map(|&(symb,_)| symb).collect();
+        alphabet.extend(self.alphabet.iter().flat_map(|declared| declared.iter().cloned()));
+        alphabet
+    }
+}
+
+/// Returns the states reachable from `states` following `edges`.
+fn closure(states: Vec<usize>, edges: &HashMap<usize,Vec<usize>>) -> HashSet<usize> {
+    let mut reached : HashSet<_> = states.iter().cloned().collect();
+    let mut stack = states;
+    while let Some(state) = stack.pop() {
+        for &next in edges.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+            if reached.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    reached
+}
+
+/// Returns a DFA accepting the words accepted by at least one of the DFAs. The
+/// product is built over all the DFAs at once, instead of folding a pairwise
+/// union, and the language of the DFA is empty if there is no DFA.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa;
+/// use automaton::dfa::core::*;
+///
+/// fn main() {
+///     let dfas = [DFA::from_words(&["ab"]), DFA::from_words(&["ba", "c"])];
+///     let union = dfa::union_all(&dfas);
+///     assert!(union.accepts_exactly(&["ab", "ba", "c"]));
+/// }
+/// ```
+pub fn union_all(dfas: &[DFA]) -> DFA {
+    product_all(dfas, |finals| finals.iter().any(|&is_final| is_final))
+}
+
+/// Returns a DFA accepting the words accepted by all the DFAs. The product is
+/// built over all the DFAs at once and stops as soon as one of the DFAs is
+/// known to have an empty language. If there is no DFA, the result only
+/// accepts the empty word.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa;
+/// use automaton::dfa::core::*;
+///
+/// fn main() {
+///     let dfas = [DFA::from_words(&["ab", "ba"]), DFA::from_words(&["ba", "c"])];
+///     let intersection = dfa::intersect_all(&dfas);
+///     assert!(intersection.accepts_exactly(&["ba"]));
+/// }
+/// ```
+pub fn intersect_all(dfas: &[DFA]) -> DFA {
+    if dfas.iter().any(|dfa| dfa.is_empty()) {
+        return DFA::with_alphabet(HashMap::new(), 0, HashSet::new(), declared_alphabet(dfas));
+    }
+    product_all(dfas, |finals| finals.iter().all(|&is_final| is_final))
+}
+
+// Builds the product of the DFAs over the union of their alphabets. A state of
+// the product is a tuple of states, a missing transition leading to `None`.
+// `accept` tells if a tuple is final from the finality of each component and
+// must be monotone: the tuples which would not be final even if all their
+// `Some` components were final can not reach a final tuple and are not built.
+fn product_all<F>(dfas: &[DFA], accept: F) -> DFA
+    where F: Fn(&[bool]) -> bool {
+    let mut alphabet : Vec<_> = dfas
+        .iter()
+        .flat_map(|dfa| dfa.alphabet().into_iter())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    alphabet.sort();
+    let start : Vec<_> = dfas.iter().map(|dfa| Some(dfa.start)).collect();
+    let mut ids = HashMap::new();
+    ids.insert(start.clone(), 0);
+    let mut tuples = vec![start];
+    let mut transitions = HashMap::new();
+    let mut finals = HashSet::new();
+    let mut i = 0;
+    while i < tuples.len() {
+        telemetry!("product", tuples.len(), tuples.len()-i-1);
+        let is_final : Vec<_> = dfas
+            .iter()
+            .zip(tuples[i].iter())
+            .map(|(dfa,state)| state.map_or(false, |s| dfa.finals.contains(&s)))
+            .collect();
+        if accept(&is_final) {
+            finals.insert(i);
+        }
+        for &symb in alphabet.iter() {
+            let dest : Vec<_> = dfas
+                .iter()
+                .zip(tuples[i].iter())
+                .map(|(dfa,state)| state.and_then(|s| dfa.transitions.get(&(symb,s)).cloned()))
+                .collect();
+            let is_live : Vec<_> = dest.iter().map(|state| state.is_some()).collect();
+            if !accept(&is_live) {
+                continue;
+            }
+            let id = tuples.len();
+            let id = *ids.entry(dest.clone()).or_insert(id);
+            if id == tuples.len() {
+                tuples.push(dest);
+            }
+            transitions.insert((symb,i), id);
+        }
+        i += 1;
+    }
+    DFA::with_alphabet(transitions, 0, finals, declared_alphabet(dfas))
+}
+
+// Returns the union of the declared alphabets of the DFAs, `None` if none of
+// them declares an alphabet.
+fn declared_alphabet<'a,I>(dfas: I) -> Option<HashSet<char>>
+    where I: IntoIterator<Item=&'a DFA> {
+    dfas.into_iter().fold(None, |union, dfa| match (union,&dfa.alphabet) {
+        (union,&None) => union,
+        (None,&Some(ref declared)) => Some(declared.clone()),
+        (Some(mut union),&Some(ref declared)) => {
+            union.extend(declared.iter().cloned());
+            Some(union)
+        }
+    })
+}
+
+// Regular expressions built by the state elimination of `DFA::to_regex`. The
+// empty language is never represented: a missing edge stands for it.
+#[derive(Debug,Clone,PartialEq)]
+enum Regex {
+    Epsilon,
+    Symbol(char),
+    Union(Vec<Regex>),
+    Concat(Vec<Regex>),
+    Star(Box<Regex>),
+}
+
+impl Regex {
+    fn union(self, other: Regex) -> Regex {
+        let mut items = match self {
+            Regex::Union(items) => items,
+            regex => vec![regex],
+        };
+        let others = match other {
+            Regex::Union(others) => others,
+            regex => vec![regex],
+        };
+        for regex in others {
+            if !items.contains(&regex) {
+                items.push(regex);
+            }
+        }
+        if items.len() == 1 { items.pop().unwrap() } else { Regex::Union(items) }
+    }
+
+    fn concat(self, other: Regex) -> Regex {
+        let mut items = match self {
+            Regex::Concat(items) => items,
+            Regex::Epsilon => vec![],
+            regex => vec![regex],
+        };
+        match other {
+            Regex::Concat(others) => items.extend(others),
+            Regex::Epsilon => {},
+            regex => items.push(regex),
+        }
+        match items.len() {
+            0 => Regex::Epsilon,
+            1 => items.pop().unwrap(),
+            _ => Regex::Concat(items),
+        }
+    }
+
+    fn star(self) -> Regex {
+        match self {
+            Regex::Epsilon => Regex::Epsilon,
+            regex @ Regex::Star(_) => regex,
+            regex => Regex::Star(Box::new(regex)),
+        }
+    }
+
+    // The union binds less tightly than the concatenation, which binds less
+    // tightly than the star.
+    fn precedence(&self) -> u8 {
+        match *self {
+            Regex::Union(_) => 0,
+            Regex::Concat(_) => 1,
+            _ => 2,
+        }
+    }
+
+    fn fmt_within(&self, f: &mut fmt::Formatter, precedence: u8) -> fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Regex::Epsilon => write!(f, "ε"),
+            Regex::Symbol(symb) if "|*()\\ε∅".contains(symb) => write!(f, "\\{}", symb),
+            Regex::Symbol(symb) => write!(f, "{}", symb),
+            Regex::Union(ref items) => {
+                for (i,regex) in items.iter().enumerate() {
+                    if i > 0 {
+                        try!(write!(f, "|"));
+                    }
+                    try!(regex.fmt_within(f, 0));
+                }
+                Ok(())
+            },
+            Regex::Concat(ref items) => {
+                for regex in items.iter() {
+                    try!(regex.fmt_within(f, 1));
+                }
+                Ok(())
+            },
+            Regex::Star(ref regex) => {
+                try!(regex.fmt_within(f, 2));
+                write!(f, "*")
+            },
+        }
+    }
+}
+
+// Adds the expression `regex` as an alternative on the edge from `src` to
+// `dest`.
+fn add_edge(edges: &mut HashMap<(usize,usize),Regex>, src: usize, dest: usize, regex: Regex) {
+    let regex = match edges.remove(&(src,dest)) {
+        Some(existing) => existing.union(regex),
+        None => regex,
+    };
+    edges.insert((src,dest), regex);
+}
+
+impl Render for DFA {
+    fn to_dot(&self) -> String {
+        DFA::to_dot(self)
+    }
+
+    fn to_table(&self) -> String {
+        render::table(self.start, &self.finals, self.states(), self.edges())
+    }
+
+    fn to_csv(&self) -> String {
+        DFA::to_csv(self)
+    }
+}
+
+impl fmt::Display for DFA {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(f, "START: {}", self.start));
+        try!(writeln!(f, "FINALS:"));
+        for fi in self.finals.iter() {
+            try!(writeln!(f,"  {}", fi));
+        }
+        try!(writeln!(f, "TRANSITIONS:"));
+        let edges = self.transitions
+            .iter()
+            .map(|(&(symb,src),&dest)| (src,Some(symb),dest))
+            .collect();
+        for (s,c,d) in dot::coalesce(edges) {
+            try!(writeln!(f, "  ({},{}) => {}", c.unwrap_or_default(), s, d));
+        }
+        write!(f, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
     fn test_dfa() {
         let dfa = DFABuilder::new()
             .add_start(0)
@@ -366,31 +5002,2768 @@ mod tests {
             .finalize()
             .unwrap();
         let samples =
-            vec![("ababac", false),
-                 ("ababc", true),
+            vec![("ababac", false),
+                 ("ababc", true),
+                 ("", false),
+                 ("abc", true),
+                 ("c", true),
+                 ("ac", false),
+                 ("ababababababababababababababababababababc", true),];
+
+        for (input,expected_result) in samples {
+            assert!(dfa.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_test_bytes() {
+        // (abc)* with a transition on a non-ASCII symbol
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 0)
+            .add_transition('é', 0, 0)
+            .finalize()
+            .unwrap();
+        let samples : &[(&[u8],bool)] =
+            &[(b"", true),
+              (b"abc", true),
+              (b"abcabcabc", true),
+              (b"ab", false),
+              (b"abcb", false),
+              (&[0x61, 0x62, 0x63, 0xe9], false),
+              ("é".as_bytes(), false)];
+        for &(input,expected_result) in samples {
+            assert!(dfa.test_bytes(input) == expected_result, "input false for: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_rejected_due_to_missing_transition() {
+        // (abc)* with a dead state on 'x'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 0)
+            .add_transition('x', 0, 3)
+            .finalize()
+            .unwrap();
+        let samples =
+            vec![("", false),
+                 ("abc", false),
+                 ("ab", false),
+                 ("abcx", false),
+                 ("abcxa", true),
+                 ("abd", true),
+                 ("d", true)];
+        for (input,expected_result) in samples {
+            assert_eq!(dfa.rejected_due_to_missing_transition(input), expected_result, "input: {}", input);
+            // the other rejections are the non-final ends of the run
+            let non_final = !expected_result && !dfa.test(input);
+            assert_eq!(non_final, dfa.test_strict(input) == Ok(false), "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_run_prefix() {
+        // (abc)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 0)
+            .finalize()
+            .unwrap();
+        let samples =
+            vec![("", (0, 0)),
+                 ("abc", (3, 0)),
+                 ("abcab", (5, 2)),
+                 ("abcaxbc", (4, 1)),
+                 ("éabc", (0, 0)),
+                 ("abcabcé", (6, 0))];
+        for (input,expected_result) in samples {
+            assert_eq!(dfa.run_prefix(input), expected_result, "input: {}", input);
+        }
+        // the run can be resumed after skipping the invalid symbol
+        let (consumed,state) = dfa.run_prefix("abxc");
+        assert_eq!((consumed,state), (2, 2));
+        assert_eq!(dfa.test_from(state, "c"), Some(0));
+    }
+
+    #[test]
+    fn test_dfa_test_codepoints() {
+        // (abc)* with a transition on a non-ASCII symbol
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 0)
+            .add_transition('é', 0, 0)
+            .finalize()
+            .unwrap();
+        let samples : &[(&[u32],bool)] =
+            &[(&[], true),
+              (&[0x61, 0x62, 0x63], true),
+              (&[0x61, 0x62, 0x63, 0xe9, 0x61, 0x62, 0x63], true),
+              (&[0x61, 0x62], false),
+              (&[0x61, 0x62, 0x63, 0x62], false),
+              (&[0xd800], false),
+              (&[0x61, 0x62, 0x63, 0x110000], false)];
+        for &(input,expected_result) in samples {
+            assert!(dfa.test_codepoints(input) == expected_result, "input false for: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_test_ignoring() {
+        // (abc)* with a transition on ' ' which is never taken
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 0)
+            .add_transition(' ', 0, 3)
+            .add_transition('d', 3, 0)
+            .finalize()
+            .unwrap();
+        let skip : HashSet<_> = [' ', '\t'].iter().cloned().collect();
+        let samples =
+            vec![("a b c", true),
+                 ("\ta bc  abc ", true),
+                 ("   ", true),
+                 ("a b", false),
+                 (" d", false),
+                 ("a-b-c", false),];
+
+        for (input,expected_result) in samples {
+            assert!(dfa.test_ignoring(input, &skip) == expected_result, "input false for: \"{}\"", input);
+        }
+        assert!(dfa.test(" d"));
+    }
+
+    #[test]
+    fn test_dfa_test_with_wildcards() {
+        let dfa = DFA::from_words(&["abc", "axc", "bd"]);
+        let samples =
+            vec![("a?c", true),
+                 ("abc", true),
+                 ("???", true),
+                 ("?d", true),
+                 ("??", true),
+                 ("a?d", false),
+                 ("?", false),
+                 ("a??c", false),
+                 ("", false),];
+
+        for (input,expected_result) in samples {
+            assert!(dfa.test_with_wildcards(input, '?') == expected_result, "input false for: \"{}\"", input);
+        }
+        // the wildcard is a regular symbol of the DFA
+        let dfa = DFA::from_words(&["a?", "bc"]);
+        assert!(dfa.test_with_wildcards("bc", '?'));
+        assert!(dfa.test_with_wildcards("b?", '?'));
+        assert!(!dfa.test_with_wildcards("b*", '?'));
+    }
+
+    #[test]
+    fn test_dfa_accepting_prefix_lengths() {
+        // (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.accepting_prefix_lengths("ababa"), vec![0, 2, 4]);
+        assert_eq!(dfa.accepting_prefix_lengths("abbab"), vec![0, 2]);
+        assert_eq!(dfa.accepting_prefix_lengths(""), vec![0]);
+        let dfa = DFA::from_words(&["éa", "éab"]);
+        assert_eq!(dfa.accepting_prefix_lengths("éabc"), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_dfa_test_detailed() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        assert!(dfa.test_detailed("ababc") == Ok(()));
+        assert!(dfa.test_detailed("c") == Ok(()));
+        // stuck in the middle of the input
+        assert!(dfa.test_detailed("abbc") == Err(2));
+        assert!(dfa.test_detailed("cab") == Err(1));
+        // valid prefix but not accepted
+        assert!(dfa.test_detailed("abab") == Err(4));
+        assert!(dfa.test_detailed("") == Err(0));
+    }
+
+    #[test]
+    fn test_dfa_test_strict() {
+        // partial DFA of (ab)*c
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let samples =
+            vec![("ababc", Ok(true)),
+                 ("abab", Ok(false)),
+                 ("", Ok(false)),
+                 ("abbc", Err(2)),
+                 ("cc", Err(1)),];
+
+        for (input,expected_result) in samples {
+            assert!(dfa.test_strict(input) == expected_result, "input false for: \"{}\"", input);
+            // the lenient test rejects both the undefined and the non-accepted words
+            assert!(dfa.test(input) == expected_result.unwrap_or(false), "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_test_from() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let prefix = dfa.test_from(0, "abab").unwrap();
+        assert!(prefix == 2);
+        for suffix in &["c", "abc", "", "a", "ac", "cc"] {
+            let resumed = dfa.test_from(prefix, suffix);
+            let full = dfa.test_from(0, &format!("abab{}", suffix));
+            assert!(resumed == full, "resumed run differs for: \"{}\"", suffix);
+        }
+        assert!(dfa.test_from(1, "bc") == Some(3));
+        assert!(dfa.test_from(3, "a").is_none());
+    }
+
+    #[test]
+    fn test_dfa_test_from_unknown_state() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        assert!(dfa.test_from(1, "") == Some(1));
+        assert!(dfa.test_from(7, "").is_none());
+        assert!(dfa.test_from(7, "a").is_none());
+    }
+
+    #[test]
+    fn test_dfa_union_all() {
+        // a*, b*, (ab)*, words ending with "ba" and words containing 'c'
+        let dfas = [
+            DFABuilder::new().add_start(0).add_final(0).add_transition('a', 0, 0).finalize().unwrap(),
+            DFABuilder::new().add_start(0).add_final(0).add_transition('b', 0, 0).finalize().unwrap(),
+            DFABuilder::new()
+                .add_start(0)
+                .add_final(0)
+                .add_transition('a', 0, 1)
+                .add_transition('b', 1, 0)
+                .finalize()
+                .unwrap(),
+            DFABuilder::new()
+                .add_start(0)
+                .add_final(2)
+                .add_transition('a', 0, 0)
+                .add_transition('b', 0, 1)
+                .add_transition('a', 1, 2)
+                .add_transition('b', 1, 1)
+                .add_transition('a', 2, 0)
+                .add_transition('b', 2, 1)
+                .finalize()
+                .unwrap(),
+            DFABuilder::new()
+                .add_start(0)
+                .add_final(1)
+                .add_transition('a', 0, 0)
+                .add_transition('b', 0, 0)
+                .add_transition('c', 0, 1)
+                .add_transition('a', 1, 1)
+                .add_transition('b', 1, 1)
+                .add_transition('c', 1, 1)
+                .finalize()
+                .unwrap(),
+        ];
+        let union = union_all(&dfas);
+        let words = ["", "a", "aaa", "bb", "ab", "abab", "aba", "bba", "abb", "bab", "ac", "cab", "bbc", "abba"];
+        for input in words.iter() {
+            let expected = dfas.iter().any(|dfa| dfa.test(input));
+            assert!(union.test(input) == expected, "input false for: \"{}\"", input);
+        }
+        assert!(union_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_intersect_all() {
+        // words over {a,b} with an even number of 'a', ending with 'b' and of
+        // length at most 4
+        let dfas = [
+            DFABuilder::new()
+                .add_start(0)
+                .add_final(0)
+                .add_transition('a', 0, 1)
+                .add_transition('b', 0, 0)
+                .add_transition('a', 1, 0)
+                .add_transition('b', 1, 1)
+                .finalize()
+                .unwrap(),
+            DFABuilder::new()
+                .add_start(0)
+                .add_final(1)
+                .add_transition('a', 0, 0)
+                .add_transition('b', 0, 1)
+                .add_transition('a', 1, 0)
+                .add_transition('b', 1, 1)
+                .finalize()
+                .unwrap(),
+            DFA::from_words(&["b", "ab", "bb", "aab", "abab", "aaab", "bbbb", "baab"]),
+        ];
+        let intersection = intersect_all(&dfas);
+        assert!(intersection.accepts_exactly(&["b", "bb", "aab", "abab", "bbbb", "baab"]));
+        let words = ["", "b", "ab", "aab", "abab", "bbbb", "bbbbb", "aa", "baab"];
+        for input in words.iter() {
+            let expected = dfas.iter().all(|dfa| dfa.test(input));
+            assert!(intersection.test(input) == expected, "input false for: \"{}\"", input);
+        }
+        assert!(intersect_all(&[]).accepts_exactly(&[""]));
+    }
+
+    #[test]
+    fn test_dfa_intersect_all_empty() {
+        // a+ and b+ share no word
+        let plus = |symb| DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition(symb, 0, 1)
+            .add_transition(symb, 1, 1)
+            .finalize()
+            .unwrap();
+        let intersection = intersect_all(&[plus('a'), plus('b'), plus('a')]);
+        assert!(intersection.is_empty());
+        assert_eq!(intersection.stats().state_count, 1);
+        // one of the DFAs has an empty language
+        let empty = DFABuilder::new().add_start(0).add_final(1).finalize().unwrap();
+        assert!(intersect_all(&[plus('a'), empty, plus('b')]).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_write_dot() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('b', 0, 1)
+            .add_transition('"', 0, 2)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 1, 2)
+            .finalize()
+            .unwrap();
+        let mut out = Vec::new();
+        dfa.write_dot(&mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert_eq!(dot, dfa.to_dot());
+        assert_eq!(dot,
+                   "digraph {\n    rankdir=LR;\n    start [shape=point];\n\
+                    \x20   0 [shape=circle];\n    1 [shape=circle];\n    2 [shape=doublecircle];\n\
+                    \x20   start -> 0;\n\
+                    \x20   0 -> 2 [label=\"\\\"\"];\n\
+                    \x20   0 -> 1 [label=\"a,b\"];\n\
+                    \x20   1 -> 2 [label=\"c\"];\n}\n");
+    }
+
+    #[test]
+    fn test_dfa_to_mermaid() {
+        // [0-9]+(;|\n) with a digit loop
+        let mut builder = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition(';', 1, 2)
+            .add_transition('\n', 1, 2);
+        for digit in "0123456789".chars() {
+            builder = builder
+                .add_transition(digit, 0, 1)
+                .add_transition(digit, 1, 1);
+        }
+        let mermaid = builder.finalize().unwrap().to_mermaid();
+        assert!(mermaid.starts_with("stateDiagram-v2\n"));
+        let lines : Vec<_> = mermaid.lines().map(str::trim).collect();
+        assert!(lines.contains(&"[*] --> s0"));
+        assert!(lines.contains(&"state \"1\" as s1"));
+        assert!(lines.contains(&"s0 --> s1: 0-9"));
+        assert!(lines.contains(&"s1 --> s1: 0-9"));
+        assert!(lines.contains(&"s1 --> s2: #10;,#59;"));
+        assert!(lines.contains(&"class s2 final"));
+        assert_eq!(lines.iter().filter(|line| line.starts_with("class ")).count(), 1);
+    }
+
+    #[test]
+    fn test_dfa_sigma_star() {
+        let dfa = DFA::sigma_star(&['a', 'b']);
+        assert!(dfa.test(""));
+        assert!(dfa.test("a"));
+        assert!(dfa.test("abba"));
+        assert!(!dfa.test("abc"));
+        assert_eq!(dfa.alphabet(), ['a', 'b'].iter().cloned().collect());
+        assert!(dfa.is_universal(&['a', 'b']));
+    }
+
+    #[test]
+    fn test_dfa_empty_language() {
+        let dfa = DFA::empty_language(&['a', 'b']);
+        assert!(dfa.is_empty());
+        for word in &["", "a", "b", "abba"] {
+            assert!(!dfa.test(word));
+        }
+        assert_eq!(dfa.alphabet(), ['a', 'b'].iter().cloned().collect());
+        assert!(DFA::empty_language(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_epsilon_only() {
+        let dfa = DFA::epsilon_only();
+        assert!(!dfa.is_empty());
+        assert!(dfa.test(""));
+        for word in &["a", "b", "ab"] {
+            assert!(!dfa.test(word));
+        }
+        assert_eq!(dfa.words(3), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_dfa_finalize_allow_empty() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize_allow_empty()
+            .unwrap();
+        assert!(dfa.is_empty());
+        for word in &["", "a", "ab", "abab"] {
+            assert!(!dfa.test(word));
+        }
+        assert_eq!(dfa.minimize(), DFA::empty_language(&[]).minimize());
+        match DFABuilder::new().add_transition('a', 0, 1).finalize_allow_empty() {
+            Err(DFAError::MissingStartingState) => assert!(true),
+            _ => assert!(false),
+        }
+        match DFABuilder::new().add_start(0).finalize() {
+            Err(DFAError::MissingFinalStates) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_dfa_filter_accepted() {
+        // (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let inputs = ["", "a", "ab", "aba", "abab", "ba", "ababab"];
+        let accepted : Vec<_> = dfa.filter_accepted(inputs.iter().cloned()).collect();
+        assert_eq!(accepted, vec!["", "ab", "abab", "ababab"]);
+        assert_eq!(dfa.filter_accepted(vec!["b", "bb"].into_iter()).count(), 0);
+    }
+
+    #[test]
+    fn test_dfa_residual_complexity() {
+        // words over {a,b} ending with "ab", with redundant states 3 and 4
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 3)
+            .add_transition('a', 1, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 4)
+            .add_transition('b', 2, 0)
+            .add_transition('a', 3, 1)
+            .add_transition('b', 3, 0)
+            .add_transition('a', 4, 1)
+            .add_transition('b', 4, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.residual_complexity(dfa.start), dfa.nerode_index());
+        assert_eq!(dfa.residual_complexity(0), 3);
+        assert_eq!(dfa.residual_complexity(4), 3);
+
+        let dfa = DFA::from_words(&["ab"]);
+        assert_eq!(dfa.residual_complexity(dfa.start), dfa.nerode_index());
+        assert_eq!(dfa.residual_complexity(0), 4);
+        assert_eq!(dfa.residual_complexity(1), 3);
+        assert_eq!(dfa.residual_complexity(2), 2);
+    }
+
+    #[test]
+    fn test_dfa_condensation() {
+        // a(b(cd)*e)*f with a dead branch from 4
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(5)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 3)
+            .add_transition('d', 3, 2)
+            .add_transition('e', 2, 1)
+            .add_transition('f', 1, 5)
+            .add_transition('g', 0, 4)
+            .add_transition('g', 4, 4)
+            .finalize()
+            .unwrap();
+        let (components,edges) = dfa.condensation();
+        let set = |states: &[usize]| states.iter().cloned().collect::<HashSet<_>>();
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[0], set(&[0]));
+        assert!(components.contains(&set(&[1, 2, 3])));
+        assert!(components.contains(&set(&[4])));
+        assert!(components.contains(&set(&[5])));
+        let position = |state: usize| components.iter().position(|component| component.contains(&state)).unwrap();
+        let mut expected = vec![(position(0),position(1)), (position(0),position(4)), (position(1),position(5))];
+        expected.sort();
+        assert_eq!(edges, expected);
+        for &(src,dest) in edges.iter() {
+            assert!(src < dest);
+        }
+
+        let (components,edges) = DFA::epsilon_only().condensation();
+        assert_eq!(components, vec![set(&[0])]);
+        assert!(edges.is_empty());
+    }
+
+    #[test]
+    fn test_dfa_simple_accepting_paths() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 0, 2)
+            .add_transition('d', 2, 0)
+            .add_transition('e', 1, 1)
+            .add_transition('f', 1, 3)
+            .add_transition('g', 0, 1)
+            .finalize()
+            .unwrap();
+        let all = vec![vec![0, 1], vec![0, 1, 2], vec![0, 2]];
+        assert_eq!(dfa.simple_accepting_paths(10), all);
+        assert_eq!(dfa.simple_accepting_paths(2), &all[..2]);
+        assert!(dfa.simple_accepting_paths(0).is_empty());
+
+        let dfa = DFA::epsilon_only();
+        assert_eq!(dfa.simple_accepting_paths(10), vec![vec![0]]);
+        assert!(dfa.simple_accepting_paths(0).is_empty());
+        assert!(DFA::empty_language(&['a']).simple_accepting_paths(10).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_cheapest_word() {
+        // a*bc|dd
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('c', 1, 2)
+            .add_transition('d', 0, 3)
+            .add_transition('d', 3, 2)
+            .finalize()
+            .unwrap();
+        let costs = |costs: &[(char,u64)]| costs.iter().cloned().collect::<HashMap<_,_>>();
+        assert_eq!(dfa.cheapest_word(&costs(&[('b', 3)])), Some(("dd".to_string(), 2)));
+        assert_eq!(dfa.cheapest_word(&costs(&[('d', 3)])), Some(("bc".to_string(), 2)));
+        assert_eq!(dfa.cheapest_word(&costs(&[('a', 0), ('d', 2)])), Some(("bc".to_string(), 2)));
+        assert_eq!(dfa.cheapest_word(&costs(&[('a', 0), ('b', 0), ('c', 0)])), Some(("bc".to_string(), 0)));
+        assert_eq!(dfa.cheapest_word(&costs(&[('b', 10), ('d', 6)])), Some(("bc".to_string(), 11)));
+        assert_eq!(DFA::epsilon_only().cheapest_word(&HashMap::new()), Some((String::new(), 0)));
+        assert_eq!(DFA::empty_language(&['a']).cheapest_word(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_dfa_acceptable_next_symbols() {
+        // (abc)* with a dead state 3 reached by 'd' from 2
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 0)
+            .add_transition('d', 2, 3)
+            .add_transition('d', 3, 3)
+            .finalize()
+            .unwrap();
+        let set = |symbs: &[char]| symbs.iter().cloned().collect::<HashSet<_>>();
+        assert_eq!(dfa.acceptable_next_symbols("ab"), set(&['c']));
+        assert_eq!(dfa.acceptable_next_symbols(""), set(&['a']));
+        assert_eq!(dfa.acceptable_next_symbols("abca"), set(&['b']));
+        assert!(dfa.acceptable_next_symbols("abd").is_empty());
+        assert!(dfa.acceptable_next_symbols("b").is_empty());
+    }
+
+    #[test]
+    fn test_dfa_power() {
+        let cube = DFA::from_words(&["ab"]).power(3);
+        assert!(cube.test("ababab"));
+        for word in &["", "ab", "abab", "abababab", "ababa"] {
+            assert!(!cube.test(word), "\"{}\" should be rejected", word);
+        }
+        assert_eq!(cube.minimize(), cube);
+        assert!(DFA::from_words(&["ab"]).power(0).accepts_exactly(&[""]));
+        assert!(DFA::from_words(&["ab"]).power(1).accepts_exactly(&["ab"]));
+        // a*b: the powers of an infinite language
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .finalize()
+            .unwrap();
+        let square = dfa.power(2);
+        for word in &["bb", "abab", "aabb", "baab"] {
+            assert!(square.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["b", "ab", "abba", "bbb", "bba"] {
+            assert!(!square.test(word), "\"{}\" should be rejected", word);
+        }
+        assert!(DFA::empty_language(&['a']).power(2).is_empty());
+        assert!(DFA::empty_language(&['a']).power(0).test(""));
+    }
+
+    #[test]
+    fn test_dfa_star_dfa() {
+        let star = DFA::from_words(&["ab"]).star_dfa();
+        for word in &["", "ab", "abab", "ababab"] {
+            assert!(star.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["a", "b", "aba", "ba", "abb"] {
+            assert!(!star.test(word), "\"{}\" should be rejected", word);
+        }
+        assert_eq!(star.stats().state_count, 2);
+        assert_eq!(star.minimize(), star);
+
+        // a*b: the starting state is on a cycle, "a" must not be accepted
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .finalize()
+            .unwrap();
+        let star = dfa.star_dfa();
+        assert!(star.test(""));
+        assert!(star.test("abaab"));
+        assert!(!star.test("a"));
+        assert!(!star.test("aba"));
+
+        assert_eq!(DFA::empty_language(&['a']).star_dfa(), DFA::epsilon_only().minimize());
+        assert_eq!(DFA::epsilon_only().star_dfa(), DFA::epsilon_only().minimize());
+    }
+
+    #[test]
+    fn test_dfa_role_counts() {
+        // 4 and 5 are unreachable, 5 being final
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(2)
+            .add_final(5)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 1, 3)
+            .add_transition('a', 4, 0)
+            .add_transition('a', 5, 4)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.role_counts(), (2, 2, 2));
+        assert_eq!(DFA::epsilon_only().role_counts(), (0, 1, 0));
+        assert_eq!(DFA::empty_language(&['a']).role_counts(), (1, 0, 0));
+    }
+
+    #[test]
+    fn test_dfa_is_reversal_closed() {
+        // a*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 0)
+            .finalize()
+            .unwrap();
+        assert!(dfa.is_reversal_closed());
+        // words over {a,b} ending with 'b'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_reversal_closed());
+        // words over {a,b} starting and ending with 'b'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('b', 0, 2)
+            .add_transition('a', 1, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('b', 2, 2)
+            .finalize()
+            .unwrap();
+        assert!(dfa.is_reversal_closed());
+        assert!(DFA::empty_language(&['a']).is_reversal_closed());
+        assert!(DFA::epsilon_only().is_reversal_closed());
+    }
+
+    #[test]
+    fn test_dfa_complete_with_trap() {
+        // (ab)*c
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .add_transition('c', 0, 2)
+            .finalize()
+            .unwrap();
+        let complete = dfa.complete_with_trap(7).unwrap();
+        let stats = complete.stats();
+        assert!(stats.is_complete);
+        assert_eq!(stats.state_count, 4);
+        for &(symb,src) in &[('b', 0), ('a', 1), ('c', 1), ('a', 2), ('b', 2), ('c', 2), ('a', 7), ('b', 7), ('c', 7)] {
+            assert_eq!(complete.next_state(src, symb), Some(7), "({},{}) should lead to the trap", symb, src);
+        }
+        for (&tr,&dest) in dfa.transitions.iter() {
+            assert_eq!(complete.transitions[&tr], dest);
+        }
+        assert_eq!(complete.minimize(), dfa.minimize());
+        assert_eq!(complete.complete_with_trap(8).unwrap(), complete);
+        for &state in &[0, 1, 2] {
+            assert_eq!(dfa.complete_with_trap(state), Err(DFAError::ExistingState(state)));
+        }
+    }
+
+    #[test]
+    fn test_dfa_test_approx() {
+        let dfa = DFA::from_words(&["abc"]);
+        assert!(dfa.test_approx("abc", 0));
+        assert!(!dfa.test_approx("abd", 0));
+        // substitution
+        assert!(dfa.test_approx("abd", 1));
+        // insertion and deletion
+        assert!(dfa.test_approx("ac", 1));
+        assert!(dfa.test_approx("abxc", 1));
+        assert!(dfa.test_approx("", 3));
+        assert!(!dfa.test_approx("", 2));
+        assert!(dfa.test_approx("bca", 2));
+        assert!(!dfa.test_approx("bca", 1));
+        assert!(!dfa.test_approx("xyzabc", 2));
+
+        // (ab)*: "aab" is one deletion away from "ab"
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert!(dfa.test_approx("aab", 1));
+        assert!(dfa.test_approx("ababa", 1));
+        assert!(!dfa.test_approx("bbbb", 1));
+        assert!(dfa.test_approx("bbbb", 2));
+    }
+
+    #[test]
+    fn test_dfa_builder_lint() {
+        let builder = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_final(5)
+            .add_final(7)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 1, 4)
+            .add_transition('d', 6, 5)
+            .unwrap();
+        assert_eq!(builder.lint(), vec![
+            "state 4 has no outgoing transitions and isn't final",
+            "final state 5 is unreachable from the starting state",
+            "final state 7 has no incoming transition",
+        ]);
+        assert!(builder.finalize().is_ok());
+
+        let builder = DFABuilder::new().add_transition('a', 0, 1).unwrap();
+        assert_eq!(builder.lint(), vec![
+            "missing starting state",
+            "missing final states",
+            "state 1 has no outgoing transitions and isn't final",
+        ]);
+
+        // a final starting state needs no incoming transition
+        let builder = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .unwrap();
+        assert!(builder.lint().is_empty());
+    }
+
+    #[test]
+    fn test_dfa_synchronized_product() {
+        // words over {a,b} with an even number of 'a'
+        let even = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        // (ab)*c
+        let other = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .add_transition('c', 0, 2)
+            .finalize()
+            .unwrap();
+        let product = |finality| even.synchronized_product(&other, finality);
+        assert!(product(ProductFinality::Both).stats().is_complete);
+        assert_eq!(product(ProductFinality::Both).minimize(),
+                   intersect_all(&[even.minimize(), other.minimize()]).minimize());
+        assert_eq!(product(ProductFinality::Either).minimize(),
+                   union_all(&[even.minimize(), other.minimize()]).minimize());
+        assert_eq!(product(ProductFinality::First).minimize(), even.minimize());
+        assert_eq!(product(ProductFinality::Second).minimize(), other.minimize());
+        let samples = [("", true, false), ("c", false, true), ("abc", false, true), ("ababc", false, true),
+                       ("aa", true, false), ("b", true, false), ("ac", false, false)];
+        for &(word,in_even,in_other) in samples.iter() {
+            assert_eq!(product(ProductFinality::Both).test(word), in_even && in_other);
+            assert_eq!(product(ProductFinality::Either).test(word), in_even || in_other);
+            assert_eq!(product(ProductFinality::First).test(word), in_even);
+            assert_eq!(product(ProductFinality::Second).test(word), in_other);
+        }
+    }
+
+    #[test]
+    fn test_dfa_finals_sorted() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(9)
+            .add_final(2)
+            .add_final(40)
+            .add_final(0)
+            .add_final(13)
+            .add_transition('a', 0, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.finals_sorted(), vec![0, 2, 9, 13, 40]);
+        assert!(DFA::empty_language(&['a']).finals_sorted().is_empty());
+    }
+
+    #[test]
+    fn test_dfa_quotient_by() {
+        // a(b|c)d with the two middle states 2 and 3 being distinct
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(4)
+            .add_final(5)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 1, 3)
+            .add_transition('d', 2, 4)
+            .add_transition('d', 3, 5)
+            .finalize()
+            .unwrap();
+        let quotient = dfa.quotient_by(|a,b| (a,b) == (3,2) || (a,b) == (4,5)).unwrap();
+        assert_eq!(quotient.stats().state_count, 4);
+        assert_eq!(quotient.next_state(1, 'c'), Some(2));
+        assert_eq!(quotient.finals_sorted(), vec![4]);
+        assert!(quotient.accepts_exactly(&["abd", "acd"]));
+
+        // merging 2 and 3 alone would need 'd' to lead to both 4 and 5
+        match dfa.quotient_by(|a,b| a+b == 5) {
+            Err(DFAError::DuplicatedTransition{symb: 'd', src: 2, ..}) => assert!(true),
+            _ => assert!(false),
+        }
+
+        // the relation is closed by transitivity: 1 ~ 2 ~ 3, giving a+
+        let dfa = DFA::from_words(&["aaa"]);
+        let quotient = dfa.quotient_by(|a,b| b == a+1 && (1..3).contains(&a)).unwrap();
+        assert_eq!(quotient.stats().state_count, 2);
+        assert!(quotient.test("a"));
+        assert!(quotient.test("aaaa"));
+        assert!(!quotient.test(""));
+
+        assert_eq!(dfa.quotient_by(|_,_| false).unwrap(), dfa);
+    }
+
+    #[test]
+    fn test_dfa_fixed_length() {
+        // all the words of length 3 over {a,b}
+        let mut builder = DFABuilder::new().add_start(0).add_final(3);
+        for state in 0..3 {
+            builder = builder.add_transition('a', state, state+1).add_transition('b', state, state+1);
+        }
+        assert_eq!(builder.finalize().unwrap().fixed_length(), Some(3));
+        // (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.fixed_length(), None);
+        // ab|c: the paths to the final state have different lengths
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 0, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.fixed_length(), None);
+        // a loop on a useless state does not matter
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 1, 3)
+            .add_transition('c', 3, 3)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.fixed_length(), Some(2));
+        assert_eq!(DFA::from_words(&["ab", "ba", "ab"]).fixed_length(), Some(2));
+        assert_eq!(DFA::epsilon_only().fixed_length(), Some(0));
+        assert_eq!(DFA::empty_language(&['a']).fixed_length(), None);
+    }
+
+    #[test]
+    fn test_dfa_intersection_over() {
+        // words over {a,b} with an even number of 'a'
+        let even = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        // words over {a,b} ending with 'b'
+        let ending = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        let product = even.intersection_over(&ending, &['a', 'b', 'c']);
+        let stats = product.stats();
+        assert!(stats.is_complete);
+        assert_eq!(stats.alphabet_size, 3);
+        for word in &["b", "aab", "abab", "bbb"] {
+            assert!(product.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["", "ab", "aa", "cb", "aacb", "bc", "c"] {
+            assert!(!product.test(word), "\"{}\" should be rejected", word);
+        }
+        // the symbols outside of the alphabet are dropped
+        let product = even.intersection_over(&ending, &['b']);
+        assert!(product.test("bb"));
+        assert!(!product.test("aab"));
+        assert_eq!(product.stats().alphabet_size, 1);
+    }
+
+    // Compiles the expressions written by `to_regex` back into DFAs.
+    fn compile_regex(regex: &str) -> DFA {
+        fn union(chars: &[char], pos: &mut usize) -> DFA {
+            let mut dfa = concat(chars, pos);
+            while *pos < chars.len() && chars[*pos] == '|' {
+                *pos += 1;
+                let other = concat(chars, pos);
+                let alphabet = dfa.alphabet().union(&other.alphabet()).cloned().collect();
+                dfa = dfa.product(&other, &alphabet, |a,b| a || b).minimize();
+            }
+            dfa
+        }
+        fn concat(chars: &[char], pos: &mut usize) -> DFA {
+            let mut dfa = DFA::epsilon_only();
+            while *pos < chars.len() && chars[*pos] != '|' && chars[*pos] != ')' {
+                let mut item = match chars[*pos] {
+                    '(' => {
+                        *pos += 1;
+                        let item = union(chars, pos);
+                        assert_eq!(chars[*pos], ')');
+                        item
+                    },
+                    'ε' => DFA::epsilon_only(),
+                    '\\' => {
+                        *pos += 1;
+                        DFA::from_words(&[&chars[*pos].to_string()])
+                    },
+                    symb => DFA::from_words(&[&symb.to_string()]),
+                };
+                *pos += 1;
+                while *pos < chars.len() && chars[*pos] == '*' {
+                    item = item.star_dfa();
+                    *pos += 1;
+                }
+                dfa = dfa.concat_dfa(&item);
+            }
+            dfa
+        }
+        let chars : Vec<_> = regex.chars().collect();
+        let mut pos = 0;
+        let dfa = union(&chars, &mut pos);
+        assert_eq!(pos, chars.len());
+        dfa.minimize()
+    }
+
+    #[test]
+    fn test_dfa_to_regex() {
+        // words over {a,b} with an even number of 'a'
+        let even = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        // ab|a(c|*)*d with a useless state
+        let mixed = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_final(4)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 1, 3)
+            .add_transition('*', 1, 3)
+            .add_transition('c', 3, 3)
+            .add_transition('*', 3, 3)
+            .add_transition('d', 3, 4)
+            .add_transition('d', 1, 4)
+            .add_transition('e', 2, 5)
+            .finalize()
+            .unwrap();
+        let single = DFA::from_words(&["abc", "ab", ""]);
+        for dfa in &[even, mixed, single, DFA::epsilon_only(), DFA::sigma_star(&['a', '|'])] {
+            let regex = dfa.to_regex();
+            assert_eq!(compile_regex(&regex), dfa.minimize(), "{}", regex);
+        }
+        assert_eq!(DFA::empty_language(&['a', 'b']).to_regex(), "∅");
+        assert_eq!(DFA::sigma_star(&['a']).to_regex(), "a*");
+        assert_eq!(DFA::from_words(&["a(", "b"]).to_regex(), "a\\(|b");
+    }
+
+    #[test]
+    fn test_dfa_match_ends() {
+        // (ab)+
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.match_ends("ababc"), vec![2, 4]);
+        assert_eq!(dfa.match_ends("abababa"), vec![2, 4, 6]);
+        assert!(dfa.match_ends("ba").is_empty());
+        assert!(dfa.match_ends("").is_empty());
+        // the empty prefix is reported when the starting state is final
+        let dfa = DFA::from_words(&["", "ß", "ßa"]);
+        assert_eq!(dfa.match_ends("ßab"), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn test_dfa_contains_substring() {
+        let dfa = DFA::contains_substring("ab", &['a', 'b']);
+        for word in &["ab", "aab", "bab", "bbabaa"] {
+            assert!(dfa.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["", "aa", "ba", "bbbaaa", "xaby"] {
+            assert!(!dfa.test(word), "\"{}\" should be rejected", word);
+        }
+        assert_eq!(dfa.stats().state_count, 3);
+        assert!(dfa.stats().is_complete);
+        // the failure links of a self-overlapping pattern
+        let dfa = DFA::contains_substring("aab", &['a', 'b', 'c']);
+        assert!(dfa.test("aaab"));
+        assert!(dfa.test("caaaabc"));
+        assert!(!dfa.test("aacab"));
+        assert!(!dfa.test("abaa"));
+        assert_eq!(dfa.minimize(), DFA::sigma_star(&['a', 'b', 'c'])
+            .concat_dfa(&DFA::from_words(&["aab"]))
+            .concat_dfa(&DFA::sigma_star(&['a', 'b', 'c'])));
+        // the empty pattern and a pattern outside of the alphabet
+        assert_eq!(DFA::contains_substring("", &['a']), DFA::sigma_star(&['a']));
+        assert!(DFA::contains_substring("ac", &['a', 'b']).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_contains_any() {
+        let (dfa, matches) = DFA::contains_any(&["ab", "ba"], &['a', 'b']);
+        for word in &["ab", "ba", "aab", "bba", "abb", "aaaaba"] {
+            assert!(dfa.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["", "a", "aaa", "bbb"] {
+            assert!(!dfa.test(word), "\"{}\" should be rejected", word);
+        }
+        assert!(dfa.stats().is_complete);
+        let mut finals = dfa.finals_sorted();
+        assert_eq!(finals.len(), 2);
+        let mut matched : Vec<_> = finals.drain(..).map(|state| matches[&state].clone()).collect();
+        matched.sort();
+        assert_eq!(matched, vec![vec![0], vec![1]]);
+        let ending = |word: &str| matches[&dfa.test_from(dfa.start(), word).unwrap()].clone();
+        assert_eq!(ending("aab"), vec![0]);
+        assert_eq!(ending("bbab"), vec![1]);
+        // the suffixes of a pattern matched at the same time, and a duplicated
+        // pattern
+        let (dfa, matches) = DFA::contains_any(&["abc", "bc", "c", "bc"], &['a', 'b', 'c']);
+        assert_eq!(matches[&dfa.test_from(0, "aabc").unwrap()], vec![0, 1, 2, 3]);
+        assert_eq!(matches[&dfa.test_from(0, "bbc").unwrap()], vec![1, 2, 3]);
+        assert_eq!(matches[&dfa.test_from(0, "ac").unwrap()], vec![2]);
+        // a pattern outside of the alphabet and the empty pattern
+        let (dfa, matches) = DFA::contains_any(&["ad", "b"], &['a', 'b']);
+        assert_eq!(dfa.minimize(), DFA::contains_substring("b", &['a', 'b']).minimize());
+        assert_eq!(matches.len(), 1);
+        let (dfa, _) = DFA::contains_any(&["", "a"], &['a']);
+        assert_eq!(dfa, DFA::sigma_star(&['a']));
+        let (dfa, matches) = DFA::contains_any(&[], &['a']);
+        assert!(dfa.is_empty());
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn test_dfa_declare_alphabet() {
+        // (ab)* over {a,b,c}
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .declare_alphabet(&['a', 'b'])
+            .declare_alphabet(&['c'])
+            .finalize()
+            .unwrap();
+        let declared : HashSet<_> = ['a', 'b', 'c'].iter().cloned().collect();
+        assert_eq!(dfa.declared_alphabet(), Some(&declared));
+        assert_eq!(dfa.alphabet(), declared);
+        // the unused symbol takes part in the complement
+        let complement = dfa.complement(&['a', 'b']);
+        assert!(complement.test("c"));
+        assert!(complement.test("abcab"));
+        assert!(!complement.test("abab"));
+        assert!(!dfa.is_universal(&[]));
+        let complete = dfa.complete_with_trap(2).unwrap();
+        assert!(complete.stats().is_complete);
+        assert_eq!(complete.stats().alphabet_size, 3);
+        // the declared alphabet survives the minimization
+        assert_eq!(dfa.minimize().declared_alphabet(), Some(&declared));
+        // the inferred alphabet is the fallback
+        let dfa = DFA::from_words(&["ab"]);
+        assert_eq!(dfa.declared_alphabet(), None);
+        assert!(!dfa.complement(&['a', 'b']).test("c"));
+        let dfa = DFA::sigma_star(&['a']);
+        assert!(dfa.is_universal(&['a']));
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 0)
+            .declare_alphabet(&['b'])
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_universal(&['a']));
+        // a* over {a,b}: the trap on b is a residual of every state
+        assert_eq!(dfa.nerode_index(), 2);
+        assert_eq!(dfa.residual_complexity(0), 2);
+        // the operations keep the union of the declared alphabets
+        let only_b : HashSet<_> = ['b'].iter().cloned().collect();
+        assert_eq!(dfa.reverse_dfa().declared_alphabet(), Some(&only_b));
+        assert_eq!(dfa.star_dfa().declared_alphabet(), Some(&only_b));
+        assert_eq!(dfa.power(0).declared_alphabet(), Some(&only_b));
+        assert_eq!(dfa.with_suffix_anything(&['c']).declared_alphabet(), Some(&only_b));
+        let other = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .declare_alphabet(&['c'])
+            .finalize()
+            .unwrap();
+        let both : HashSet<_> = ['b', 'c'].iter().cloned().collect();
+        assert_eq!(dfa.concat_dfa(&other).declared_alphabet(), Some(&both));
+        assert_eq!(dfa.synchronized_product(&other, ProductFinality::Both).declared_alphabet(), Some(&both));
+        assert_eq!(dfa.intersection_over(&other, &['a']).declared_alphabet(), Some(&both));
+        assert_eq!(union_all(&[dfa, other]).declared_alphabet(), Some(&both));
+        let renamed = DFA::from_words(&["a"]).compose_with_map(&HashMap::new()).unwrap();
+        assert_eq!(renamed.declared_alphabet(), None);
+    }
+
+    #[test]
+    fn test_dfa_as_complete() {
+        let completions = || COMPLETIONS.with(|completions| completions.get());
+        // (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let before = completions();
+        let complete = dfa.as_complete();
+        assert_eq!(complete.trap(), Some(2));
+        assert_eq!(complete.dfa().stats().state_count, 3);
+        assert!(complete.dfa().stats().is_complete);
+        assert_eq!(complete.dfa().minimize(), dfa.minimize());
+        assert_eq!(completions(), before + 1);
+        // the complements over the alphabet of the DFA reuse the completed form
+        for _ in 0..3 {
+            let complement = dfa.complement(&['a', 'b']);
+            assert!(complement.test("aba"));
+            assert!(!complement.test("abab"));
+            assert!(!dfa.is_universal(&['b']));
+        }
+        assert_eq!(completions(), before + 1);
+        // another alphabet is completed on demand
+        assert!(dfa.complement(&['c']).test("c"));
+        assert_eq!(completions(), before + 1);
+        // the cache does not take part in the equality
+        let other = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa, other);
+        // a complete DFA has no trap
+        let dfa = DFA::sigma_star(&['a']);
+        assert_eq!(dfa.as_complete().trap(), None);
+        assert_eq!(dfa.as_complete().dfa(), &dfa);
+    }
+
+    #[test]
+    fn test_dfa_find() {
+        // a multi-byte alphabet: [éà]+ß
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('é', 0, 1)
+            .add_transition('à', 0, 1)
+            .add_transition('é', 1, 1)
+            .add_transition('à', 1, 1)
+            .add_transition('ß', 1, 2)
+            .finalize()
+            .unwrap();
+        let input = "aéé€ßàéàß日本";
+        let (start,end) = dfa.find(input).unwrap();
+        assert!(input.is_char_boundary(start) && input.is_char_boundary(end));
+        assert_eq!(dfa.find_str(input), Some("àéàß"));
+        assert_eq!(dfa.find_str("ééß"), Some("ééß"));
+        assert_eq!(dfa.find_str("éé€ß"), None);
+        assert_eq!(dfa.find_str(""), None);
+        // every end reported lies on a character boundary
+        for (start,_) in input.char_indices() {
+            for end in dfa.match_ends(&input[start..]) {
+                assert!(input.is_char_boundary(start+end));
+            }
+        }
+        // leftmost first, then longest, the empty match included
+        let dfa = DFA::from_words(&["€", "€€", "日"]);
+        assert_eq!(dfa.find("x€€€日"), Some((1,7)));
+        assert_eq!(dfa.find_str("本日€"), Some("日"));
+        let dfa = DFA::from_words(&["", "ü"]);
+        assert_eq!(dfa.find("aü"), Some((0,0)));
+        assert_eq!(dfa.find_str("üa"), Some("ü"));
+        assert_eq!(dfa.find(""), Some((0,0)));
+    }
+
+    #[test]
+    fn test_dfa_to_csv() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 0, 2)
+            .add_transition('"', 1, 2)
+            .add_transition('\n', 2, 0)
+            .finalize()
+            .unwrap();
+        let csv = dfa.to_csv();
+        let rows : Vec<_> = csv.lines().collect();
+        assert_eq!(rows[0], "src,symbol,dest");
+        assert_eq!(rows[1], "0,\"a\",2");
+        assert_eq!(rows[2], "0,\"b\",1");
+        assert_eq!(rows[3], "1,\"\"\"\",2");
+        assert_eq!(csv.split('\n').filter(|row| row.starts_with("2,")).count(), 1);
+        assert!(csv.ends_with("2,\"\n\",0\n"));
+        assert_eq!(dfa.states_to_csv(), "state,start,final\n0,true,false\n1,false,false\n2,false,true\n");
+        let dfa = DFA::epsilon_only();
+        assert_eq!(dfa.to_csv(), "src,symbol,dest\n");
+        assert_eq!(dfa.states_to_csv(), "state,start,final\n0,true,true\n");
+    }
+
+    #[test]
+    fn test_dfa_builder_checkpoint() {
+        let common = DFABuilder::new()
+            .add_start(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .declare_alphabet(&['c'])
+            .unwrap();
+        let checkpoint = common.checkpoint();
+        // (ab)+
+        let loop_back = checkpoint
+            .checkpoint()
+            .add_final(2)
+            .add_transition('a', 2, 1)
+            .finalize()
+            .unwrap();
+        // abc
+        let straight = checkpoint
+            .add_final(3)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        assert!(straight.accepts_exactly(&["abc"]));
+        assert!(loop_back.test("abab"));
+        assert!(!loop_back.test("abc"));
+        assert_eq!(loop_back.declared_alphabet(), straight.declared_alphabet());
+        // the branches do not alter the checkpoint
+        assert!(common.checkpoint().finalize().is_err());
+        assert_eq!(common.add_final(2).finalize().unwrap().minimize(),
+                   DFA::from_words(&["ab"]).minimize());
+    }
+
+    #[test]
+    fn test_dfa_test_reversed() {
+        // a(b|c)*d
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 1)
+            .add_transition('c', 1, 1)
+            .add_transition('d', 1, 2)
+            .finalize()
+            .unwrap();
+        for word in &["ad", "abcd", "abbbd", "da", "dcba", "", "a", "abcda"] {
+            let reversed : String = word.chars().rev().collect();
+            assert_eq!(dfa.test_reversed(&reversed), dfa.test(word), "{}", word);
+        }
+        assert!(dfa.test_reversed("dcba"));
+        let dfa = DFA::from_words(&["abc", ""]);
+        assert_eq!(dfa.test_reversed("cba"), dfa.test("abc"));
+        assert!(dfa.test_reversed(""));
+        assert!(!dfa.test_reversed("ab"));
+    }
+
+    #[test]
+    fn test_dfa_words_of_length() {
+        // the words of length 3 over {a,b} starting with 'a' or ending with 'b'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(6)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 2)
+            .add_transition('a', 1, 3)
+            .add_transition('b', 1, 3)
+            .add_transition('a', 2, 4)
+            .add_transition('b', 2, 4)
+            .add_transition('a', 3, 6)
+            .add_transition('b', 3, 6)
+            .add_transition('b', 4, 6)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.words_of_length(3), vec!["aaa", "aab", "aba", "abb", "bab", "bbb"]);
+        for len in &[0, 1, 2, 4] {
+            assert!(dfa.words_of_length(*len).is_empty());
+        }
+        // the exact length among longer and shorter words, cycles included
+        let dfa = DFA::sigma_star(&['b', 'a']);
+        assert_eq!(dfa.words_of_length(0), vec![""]);
+        assert_eq!(dfa.words_of_length(2), vec!["aa", "ab", "ba", "bb"]);
+        let dfa = DFA::from_words(&["", "xy", "xyz", "yz"]);
+        assert_eq!(dfa.words_of_length(2), vec!["xy", "yz"]);
+        assert_eq!(dfa.words_of_length(0), vec![""]);
+    }
+
+    #[test]
+    fn test_dfa_finalize_or_default_final() {
+        // (ab)*, the starting state being made final
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize_or_default_final()
+            .unwrap();
+        assert_eq!(dfa.finals_sorted(), vec![0]);
+        assert!(dfa.test(""));
+        assert!(dfa.test("abab"));
+        assert!(!dfa.test("a"));
+        // the final states specified are kept
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .finalize_or_default_final()
+            .unwrap();
+        assert_eq!(dfa.finals_sorted(), vec![1]);
+        let dfa = DFABuilder::new().add_final(1).finalize_or_default_final();
+        assert_eq!(dfa, Err(DFAError::MissingStartingState));
+    }
+
+    #[test]
+    fn test_dfa_with_length_between() {
+        // (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let bounded = dfa.with_length_between(2, 4);
+        assert!(bounded.accepts_exactly(&["ab", "abab"]));
+        assert!(bounded.is_trim());
+        assert!(!bounded.test(""));
+        assert!(!bounded.test("ababab"));
+        assert!(dfa.with_length_between(0, 0).accepts_exactly(&[""]));
+        assert!(dfa.with_length_between(3, 3).is_empty());
+        assert!(dfa.with_length_between(4, 2).is_empty());
+        let bounded = dfa.with_length_between(0, 100);
+        assert!(bounded.test(&"ab".repeat(50)));
+        assert!(!bounded.test(&"ab".repeat(51)));
+    }
+
+    #[test]
+    fn test_dfa_has_ambiguous_longest_match() {
+        // the tokens "if" (0), [a-z]+ (1) and " "+ (2), the keyword colliding
+        // with the identifiers
+        let mut builder = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_final(2)
+            .add_final(3)
+            .add_final(4)
+            .add_transition(' ', 0, 4)
+            .add_transition(' ', 4, 4);
+        for symb in "abcdefghijklmnopqrstuvwxyz".chars() {
+            builder = builder
+                .add_transition(symb, 0, if symb == 'i' { 1 } else { 3 })
+                .add_transition(symb, 1, if symb == 'f' { 2 } else { 3 })
+                .add_transition(symb, 2, 3)
+                .add_transition(symb, 3, 3);
+        }
+        let dfa = builder.finalize().unwrap();
+        // "if" ends in the keyword state 2, "i" then "f" in the identifier state 3
+        assert!(dfa.has_ambiguous_longest_match("if"));
+        assert!(dfa.has_ambiguous_longest_match("ifx  if"));
+        assert!(dfa.has_ambiguous_longest_match("x+if"));
+        assert!(!dfa.has_ambiguous_longest_match("ifx iff i"));
+        assert!(!dfa.has_ambiguous_longest_match(""));
+        let tags : HashMap<_,_> =
+            [(1,vec![1]), (2,vec![0, 1]), (3,vec![1]), (4,vec![2])].iter().cloned().collect();
+        assert!(dfa.has_ambiguous_longest_match_tagged("if", &tags));
+        assert!(dfa.has_ambiguous_longest_match_tagged("ifx  if", &tags));
+        assert!(dfa.has_ambiguous_longest_match_tagged("x+if", &tags));
+        assert!(!dfa.has_ambiguous_longest_match_tagged("ifx iff i", &tags));
+        assert!(!dfa.has_ambiguous_longest_match_tagged("", &tags));
+        assert!(!dfa.has_ambiguous_longest_match_tagged("if", &HashMap::new()));
+        // a repeated tag is a single token type
+        let tags : HashMap<_,_> = [(2,vec![1, 1])].iter().cloned().collect();
+        assert!(!dfa.has_ambiguous_longest_match_tagged("if", &tags));
+    }
+
+    #[test]
+    fn test_dfa_shuffle() {
+        let nfa = DFA::from_words(&["ab"]).shuffle(&DFA::from_words(&["cd"]));
+        let interleavings = ["abcd", "acbd", "acdb", "cabd", "cadb", "cdab"];
+        for word in interleavings.iter() {
+            assert!(nfa.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["abdc", "bacd", "dcab", "abc", "abcdab", ""] {
+            assert!(!nfa.test(word), "\"{}\" should be rejected", word);
+        }
+        assert!(nfa.to_dfa().accepts_exactly(&interleavings));
+        // a shared symbol, and the empty word as a neutral element
+        let nfa = DFA::from_words(&["a"]).shuffle(&DFA::from_words(&["ab"]));
+        assert!(nfa.to_dfa().accepts_exactly(&["aab", "aba"]));
+        let dfa = DFA::from_words(&["xy", "z"]);
+        assert_eq!(dfa.shuffle(&DFA::epsilon_only()).to_dfa().minimize(), dfa.minimize());
+        // the empty language absorbs the other
+        let nfa = dfa.shuffle(&DFA::empty_language(&['a']));
+        assert!(nfa.to_dfa().is_empty());
+    }
+
+    #[test]
+    fn test_dfa_project_out() {
+        // b*ab*(cb*)*, 'b' being an internal event
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 1)
+            .add_transition('c', 1, 1)
+            .finalize()
+            .unwrap();
+        let projected = dfa.project_out('b');
+        for word in &["a", "ac", "acc"] {
+            assert!(projected.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["", "ab", "ca", "aa", "bac"] {
+            assert!(!projected.test(word), "\"{}\" should be rejected", word);
+        }
+        // ac*
+        let expected = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 1, 1)
+            .finalize()
+            .unwrap();
+        assert_eq!(projected.to_dfa().minimize(), expected.minimize());
+        // the erased symbol may lead to the only final state
+        let projected = DFA::from_words(&["abb", "bb"]).project_out('b');
+        assert!(projected.to_dfa().accepts_exactly(&["a", ""]));
+        assert!(DFA::from_words(&["ab"]).project_out('x').to_dfa().accepts_exactly(&["ab"]));
+    }
+
+    #[test]
+    fn test_dfa_substitute() {
+        // (xc)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('x', 0, 1)
+            .add_transition('c', 1, 0)
+            .finalize()
+            .unwrap();
+        let ab = DFA::from_words(&["ab"]);
+        let enfa = dfa.substitute('x', &ab);
+        for word in &["", "abc", "abcabc", "abcabcabc"] {
+            assert!(enfa.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["x", "xc", "ab", "abab", "abcab", "c"] {
+            assert!(!enfa.test(word), "\"{}\" should be rejected", word);
+        }
+        // several copies, a looping replacement and the empty word
+        let dfa = DFA::from_words(&["x-x", "y"]);
+        // (ab)*
+        let ab_star = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let enfa = dfa.substitute('x', &ab_star);
+        for word in &["-", "ab-", "-abab", "abab-ab", "y"] {
+            assert!(enfa.test(word), "\"{}\" should be accepted", word);
+        }
+        for word in &["x-x", "a-", "ab-ab-", ""] {
+            assert!(!enfa.test(word), "\"{}\" should be rejected", word);
+        }
+        // an empty replacement removes the words using the symbol
+        let enfa = dfa.substitute('x', &DFA::empty_language(&['a']));
+        assert!(enfa.to_dfa().accepts_exactly(&["y"]));
+    }
+
+    #[test]
+    fn test_dfa_is_star_free() {
+        // the words with an even number of 'a' count modulo 2
+        let even = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        assert!(!even.is_star_free());
+        // (ab)* is star-free although it uses a star
+        let ab_star = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert!(ab_star.is_star_free());
+        assert!(DFA::contains_substring("ab", &['a', 'b']).is_star_free());
+        assert!(DFA::contains_substring("aba", &['a', 'b', 'c']).is_star_free());
+        assert!(DFA::from_words(&["abc", "b", ""]).is_star_free());
+        assert!(DFA::sigma_star(&['a', 'b']).is_star_free());
+        assert!(DFA::empty_language(&['a']).is_star_free());
+        assert!(DFA::epsilon_only().is_star_free());
+        // (aaa)*b is not, the counter modulo 3 being followed by another state
+        let modulo = DFA::from_words(&["aaa"]).star_dfa().concat_dfa(&DFA::from_words(&["b"]));
+        assert!(!modulo.is_star_free());
+    }
+
+    #[test]
+    fn test_dfa_syntactic_monoid() {
+        // a*b*: the transformations of ε, a, b, ab and ba (the zero)
+        let a_b = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        let monoid = a_b.syntactic_monoid();
+        assert_eq!(monoid.size(), 5);
+        assert_eq!(monoid.elements()[0], vec![0, 1, 2]);
+        assert!(!monoid.is_group());
+        assert!(monoid.is_aperiodic());
+        // the words with a number of 'a' multiple of 3: the cyclic group Z/3Z
+        let modulo = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('a', 2, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('b', 1, 1)
+            .add_transition('b', 2, 2)
+            .finalize()
+            .unwrap();
+        let monoid = modulo.syntactic_monoid();
+        assert_eq!(monoid.size(), 3);
+        assert!(monoid.is_group());
+        assert!(!monoid.is_aperiodic());
+        // ab: ε, a, b, ab and the zero shared by aa, ba and bb
+        let monoid = DFA::from_words(&["ab"]).syntactic_monoid();
+        assert_eq!(monoid.size(), 5);
+        assert!(monoid.is_aperiodic());
+        assert_eq!(DFA::epsilon_only().syntactic_monoid().size(), 1);
+    }
+
+    #[test]
+    fn test_dfa_k_equivalent() {
+        // aab|bac: the states 1 and 4 reject the words of length at most 1 and
+        // are distinguished by "ab"
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 2, 3)
+            .add_transition('b', 0, 4)
+            .add_transition('a', 4, 5)
+            .add_transition('c', 5, 3)
+            .finalize()
+            .unwrap();
+        assert!(dfa.k_equivalent(1, 4, 0));
+        assert!(dfa.k_equivalent(1, 4, 1));
+        assert!(!dfa.k_equivalent(1, 4, 2));
+        assert!(!dfa.k_equivalent(1, 4, 10));
+        assert!(dfa.k_equivalent(1, 1, 10));
+        assert!(!dfa.k_equivalent(2, 3, 0));
+        assert!(!dfa.k_equivalent(2, 5, 1));
+        // a missing transition is equivalent to a transition to a dead state
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 2)
+            .add_transition('b', 2, 2)
+            .add_transition('c', 0, 3)
+            .finalize()
+            .unwrap();
+        for k in 0..5 {
+            assert!(dfa.k_equivalent(1, 2, k));
+        }
+    }
+
+    #[test]
+    fn test_dfa_disjoint_from() {
+        // identifiers starting with a letter and numbers
+        let identifiers = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 1)
+            .add_transition('b', 1, 1)
+            .add_transition('0', 1, 1)
+            .add_transition('1', 1, 1)
+            .finalize()
+            .unwrap();
+        let numbers = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('0', 0, 1)
+            .add_transition('1', 0, 1)
+            .add_transition('0', 1, 1)
+            .add_transition('1', 1, 1)
+            .finalize()
+            .unwrap();
+        assert_eq!(identifiers.disjoint_from(&numbers), Ok(()));
+        assert_eq!(numbers.disjoint_from(&identifiers), Ok(()));
+        assert_eq!(identifiers.disjoint_from(&DFA::empty_language(&['a'])), Ok(()));
+        // the witness is a shortest common word
+        let keywords = DFA::from_words(&["1", "abba", "b10", "if"]);
+        assert_eq!(identifiers.disjoint_from(&keywords), Err("b10".to_string()));
+        assert_eq!(numbers.disjoint_from(&keywords), Err("1".to_string()));
+        assert_eq!(DFA::epsilon_only().disjoint_from(&DFA::sigma_star(&['a'])), Err(String::new()));
+    }
+
+    #[test]
+    fn test_dfa_symbol_ranges() {
+        let mut builder = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('z', 1, 0);
+        for symb in "abcde".chars() {
+            builder = builder.add_transition(symb, 0, 1);
+        }
+        let dfa = builder.finalize().unwrap();
+        assert!(dfa.to_dot().contains("    0 -> 1 [label=\"a-e\"];\n"));
+        assert!(dfa.to_dot().contains("    1 -> 0 [label=\"z\"];\n"));
+        assert!(format!("{}", dfa).ends_with("TRANSITIONS:\n  (a-e,0) => 1\n  (z,1) => 0\n"));
+    }
+
+    #[test]
+    fn test_dfa_stats() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let stats = dfa.stats();
+        assert_eq!(stats, DFAStats {
+            state_count      : 4,
+            transition_count : 5,
+            final_count      : 1,
+            alphabet_size    : 3,
+            is_complete      : false,
+        });
+        assert_eq!(format!("{}", stats),
+                   "states: 4, transitions: 5, finals: 1, alphabet: 3, complete: false");
+    }
+
+    #[test]
+    fn test_dfa_builder() {
+        let _dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_dfa_degrees() {
+        // (ab)*c
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let degrees : Vec<_> = (0..5).map(|state| (dfa.out_degree(state),dfa.in_degree(state))).collect();
+        assert_eq!(degrees, vec![(2, 0), (1, 2), (2, 1), (0, 2), (0, 0)]);
+    }
+
+    #[test]
+    fn test_dfa_predecessors() {
+        // (ab)*c with a loop on 'd'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .add_transition('d', 1, 1)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.predecessors(0), vec![]);
+        assert_eq!(dfa.predecessors(1), vec![('a', 0), ('a', 2), ('d', 1)]);
+        assert_eq!(dfa.predecessors(3), vec![('c', 0), ('c', 2)]);
+        assert_eq!(dfa.predecessors(4), vec![]);
+    }
+
+    #[test]
+    fn test_dfa_self_loops() {
+        // whitespaces are skipped before and after an identifier made of 'a' and 'b'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_final(2)
+            .add_transition(' ', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 1)
+            .add_transition('b', 1, 1)
+            .add_transition(' ', 1, 2)
+            .add_transition(' ', 2, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.self_loops(), vec![(0, ' '), (1, 'a'), (1, 'b'), (2, ' ')]);
+    }
+
+    #[test]
+    fn test_dfa_visit_transitions() {
+        let dfa = DFA::from_words(&["then", "the", "this", "a", "ab"]);
+        // an order-dependent checksum of the edges
+        let checksum = |acc: u64, symb: char, src: usize, dest: usize| {
+            acc.wrapping_mul(31).wrapping_add(symb as u64 ^ (src as u64) << 21 ^ (dest as u64) << 42)
+        };
+        let mut visited = 0;
+        dfa.visit_transitions(|symb, src, dest| visited = checksum(visited, symb, src, dest));
+        let expected = dfa
+            .transitions()
+            .into_iter()
+            .fold(0, |acc,(symb,src,dest)| checksum(acc, symb, src, dest));
+        assert_eq!(visited, expected);
+        let mut count = 0;
+        DFA::epsilon_only().visit_transitions(|_, _, _| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_dfa_grouped_transitions() {
+        // [0-9]+(x|y)? with digits looping on the final state
+        let mut builder = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_final(2)
+            .add_transition('y', 1, 2)
+            .add_transition('x', 1, 2);
+        for digit in "9876543210".chars() {
+            builder = builder
+                .add_transition(digit, 0, 1)
+                .add_transition(digit, 1, 1);
+        }
+        let dfa = builder.finalize().unwrap();
+        let groups = dfa.grouped_transitions();
+        let digits : Vec<_> = "0123456789".chars().collect();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&(0, 1)], digits);
+        assert_eq!(groups[&(1, 1)], digits);
+        assert_eq!(groups[&(1, 2)], vec!['x', 'y']);
+        let total : usize = groups.values().map(Vec::len).sum();
+        assert_eq!(total, dfa.transitions().len());
+    }
+
+    #[test]
+    fn test_dfa_words() {
+        // (ab)*c with a dead state
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .add_transition('d', 1, 4)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.words(5), vec!["c", "abc", "ababc"]);
+        assert_eq!(dfa.words(0), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_dfa_words_bfs_config_frontier() {
+        // the starting state has 26 successors which all accept any word over {a-z}
+        let mut dfa = DFABuilder::new().add_start(0).add_final(1);
+        for symb in "abcdefghijklmnopqrstuvwxyz".chars() {
+            dfa = dfa.add_transition(symb, 0, 1).add_transition(symb, 1, 1);
+        }
+        let dfa = dfa.finalize().unwrap();
+        assert_eq!(dfa.words_bfs_config(1, 100).unwrap().len(), 26);
+        assert_eq!(dfa.words_bfs_config(2, 100), Err(EnumError::FrontierExceeded(26*26)));
+        assert_eq!(dfa.words_bfs_config(1, 25), Err(EnumError::FrontierExceeded(26)));
+    }
+
+    #[test]
+    fn test_dfa_iter_language_ordered() {
+        // identifiers made of a letter followed by letters and digits
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 1)
+            .add_transition('b', 1, 1)
+            .add_transition('0', 1, 1)
+            .finalize()
+            .unwrap();
+        let first = |order: &[char]| -> Vec<String> { dfa.iter_language_ordered(order).unwrap().take(6).collect() };
+        assert_eq!(first(&['0', 'a', 'b']), vec!["a", "b", "a0", "aa", "ab", "b0"]);
+        assert_eq!(first(&['b', 'a', '0']), vec!["b", "a", "bb", "ba", "b0", "ab"]);
+        // the natural order gives the order of `words`
+        assert_eq!(first(&['0', 'a', 'b', 'a']), dfa.words(2)[..6].to_vec());
+        assert_eq!(dfa.iter_language_ordered(&['z', 'b', 'c']).err(), Some(EnumError::MissingSymbol('0')));
+        // a finite language
+        let dfa = DFA::from_words(&["ba", "b", "ab", ""]);
+        let words : Vec<_> = dfa.iter_language_ordered(&['b', 'a']).unwrap().collect();
+        assert_eq!(words, vec!["", "b", "ba", "ab"]);
+        assert_eq!(DFA::empty_language(&['a']).iter_language_ordered(&['a']).unwrap().next(), None);
+    }
+
+    #[test]
+    fn test_dfa_is_trim() {
+        // (ab)*c with an unreachable state 4 and a dead state 5
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .add_transition('c', 4, 3)
+            .add_transition('d', 1, 5)
+            .add_transition('d', 5, 5)
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_trim());
+        let trimmed = dfa.trim();
+        assert!(trimmed.is_trim());
+        assert_eq!(trimmed.stats().state_count, 4);
+        assert_eq!(trimmed.stats().transition_count, 5);
+        for input in &["ababc", "c", "abd", "abdd", ""] {
+            assert!(dfa.test(input) == trimmed.test(input), "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_is_trim_empty_language() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_trim());
+        let trimmed = dfa.trim();
+        assert!(trimmed.is_trim());
+        assert!(trimmed.transitions().is_empty());
+        assert!(trimmed.finals().is_empty());
+    }
+
+    #[test]
+    fn test_dfa_accepts_exactly() {
+        // (ab){1,2}
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_final(4)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 3)
+            .add_transition('b', 3, 4)
+            .finalize()
+            .unwrap();
+        assert!(dfa.accepts_exactly(&["ab", "abab"]));
+        assert!(dfa.accepts_exactly(&["abab", "ab", "ab"]));
+        assert!(!dfa.accepts_exactly(&["ab", "abab", "ababab"]));
+        assert!(!dfa.accepts_exactly(&["ab"]));
+        assert!(!dfa.accepts_exactly(&[]));
+    }
+
+    #[test]
+    fn test_dfa_insert_word() {
+        let words = ["car", "cars", "cat", "cats", "do", "dog", "dogs"];
+        let dfa = DFA::from_words(&words).minimize();
+        let inserted = dfa.insert_word("dot");
+        for input in words.iter().chain(["dot"].iter()) {
+            assert!(inserted.test(input), "input false for: \"{}\"", input);
+        }
+        assert!(!inserted.test("dots"));
+        assert_eq!(inserted, DFA::from_words(&["car", "cars", "cat", "cats", "do", "dog", "dogs", "dot"]).minimize());
+        assert_eq!(inserted.insert_word("dot"), inserted);
+        assert!(DFA::from_words(&[]).insert_word("a").accepts_exactly(&["a"]));
+        // the dictionary built word by word is minimal at each step
+        let mut built = DFA::from_words(&[]);
+        for (i,word) in words.iter().enumerate() {
+            built = built.insert_word(word);
+            assert_eq!(built, DFA::from_words(&words[..i+1]).minimize(), "word: \"{}\"", word);
+        }
+        // the suffix "at" is shared, "bat" must not get an "s"
+        let inserted = DFA::from_words(&["bat", "cat"]).minimize().insert_word("cats");
+        assert!(inserted.accepts_exactly(&["bat", "cat", "cats"]));
+        assert_eq!(inserted, DFA::from_words(&["bat", "cat", "cats"]).minimize());
+        // (ab)*: the starting state is on a cycle and is cloned
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let inserted = dfa.insert_word("aba");
+        for &(input,expected_result) in &[("", true), ("ab", true), ("aba", true), ("a", false), ("ababa", false)] {
+            assert!(inserted.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_dfa_from_words() {
+        let dfa = DFA::from_words(&["then", "the", "this", ""]);
+        assert_eq!(dfa.words(4), vec!["", "the", "then", "this"]);
+        assert!(DFA::from_words(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_is_prefix_free() {
+        // Huffman code
+        let code = DFA::from_words(&["0", "10", "110", "111"]);
+        assert!(code.is_prefix_free());
+        // (0|10)*11 stops at the first "11"
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('0', 0, 0)
+            .add_transition('1', 0, 1)
+            .add_transition('0', 1, 0)
+            .add_transition('1', 1, 2)
+            .finalize()
+            .unwrap();
+        assert!(dfa.is_prefix_free());
+    }
+
+    #[test]
+    fn test_dfa_is_not_prefix_free() {
+        assert!(!DFA::from_words(&["0", "10", "101"]).is_prefix_free());
+        // a+ with a loop on the final state
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 1)
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_prefix_free());
+    }
+
+    #[test]
+    fn test_dfa_diff() {
+        // (ab)*c
+        let dfa1 = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        // the same DFA renumbered, where the edge ('c',2) => 3 goes to 2
+        let dfa2 = DFABuilder::new()
+            .add_start(10)
+            .add_final(13)
+            .add_transition('a', 10, 11)
+            .add_transition('c', 10, 13)
+            .add_transition('b', 11, 12)
+            .add_transition('a', 12, 11)
+            .add_transition('c', 12, 12)
+            .finalize()
+            .unwrap();
+        let diff = dfa1.diff(&dfa2);
+        assert_eq!(diff, AutomatonDiff {
+            removed_transitions : vec![('c', 2, 3)],
+            added_transitions   : vec![('c', 12, 12)],
+            removed_finals      : vec![],
+            added_finals        : vec![],
+        });
+        assert_eq!(format!("{}", diff), "- (c,2) => 3\n+ (c,12) => 12\n");
+        assert!(dfa1.diff(&dfa1).is_empty());
+        // only the numbering differs
+        let mut builder = DFABuilder::new().add_start(10).add_final(13);
+        for (symb,src,dest) in dfa1.transitions() {
+            builder = builder.add_transition(symb, src+10, dest+10);
+        }
+        assert!(dfa1.diff(&builder.finalize().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_diff_finals() {
+        let dfa1 = DFA::from_words(&["ab"]);
+        let dfa2 = DFA::from_words(&["a", "ab", "x"]);
+        let diff = dfa1.diff(&dfa2);
+        assert!(diff.removed_transitions.is_empty());
+        assert_eq!(diff.added_transitions, vec![('x', 0, 3)]);
+        assert!(diff.removed_finals.is_empty());
+        assert_eq!(diff.added_finals, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_dfa_is_isomorphic() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let permuted = DFABuilder::new()
+            .add_start(2)
+            .add_final(0)
+            .add_transition('a', 2, 3)
+            .add_transition('c', 2, 0)
+            .add_transition('b', 3, 1)
+            .add_transition('a', 1, 3)
+            .add_transition('c', 1, 0)
+            .finalize()
+            .unwrap();
+        assert!(dfa.is_isomorphic(&permuted));
+        assert!(permuted.is_isomorphic(&dfa));
+        // same language but an extra state
+        let different = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_final(4)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 4)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_isomorphic(&different));
+    }
+
+    #[test]
+    fn test_dfa_single_word() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 3)
+            .add_transition('d', 2, 4)
+            .add_transition('d', 4, 4)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.single_word(), Some("abc".to_string()));
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.single_word(), Some("".to_string()));
+    }
+
+    #[test]
+    fn test_dfa_single_word_none() {
+        // (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.single_word(), None);
+        // ab|abc
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.single_word(), None);
+        // empty language
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.single_word(), None);
+    }
+
+    #[test]
+    fn test_dfa_is_complement_of() {
+        // words ending with 'b'
+        let accepter = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        // words not ending with 'b', the same complete table with the finality swapped
+        let rejecter = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        assert!(accepter.is_complement_of(&rejecter, &['a','b']));
+        assert!(rejecter.is_complement_of(&accepter, &['a','b']));
+        // 'c' is rejected by both
+        assert!(!accepter.is_complement_of(&rejecter, &['a','b','c']));
+    }
+
+    #[test]
+    fn test_dfa_is_complement_of_near_miss() {
+        // words ending with 'b'
+        let accepter = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 1)
+            .finalize()
+            .unwrap();
+        // words ending with 'a', the empty word is missing
+        let rejecter = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 2)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 1, 1)
+            .add_transition('a', 2, 2)
+            .add_transition('b', 2, 1)
+            .finalize()
+            .unwrap();
+        assert!(!accepter.is_complement_of(&rejecter, &['a','b']));
+        // words ending with 'b' or empty overlap with the accepter
+        let overlapping = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(1)
+            .add_transition('a', 0, 2)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 1, 1)
+            .add_transition('a', 2, 2)
+            .add_transition('b', 2, 1)
+            .finalize()
+            .unwrap();
+        assert!(!accepter.is_complement_of(&overlapping, &['a','b']));
+    }
+
+    #[test]
+    fn test_dfa_is_universal() {
+        // words over {a,b} with at most one 'b', or with at least two
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(1)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 2)
+            .add_transition('b', 2, 2)
+            .finalize()
+            .unwrap();
+        assert!(dfa.is_universal(&['a','b']));
+        assert!(dfa.is_universal(&['a']));
+        assert!(!dfa.is_universal(&['a','b','c']));
+    }
+
+    #[test]
+    fn test_dfa_is_universal_not_universal() {
+        // words over {a,b} with at most one 'b'
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 1)
+            .finalize()
+            .unwrap();
+        assert!(!dfa.is_universal(&['a','b']));
+        assert!(dfa.is_universal(&['a']));
+        let complement = dfa.complement(&['a','b']);
+        assert!(!complement.is_empty());
+        assert!(complement.test("abab"));
+        assert!(!complement.test("aba"));
+    }
+
+    #[test]
+    fn test_dfa_minimize() {
+        // (ab)*c with duplicated states and an unreachable state
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_final(5)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 4)
+            .add_transition('c', 2, 5)
+            .add_transition('b', 4, 0)
+            .add_transition('c', 6, 3)
+            .finalize()
+            .unwrap();
+        let minimal = dfa.minimize();
+        assert_eq!(minimal.stats().state_count, 3);
+        assert_eq!(minimal.transitions(), vec![('a', 0, 1), ('c', 0, 2), ('b', 1, 0)]);
+        for input in &["", "c", "abc", "ababc", "abab", "abcc", "abac"] {
+            assert!(dfa.test(input) == minimal.test(input), "input false for: \"{}\"", input);
+        }
+        assert_eq!(minimal.minimize(), minimal);
+    }
+
+    #[test]
+    fn test_dfa_minimize_empty_language() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 0)
+            .finalize()
+            .unwrap();
+        let minimal = dfa.minimize();
+        assert_eq!(minimal.stats().state_count, 1);
+        assert!(minimal.is_empty());
+    }
+
+    #[test]
+    fn test_dfa_nerode_index() {
+        // (ab)* with a duplicated state: the classes are (ab)*, (ab)*a and the
+        // words which are not prefixes of (ab)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 3)
+            .add_transition('b', 3, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.nerode_index(), 3);
+        assert_eq!(dfa.complement(&['a', 'b']).nerode_index(), 3);
+        let empty = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(empty.nerode_index(), 1);
+    }
+
+    #[test]
+    fn test_dfa_distinguishing_string() {
+        // words over {0,1} ending with "01", with a duplicated state 3
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('0', 0, 1)
+            .add_transition('1', 0, 0)
+            .add_transition('0', 1, 3)
+            .add_transition('1', 1, 2)
+            .add_transition('0', 2, 1)
+            .add_transition('1', 2, 0)
+            .add_transition('0', 3, 3)
+            .add_transition('1', 3, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.distinguishing_string(0, 2), Some("".to_string()));
+        assert_eq!(dfa.distinguishing_string(0, 1), Some("1".to_string()));
+        assert_eq!(dfa.distinguishing_string(1, 3), None);
+        assert_eq!(dfa.distinguishing_string(3, 3), None);
+    }
+
+    #[test]
+    fn test_dfa_distinguishing_string_partial() {
+        // the states 0 and 3 only accept "aab" and "ab" respectively
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(4)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 2, 4)
+            .add_transition('a', 3, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.distinguishing_string(0, 3), Some("ab".to_string()));
+        assert_eq!(dfa.distinguishing_string(0, 4), Some("".to_string()));
+        assert_eq!(dfa.distinguishing_string(1, 3), None);
+    }
+
+    #[test]
+    fn test_dfa_compose_with_map() {
+        // (AB|c)*
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('A', 0, 1)
+            .add_transition('B', 1, 0)
+            .add_transition('c', 0, 0)
+            .finalize()
+            .unwrap();
+        let map : HashMap<_,_> = [('A', 'a'), ('B', 'b'), ('Z', 'z')].iter().cloned().collect();
+        let lower = dfa.compose_with_map(&map).unwrap();
+        let samples =
+            vec![("", true),
+                 ("ab", true),
+                 ("abcab", true),
+                 ("cc", true),
+                 ("AB", false),
+                 ("aB", false),
+                 ("C", false),];
+
+        for (input,expected_result) in samples {
+            assert!(lower.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+        let map : HashMap<_,_> = [('A', 'c')].iter().cloned().collect();
+        assert_eq!(dfa.compose_with_map(&map),
+                   Err(DFAError::DuplicatedTransition{symb: 'c', src: 0, existing: 1, attempted: 0}));
+    }
+
+    #[test]
+    fn test_dfa_accepts_all_substrings_of() {
+        // words over {a,b} without "bb"
+        let fragments = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .finalize()
+            .unwrap();
+        // (ab)*
+        let whole = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert!(fragments.accepts_all_substrings_of(&whole));
+        assert!(fragments.accepts_all_substrings_of(&DFA::from_words(&["abaab", "ba"])));
+        assert!(fragments.accepts_all_substrings_of(&DFA::from_words(&[])));
+        // "abba" has the factor "bb"
+        assert!(!fragments.accepts_all_substrings_of(&DFA::from_words(&["aba", "abba"])));
+        // the factor "c" is not accepted
+        assert!(!fragments.accepts_all_substrings_of(&DFA::from_words(&["acab"])));
+        // the empty factor is not accepted
+        assert!(!whole.complement(&['a', 'b']).accepts_all_substrings_of(&DFA::from_words(&["ab"])));
+    }
+
+    #[test]
+    fn test_dfa_with_suffix_anything() {
+        // ab
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        let alphabet : Vec<_> = "abcdefghijklmnopqrstuvwxyz".chars().collect();
+        let anything = dfa.with_suffix_anything(&alphabet);
+        let samples =
+            vec![("ab", true),
+                 ("abxyz", true),
+                 ("abab", true),
+                 ("abba", true),
                  ("", false),
-                 ("abc", true),
-                 ("c", true),
-                 ("ac", false),
-                 ("ababababababababababababababababababababc", true),];
+                 ("a", false),
+                 ("xab", false),
+                 ("aab", false),];
 
         for (input,expected_result) in samples {
-            assert!(dfa.test(input) == expected_result, "input false for: \"{}\"", input);
+            assert!(anything.test(input) == expected_result, "input false for: \"{}\"", input);
         }
+        assert!(!anything.test("ab!"));
+        assert!(!anything.is_universal(&alphabet));
     }
 
     #[test]
-    fn test_dfa_builder() {
-        let _dfa = DFABuilder::new()
+    fn test_dfa_language_fingerprint() {
+        // (ab)* built in two different ways
+        let dfa1 = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let dfa2 = DFABuilder::new()
+            .add_start(5)
+            .add_final(5)
+            .add_final(7)
+            .add_transition('a', 5, 6)
+            .add_transition('b', 6, 7)
+            .add_transition('a', 7, 8)
+            .add_transition('b', 8, 5)
+            .add_transition('c', 9, 5)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa1.language_fingerprint(), dfa2.language_fingerprint());
+        // (ab)+
+        let dfa3 = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .finalize()
+            .unwrap();
+        assert!(dfa1.language_fingerprint() != dfa3.language_fingerprint());
+        // the fingerprint does not depend on the run
+        assert_eq!(DFA::from_words(&["a"]).language_fingerprint(), 0x239e_fa5d_9dcb_0c64);
+    }
+
+    #[test]
+    fn test_dfa_right_quotient() {
+        // words over {a,b,c} ending with "abc"
+        let dfa = DFABuilder::new()
             .add_start(0)
             .add_final(3)
             .add_transition('a', 0, 1)
-            .add_transition('c', 0, 3)
+            .add_transition('b', 0, 0)
+            .add_transition('c', 0, 0)
+            .add_transition('a', 1, 1)
             .add_transition('b', 1, 2)
+            .add_transition('c', 1, 0)
             .add_transition('a', 2, 1)
+            .add_transition('b', 2, 0)
+            .add_transition('c', 2, 3)
+            .add_transition('a', 3, 1)
+            .add_transition('b', 3, 0)
+            .add_transition('c', 3, 0)
+            .finalize()
+            .unwrap();
+        let quotient = dfa.right_quotient("bc");
+        let samples =
+            vec![("a", true),
+                 ("cba", true),
+                 ("abca", true),
+                 ("", false),
+                 ("ab", false),
+                 ("abc", false),];
+
+        for (input,expected_result) in samples {
+            assert!(quotient.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+        assert_eq!(quotient.nerode_index(), 2);
+        assert!(dfa.right_quotient("").is_isomorphic(&dfa.minimize()));
+        assert!(dfa.right_quotient("d").is_empty());
+    }
+
+    #[test]
+    fn test_dfa_concat_dfa() {
+        let ab = DFA::from_words(&["ab"]);
+        let cd = DFA::from_words(&["cd"]);
+        let abcd = ab.concat_dfa(&cd);
+        assert!(abcd.test("abcd"));
+        assert!(!abcd.test("ab"));
+        assert!(!abcd.test("cdab"));
+        assert!(abcd.accepts_exactly(&["abcd"]));
+        assert_eq!(abcd, abcd.minimize());
+        assert_eq!(abcd.stats().state_count, 5);
+        // a* then (ab)*
+        let a_star = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 0)
+            .finalize()
+            .unwrap();
+        let ab_star = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let dfa = a_star.concat_dfa(&ab_star);
+        for input in &["", "a", "aaab", "abab", "aabab"] {
+            assert!(dfa.test(input), "input false for: \"{}\"", input);
+        }
+        for input in &["b", "aba", "abb", "ba"] {
+            assert!(!dfa.test(input), "input false for: \"{}\"", input);
+        }
+        assert!(ab.concat_dfa(&DFA::from_words(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_dfa_reverse_dfa() {
+        // words over {0,1} ending with "01"
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('0', 0, 1)
+            .add_transition('1', 0, 0)
+            .add_transition('0', 1, 1)
+            .add_transition('1', 1, 2)
+            .add_transition('0', 2, 1)
+            .add_transition('1', 2, 0)
+            .finalize()
+            .unwrap();
+        let reversed = dfa.reverse_dfa();
+        let samples =
+            vec![("10", true),
+                 ("100", true),
+                 ("1011", true),
+                 ("1", false),
+                 ("01", false),
+                 ("110", false),
+                 ("", false),];
+
+        for (input,expected_result) in samples {
+            assert!(reversed.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+        // 1 then 0 then anything
+        assert_eq!(reversed.stats().state_count, 3);
+    }
+
+    #[test]
+    fn test_dfa_auto_builder() {
+        let mut builder = DFAAutoBuilder::new();
+        let even = builder.state();
+        let odd = builder.state();
+        builder.start(even);
+        builder.accept(even);
+        builder.transition(even, 'a', odd);
+        builder.transition(odd, 'b', even);
+        let auto = builder.build().unwrap();
+        let manual = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(auto, manual);
+    }
+
+    #[test]
+    fn test_dfa_auto_builder_error() {
+        let mut builder = DFAAutoBuilder::new();
+        let s0 = builder.state();
+        let s1 = builder.state();
+        builder.start(s0);
+        builder.accept(s1);
+        builder.transition(s0, 'a', s1);
+        builder.transition(s0, 'a', s0);
+        builder.transition(s1, 'b', s0);
+        assert_eq!(builder.build(),
+                   Err(DFAError::DuplicatedTransition{symb: 'a', src: s0, existing: s1, attempted: s0}));
+    }
+
+    #[test]
+    fn test_dfa_builder_remove_transition() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .remove_transition('c', 0)
+            .remove_transition('d', 0)
             .add_transition('c', 2, 3)
             .finalize()
             .unwrap();
+        assert!(dfa.test("abc"));
+        assert!(!dfa.test("c"));
+        assert_eq!(dfa.transitions(), vec![('a', 0, 1), ('b', 1, 2), ('c', 2, 3)]);
+        // the removed transition can be inserted again
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .remove_transition('a', 0)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        assert!(dfa.test("a"));
+    }
+
+    #[test]
+    fn test_dfa_builder_set_transition() {
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .set_transition('a', 0, 2)
+            .set_transition('c', 2, 0)
+            .finalize()
+            .unwrap();
+        assert_eq!(dfa.transitions(), vec![('a', 0, 2), ('b', 1, 2), ('c', 2, 0)]);
+        assert!(dfa.test("a"));
+        assert!(dfa.test("aca"));
+        assert!(!dfa.test("ab"));
     }
 
     #[test]
@@ -405,10 +7778,9 @@ mod tests {
             .add_transition('c', 2, 3)
             .add_transition('a', 0, 2)
             .finalize();
-        match dfa {
-            Err(DFAError::DuplicatedTransition(sy,sr)) => assert!((sy,sr) == ('a',0)),
-            _ => assert!(false, "DuplicatedTransition expected."),
-        }
+        let err = dfa.unwrap_err();
+        assert_eq!(err, DFAError::DuplicatedTransition{symb: 'a', src: 0, existing: 1, attempted: 2});
+        assert_eq!(format!("{}", err), "Duplicated transition ('a',0): 1 already defined, 2 attempted.");
     }
 
     #[test]
@@ -417,10 +7789,7 @@ mod tests {
             .add_final(3)
             .add_transition('a', 0, 1)
             .finalize();
-        match dfa {
-            Err(DFAError::MissingStartingState) => assert!(true),
-            _ => assert!(false, "MissingStartingState expected."),
-        }
+        assert_eq!(dfa, Err(DFAError::MissingStartingState));
     }
 
     #[test]
@@ -429,9 +7798,6 @@ mod tests {
             .add_start(0)
             .add_transition('a', 0, 1)
             .finalize();
-        match dfa {
-            Err(DFAError::MissingFinalStates) => assert!(true),
-            _ => assert!(false, "MissingFinalStates expected."),
-        }
+        assert_eq!(dfa, Err(DFAError::MissingFinalStates));
     }
 }
@@ -10,3 +10,14 @@
 pub mod core;
 /// dfa core reader
 pub mod reader;
+/// dfa compact representation
+pub mod compact;
+/// dfa with outputs on the transitions or on the states
+pub mod output;
+/// dfa over the bytes with a dense transition table
+pub mod byte;
+
+pub use self::core::{union_all,intersect_all};
+/// dfa binary serialization
+#[cfg(feature = "binary")]
+pub mod binary;
@@ -0,0 +1,184 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+
+use dfa::core::DFA;
+
+/// The type `DFAWithOutput` is a `DFA` used as a Mealy machine: an output
+/// symbol is associated with the transitions and the run of an input emits the
+/// outputs of the transitions it follows. A transition without output emits
+/// nothing.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa::core::*;
+/// use automaton::dfa::output::*;
+/// use std::collections::HashMap;
+///
+/// fn main() {
+///     // (ab)*, each 'b' is written 'B'
+///     let dfa = DFABuilder::new()
+///         .add_start(0)
+///         .add_final(0)
+///         .add_transition('a', 0, 1)
+///         .add_transition('b', 1, 0)
+///         .finalize()
+///         .unwrap();
+///     let outputs : HashMap<_,_> = [(('a',0),'a'), (('b',1),'B')].iter().cloned().collect();
+///     let machine = DFAWithOutput::new(dfa, outputs);
+///     assert_eq!(machine.translate("abab"), Some("aBaB".to_string()));
+/// }
+/// ```
+#[derive(Debug,PartialEq,Eq)]
+pub struct DFAWithOutput {
+    dfa     : DFA,
+    outputs : HashMap<(char,usize),char>,
+}
+
+impl DFAWithOutput {
+    /// Creates a Mealy machine from a DFA and the outputs of its transitions,
+    /// indexed like the transitions by `(symb,src)`.
+    pub fn new(dfa: DFA, outputs: HashMap<(char,usize),char>) -> DFAWithOutput {
+        DFAWithOutput{dfa, outputs}
+    }
+
+    /// Returns the underlying DFA.
+    pub fn dfa(&self) -> &DFA {
+        &self.dfa
+    }
+
+    /// Returns the concatenation of the outputs of the transitions followed by
+    /// the run of the input, or `None` if the run falls off the transition
+    /// table. The state reached at the end of the run does not need to be final.
+    pub fn translate(&self, input: &str) -> Option<String> {
+        let mut output = String::new();
+        let mut state = self.dfa.start();
+        for c in input.chars() {
+            let next = self.dfa.next_state(state, c)?;
+            if let Some(&out) = self.outputs.get(&(c,state)) {
+                output.push(out);
+            }
+            state = next;
+        }
+        Some(output)
+    }
+}
+
+/// The type `DFAWithStateOutput` is a `DFA` used as a Moore machine: an output
+/// value is associated with the states and the run of an input emits the
+/// outputs of the states it visits, starting with the starting state. A state
+/// without output emits nothing.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa::core::*;
+/// use automaton::dfa::output::*;
+/// use std::collections::HashMap;
+///
+/// fn main() {
+///     // parity of the number of 'a'
+///     let dfa = DFABuilder::new()
+///         .add_start(0)
+///         .add_final(0)
+///         .add_transition('a', 0, 1)
+///         .add_transition('a', 1, 0)
+///         .finalize()
+///         .unwrap();
+///     let outputs : HashMap<_,_> = [(0,"even"), (1,"odd")].iter().cloned().collect();
+///     let machine = DFAWithStateOutput::new(dfa, outputs);
+///     assert_eq!(machine.state_output_sequence("aa"), Some(vec!["even", "odd", "even"]));
+/// }
+/// ```
+#[derive(Debug,PartialEq,Eq)]
+pub struct DFAWithStateOutput<O> {
+    dfa     : DFA,
+    outputs : HashMap<usize,O>,
+}
+
+impl<O: Clone> DFAWithStateOutput<O> {
+    /// Creates a Moore machine from a DFA and the outputs of its states.
+    pub fn new(dfa: DFA, outputs: HashMap<usize,O>) -> DFAWithStateOutput<O> {
+        DFAWithStateOutput{dfa, outputs}
+    }
+
+    /// Returns the underlying DFA.
+    pub fn dfa(&self) -> &DFA {
+        &self.dfa
+    }
+
+    /// Returns the outputs of the states visited by the run of the input, or
+    /// `None` if the run falls off the transition table. The state reached at
+    /// the end of the run does not need to be final.
+    pub fn state_output_sequence(&self, input: &str) -> Option<Vec<O>> {
+        let mut state = self.dfa.start();
+        let mut outputs : Vec<_> = self.outputs.get(&state).cloned().into_iter().collect();
+        for c in input.chars() {
+            state = self.dfa.next_state(state, c)?;
+            outputs.extend(self.outputs.get(&state).cloned());
+        }
+        Some(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::*;
+
+    #[test]
+    fn test_translate() {
+        // binary words, the bits are complemented and the separators '-' are
+        // dropped
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('0', 0, 0)
+            .add_transition('1', 0, 0)
+            .add_transition('-', 0, 1)
+            .add_transition('0', 1, 0)
+            .add_transition('1', 1, 0)
+            .finalize()
+            .unwrap();
+        let outputs : HashMap<_,_> =
+            [(('0',0),'1'), (('1',0),'0'), (('0',1),'1'), (('1',1),'0')].iter().cloned().collect();
+        let machine = DFAWithOutput::new(dfa, outputs);
+        assert_eq!(machine.translate("0110"), Some("1001".to_string()));
+        assert_eq!(machine.translate("01-10"), Some("1001".to_string()));
+        assert_eq!(machine.translate("01-"), Some("10".to_string()));
+        assert_eq!(machine.translate(""), Some("".to_string()));
+        assert_eq!(machine.translate("01--"), None);
+        assert_eq!(machine.translate("012"), None);
+    }
+
+    #[test]
+    fn test_state_output_sequence() {
+        // counts the symbols modulo 3
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 0)
+            .finalize()
+            .unwrap();
+        let outputs : HashMap<_,_> = [(0,0), (1,1), (2,2)].iter().cloned().collect();
+        let machine = DFAWithStateOutput::new(dfa, outputs);
+        assert_eq!(machine.state_output_sequence("abaab"), Some(vec![0, 1, 2, 0, 1, 2]));
+        assert_eq!(machine.state_output_sequence(""), Some(vec![0]));
+        assert_eq!(machine.state_output_sequence("abb"), None);
+    }
+}
@@ -7,6 +7,8 @@
 // except according to those terms.
 
 extern crate itertools;
+#[cfg(feature = "flate2")]
+extern crate flate2;
 
 use std::io;                           // Error
 use std::io::{BufReader,BufRead}; // read_to_string
@@ -16,9 +18,14 @@ use std::fmt;                          // Formatter, format!, Display, Debug, wr
 use std::error;
 use std::fs::File;                     // File, open
 use std::result;
+use std::collections::HashMap;
 use self::itertools::Itertools;        // fold_results
+#[cfg(feature = "flate2")]
+use self::flate2::read::GzDecoder;
 
 use dfa::core::{DFA,DFABuilder,DFAError,DFABuilding};
+use reader::{ReaderError,ReaderLimits,ReaderOptions};
+use dot;
 
 /// Type `DFAReaderError` describes the list of errors that can occur during
 /// the parsing of a DFA file.
@@ -42,6 +49,19 @@ pub enum DFAReaderError {
     Io(io::Error),
     /// Error `Parse` is relative to the parsing errors (a state is an intger).
     Parse(num::ParseIntError,usize),
+    /// Error `TooLong` means the specified line exceeds the `ReaderLimits` given to
+    /// one of the `*_limited` constructors.
+    TooLong(usize),
+    /// Error `IllformedState` means the state name on the specified line is not an
+    /// identifier (only alphanumeric characters and underscores are allowed).
+    IllformedState(usize),
+    /// Error `UnknownState` means the state `String` appears as the destination of a
+    /// transition on the specified line but is neither the starting state, a final
+    /// state nor the source of a transition.
+    UnknownState(String,usize),
+    /// Error `IllformedDot` means the specified line is not one of the statements
+    /// written by `DFA::to_dot`.
+    IllformedDot(usize),
 }
 
 impl fmt::Display for DFAReaderError {
@@ -54,6 +74,10 @@ impl fmt::Display for DFAReaderError {
             DFAReaderError::IllformedTransition(ref line) => write!(f, "Line {}: too much elements.", line),
             DFAReaderError::DFA(ref err,ref line) => write!(f, "Line {}: DFAError {}", line, err),
             DFAReaderError::Parse(ref err,ref line) => write!(f, "Line {}: parse error {}", line, err),
+            DFAReaderError::TooLong(ref line) => write!(f, "Line {}: the line exceeds the reader limits.", line),
+            DFAReaderError::IllformedDot(ref line) => write!(f, "Line {}: unexpected DOT statement.", line),
+            DFAReaderError::IllformedState(ref line) => write!(f, "Line {}: the state name is not an identifier.", line),
+            DFAReaderError::UnknownState(ref name,ref line) => write!(f, "Line {}: unknown state {}.", line, name),
         }
     }
 }
@@ -68,6 +92,10 @@ impl error::Error for DFAReaderError {
             DFAReaderError::IllformedTransition(_) => "Too much elements.",
             DFAReaderError::DFA(ref err,_) => err.description(),
             DFAReaderError::Parse(ref err,_) => err.description(),
+            DFAReaderError::TooLong(_) => "The line exceeds the reader limits.",
+            DFAReaderError::IllformedDot(_) => "Unexpected DOT statement.",
+            DFAReaderError::IllformedState(_) => "The state name is not an identifier.",
+            DFAReaderError::UnknownState(_,_) => "Unknown state.",
         }
     }
 
@@ -94,6 +122,22 @@ impl From<num::ParseIntError> for DFAReaderError {
     }
 }
 
+impl ReaderError for DFAReaderError {
+    fn line(&self) -> Option<usize> {
+        match *self {
+            DFAReaderError::IncompleteTransition(line) => Some(line),
+            DFAReaderError::IllformedTransition(line) => Some(line),
+            DFAReaderError::DFA(_,line) => Some(line),
+            DFAReaderError::Parse(_,line) => Some(line),
+            DFAReaderError::TooLong(line) => Some(line),
+            DFAReaderError::IllformedDot(line) => Some(line),
+            DFAReaderError::IllformedState(line) => Some(line),
+            DFAReaderError::UnknownState(_,line) => Some(line),
+            _ => None,
+        }
+    }
+}
+
 /// Alias for result::Result<T,DFAReaderError>.
 pub type Result<T> = result::Result<T,DFAReaderError>;
 
@@ -133,14 +177,42 @@ impl DFAReader {
     /// ```
     pub fn new_from_file<P: AsRef<Path>>(file_path: P) -> Result<DFA> {
         let file = try!(File::open(file_path));
-        let file = BufReader::new(file);
-        DFAReader::new_from_lines(&mut file.lines())
+        DFAReader::new_from_reader(BufReader::new(file))
     }
 
-    fn read_start(dfa: DFABuilder, lines : &mut Iterator<Item=(usize,io::Result<String>)>) -> Result<DFABuilder> {
+    /// Reads a DFA from any buffered reader, such as the standard input or a
+    /// decompressed stream. The format is the same as for `new_from_string`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::reader::*;
+    ///
+    /// fn main() {
+    ///     let dfa = DFAReader::new_from_reader("0\n1\na 0 1".as_bytes()).unwrap();
+    ///     assert!(dfa.test("a"));
+    /// }
+    /// ```
+    pub fn new_from_reader<R: BufRead>(reader: R) -> Result<DFA> {
+        DFAReader::new_from_lines(&mut reader.lines(), false)
+    }
+
+    /// Reads a DFA from a gzip-compressed file, which is decompressed on the fly
+    /// while it is read. The decompressed contents have the same format as for
+    /// `new_from_file`.
+    #[cfg(feature = "flate2")]
+    pub fn new_from_gz_file<P: AsRef<Path>>(file_path: P) -> Result<DFA> {
+        let file = try!(File::open(file_path));
+        DFAReader::new_from_reader(BufReader::new(GzDecoder::new(file)))
+    }
+
+    fn read_start<F>(dfa: DFABuilder, lines : &mut Iterator<Item=(usize,io::Result<String>)>, state: &mut F) -> Result<DFABuilder>
+        where F: FnMut(&str,usize) -> Result<usize> {
         let (nline,line) = try!(lines.next().ok_or(DFAReaderError::MissingStartingState));
         let line = try!(line);
-        let start = try!(DFAReader::parse_dfa_error(&line,nline));
+        let start = try!(state(&line,nline));
         let dfa = dfa.add_start(start);
         match dfa {
             Ok(dfa) => Ok(dfa),
@@ -148,23 +220,25 @@ impl DFAReader {
         }
     }
 
-    fn read_finals(dfa: DFABuilder, lines : &mut Iterator<Item=(usize,io::Result<String>)>) -> Result<DFABuilder> {
+    fn read_finals<F>(dfa: DFABuilder, lines : &mut Iterator<Item=(usize,io::Result<String>)>, options: &ReaderOptions, state: &mut F) -> Result<DFABuilder>
+        where F: FnMut(&str,usize) -> Result<usize> {
         let (nline,line) = try!(lines.next().ok_or(DFAReaderError::MissingFinalStates));
         let line = try!(line);
-        let dfa = try!(try!(line
-            .split_whitespace()
-            .map(|token| DFAReader::parse_dfa_error(token,nline))
+        let dfa = try!(try!(options
+            .tokens(&line)
+            .into_iter()
+            .map(|token| state(token,nline))
             .fold_results(Ok(dfa), |acc, elt| acc.add_final(elt)))
             .map_err(|e| DFAReaderError::DFA(e,nline)));
         Ok(dfa)
     }
 
-    fn read_transition(dfa: DFABuilder, line : (usize,io::Result<String>))-> Result<DFABuilder> {
+    fn read_transition<F>(dfa: DFABuilder, line : (usize,io::Result<String>), options: &ReaderOptions, state: &mut F)-> Result<DFABuilder>
+        where F: FnMut(&str,usize) -> Result<usize> {
         let (nline,line) = line;
         let line = try!(line);
-        let mut tokens = line.split_whitespace();
-        // can't fail because lines iterates over the non-empty line
-        let mut symbs = tokens.next().unwrap().chars();
+        let mut tokens = options.tokens(&line).into_iter();
+        let mut symbs = try!(tokens.next().ok_or(DFAReaderError::IncompleteTransition(nline))).chars();
         let symb = symbs.nth(0).unwrap();
         if symbs.next().is_some() {
             return Err(DFAReaderError::IllformedTransition(nline));
@@ -172,11 +246,11 @@ impl DFAReader {
         let src = try!(tokens
             .next()
             .ok_or(DFAReaderError::IncompleteTransition(nline))
-            .and_then(|contents| DFAReader::parse_dfa_error(contents,nline)));
+            .and_then(|contents| state(contents,nline)));
         let dest = try!(tokens
             .next()
             .ok_or(DFAReaderError::IncompleteTransition(nline))
-            .and_then(|contents| DFAReader::parse_dfa_error(contents,nline)));
+            .and_then(|contents| state(contents,nline)));
         if tokens.next().is_some() {
             return Err(DFAReaderError::IllformedTransition(nline));
         }
@@ -184,11 +258,18 @@ impl DFAReader {
         Ok(dfa)
     }
 
-    fn new_from_lines(lines : &mut Iterator<Item=io::Result<String>>) -> Result<DFA> {
+    fn new_from_lines(lines : &mut Iterator<Item=io::Result<String>>, finals_first: bool) -> Result<DFA> {
+        DFAReader::new_from_lines_with(lines, finals_first, &ReaderOptions::default(), &mut DFAReader::parse_dfa_error)
+    }
+
+    // `state` converts the tokens that represent states into integers.
+    // `finals_first` swaps the starting state line and the finals line.
+    fn new_from_lines_with<F>(lines : &mut Iterator<Item=io::Result<String>>, finals_first: bool, options: &ReaderOptions, state: &mut F) -> Result<DFA>
+        where F: FnMut(&str,usize) -> Result<usize> {
         let mut dfa = try!(DFABuilder::new().map_err(|e| DFAReaderError::DFA(e,0)));
         let mut lines = lines
             .map(|line| {
-                line.map(|contents| contents.split('#').nth(0).unwrap().trim().to_owned())
+                line.map(|contents| options.uncommented(&contents).to_owned())
             })
             .enumerate().map(|(nline,line)| (nline+1,line))
             .filter(|&(_,ref line)| {
@@ -196,10 +277,15 @@ impl DFAReader {
                 let line = line.as_ref();
                 line.is_err() || !line.unwrap().is_empty()
             });
-        dfa = try!(DFAReader::read_start(dfa, &mut lines));
-        dfa = try!(DFAReader::read_finals(dfa, &mut lines));
+        if finals_first {
+            dfa = try!(DFAReader::read_finals(dfa, &mut lines, options, state));
+            dfa = try!(DFAReader::read_start(dfa, &mut lines, state));
+        } else {
+            dfa = try!(DFAReader::read_start(dfa, &mut lines, state));
+            dfa = try!(DFAReader::read_finals(dfa, &mut lines, options, state));
+        }
         for line in lines {
-            dfa = try!(DFAReader::read_transition(dfa, line));
+            dfa = try!(DFAReader::read_transition(dfa, line, options, state));
         }
         dfa.finalize().map_err(|e| DFAReaderError::DFA(e,0))
     }
@@ -236,7 +322,264 @@ impl DFAReader {
     /// }
     /// ```
     pub fn new_from_string(dfa: &str) -> Result<DFA> {
-        DFAReader::new_from_lines(&mut dfa.lines().map(|line| Ok(line.to_string())))
+        DFAReader::new_from_lines(&mut dfa.lines().map(|line| Ok(line.to_string())), false)
+    }
+
+    /// Reads a DFA from a file whose finals line comes before the starting state
+    /// line.
+    ///
+    /// See `new_from_string_finals_first`.
+    pub fn new_from_file_finals_first<P: AsRef<Path>>(file_path: P) -> Result<DFA> {
+        let file = try!(File::open(file_path));
+        let file = BufReader::new(file);
+        DFAReader::new_from_lines(&mut file.lines(), true)
+    }
+
+    /// Reads a DFA from a `&str` whose finals line comes before the starting
+    /// state line, as emitted by some tools. The rest of the format is the same
+    /// as for `new_from_string`.
+    ///
+    /// # Description
+    ///
+    /// * `dfa` - The string representation of the DFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::reader::*;
+    ///
+    /// fn main() {
+    ///     // (ab)+
+    ///     let dfa =
+    ///         "2\n\
+    ///          0\n\
+    ///          a 0 1\n\
+    ///          b 1 2\n\
+    ///          a 2 1";
+    ///     let dfa = DFAReader::new_from_string_finals_first(dfa).unwrap();
+    ///     assert!(dfa.test("abab"));
+    /// }
+    /// ```
+    pub fn new_from_string_finals_first(dfa: &str) -> Result<DFA> {
+        DFAReader::new_from_lines(&mut dfa.lines().map(|line| Ok(line.to_string())), true)
+    }
+
+    /// Reads a DFA from a `&str` as `new_from_string`, the tokens of the lines
+    /// being separated by `options.delimiter` and the comments starting with
+    /// `options.comment`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::reader::*;
+    /// use automaton::reader::ReaderOptions;
+    ///
+    /// fn main() {
+    ///     // (ab)+
+    ///     let dfa =
+    ///         "0\n\
+    ///          2\n\
+    ///          a,0,1\n\
+    ///          b,1,2\n\
+    ///          a,2,1";
+    ///     let options = ReaderOptions{delimiter: ',', ..ReaderOptions::default()};
+    ///     let dfa = DFAReader::new_from_string_with_options(dfa, &options).unwrap();
+    ///     assert!(dfa.test("abab"));
+    /// }
+    /// ```
+    pub fn new_from_string_with_options(dfa: &str, options: &ReaderOptions) -> Result<DFA> {
+        let mut lines = dfa.lines().map(|line| Ok(line.to_string()));
+        DFAReader::new_from_lines_with(&mut lines, false, options, &mut DFAReader::parse_dfa_error)
+    }
+
+    /// Reads a DFA from a `&str` as `new_from_string`, after checking that no
+    /// line exceeds `limits`.
+    ///
+    /// # Errors
+    ///
+    /// Return a `DFAReaderError::TooLong` with the number of the first line exceeding
+    /// `limits`.
+    pub fn new_from_string_limited(dfa: &str, limits: &ReaderLimits) -> Result<DFA> {
+        match limits.first_exceeding(dfa.lines()) {
+            Some(nline) => Err(DFAReaderError::TooLong(nline)),
+            None => DFAReader::new_from_string(dfa),
+        }
+    }
+
+    /// Reads a DFA from a file as `new_from_file`. The reading stops on the
+    /// first line exceeding `limits`, without buffering more than
+    /// `limits.max_line_length` bytes of it, so that an untrusted file can not
+    /// exhaust the memory with a single line.
+    ///
+    /// # Errors
+    ///
+    /// Return a `DFAReaderError::TooLong` with the number of the first line exceeding
+    /// `limits`.
+    pub fn new_from_file_limited<P: AsRef<Path>>(file_path: P, limits: &ReaderLimits) -> Result<DFA> {
+        let file = try!(File::open(file_path));
+        let lines = try!(try!(limits.read_lines(BufReader::new(file))).map_err(DFAReaderError::TooLong));
+        DFAReader::new_from_lines(&mut lines.into_iter().map(Ok), false)
+    }
+
+    /// Reads a DFA from the subset of the Graphviz DOT format written by
+    /// `DFA::to_dot`: the node statements, whose `doublecircle` shape marks the
+    /// final states, the edge from the `start` point to the starting state and
+    /// the labelled edges. A label lists the symbols of the transitions between
+    /// two states, separated by commas, a range `a-e` standing for all the
+    /// symbols from `a` to `e`.
+    ///
+    /// # Errors
+    ///
+    /// Return a `DFAReaderError::IllformedDot` if a line is not one of these
+    /// statements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::reader::*;
+    ///
+    /// fn main() {
+    ///     let dfa =
+    ///         "digraph {\n\
+    ///          start [shape=point];\n\
+    ///          0 [shape=circle];\n\
+    ///          1 [shape=doublecircle];\n\
+    ///          start -> 0;\n\
+    ///          0 -> 1 [label=\"a-c,x\"];\n\
+    ///          }";
+    ///     let dfa = DFAReader::new_from_dot(dfa).unwrap();
+    ///     assert!(dfa.test("b"));
+    ///     assert!(dfa.test("x"));
+    ///     assert!(!dfa.test("d"));
+    /// }
+    /// ```
+    pub fn new_from_dot(dot: &str) -> Result<DFA> {
+        let mut dfa = try!(DFABuilder::new().map_err(|e| DFAReaderError::DFA(e,0)));
+        let mut start = None;
+        for (nline,line) in dot.lines().enumerate().map(|(nline,line)| (nline+1,line.trim())) {
+            match line {
+                "" | "digraph {" | "}" | "rankdir=LR;" | "start [shape=point];" => continue,
+                _ => (),
+            }
+            let illformed = || DFAReaderError::IllformedDot(nline);
+            let statement = try!(line.strip_suffix(';').ok_or_else(illformed));
+            if let Some(state) = statement.strip_prefix("start -> ") {
+                start = Some(try!(DFAReader::parse_dfa_error(state, nline)));
+                continue;
+            }
+            let (head,attributes) = try!(statement
+                .split_once(" [")
+                .and_then(|(head,attributes)| attributes.strip_suffix(']').map(|attributes| (head,attributes)))
+                .ok_or_else(illformed));
+            match head.split_once(" -> ") {
+                Some((src,dest)) => {
+                    let src = try!(DFAReader::parse_dfa_error(src, nline));
+                    let dest = try!(DFAReader::parse_dfa_error(dest, nline));
+                    let symbs = try!(attributes
+                        .strip_prefix("label=\"")
+                        .and_then(|label| label.strip_suffix('"'))
+                        .and_then(dot::parse_label)
+                        .ok_or_else(illformed));
+                    for symb in symbs {
+                        dfa = try!(dfa.add_transition(symb,src,dest).map_err(|e| DFAReaderError::DFA(e,nline)));
+                    }
+                },
+                None => {
+                    let state = try!(DFAReader::parse_dfa_error(head, nline));
+                    match attributes {
+                        "shape=doublecircle" => dfa = try!(dfa.add_final(state).map_err(|e| DFAReaderError::DFA(e,nline))),
+                        "shape=circle" => (),
+                        _ => return Err(illformed()),
+                    }
+                },
+            }
+        }
+        let start = try!(start.ok_or(DFAReaderError::MissingStartingState));
+        dfa.add_start(start).finalize().map_err(|e| DFAReaderError::DFA(e,0))
+    }
+
+    /// Reads a DFA whose states are named by identifiers from a file.
+    ///
+    /// See `new_from_string_with_names`.
+    pub fn new_from_file_with_names<P: AsRef<Path>>(file_path: P) -> Result<(DFA,HashMap<String,usize>)> {
+        let file = try!(File::open(file_path));
+        let file = BufReader::new(file);
+        DFAReader::new_named_from_lines(&mut file.lines())
+    }
+
+    /// Reads a DFA whose states are named by identifiers from a `&str`. The
+    /// states are numbered in the order of their first appearance and the table
+    /// mapping the names to the numbers is returned along with the DFA.
+    ///
+    /// # Description
+    ///
+    /// * `dfa` - The string representation of the DFA.
+    ///
+    /// # Errors
+    ///
+    /// Return a `DFAReaderError::IllformedState` if a state name is not made of
+    /// alphanumeric characters and underscores.
+    ///
+    /// Return a `DFAReaderError::UnknownState` if a state only appears as the
+    /// destination of transitions, which usually is a misspelled name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::dfa::reader::*;
+    ///
+    /// fn main() {
+    ///     // (ab)*
+    ///     let dfa =
+    ///         "even\n\
+    ///          even\n\
+    ///          a even odd\n\
+    ///          b odd even";
+    ///     let (dfa,names) = DFAReader::new_from_string_with_names(dfa).unwrap();
+    ///     assert_eq!(names["even"], 0);
+    ///     assert_eq!(names["odd"], 1);
+    ///     assert!(dfa.test("abab"));
+    /// }
+    /// ```
+    pub fn new_from_string_with_names(dfa: &str) -> Result<(DFA,HashMap<String,usize>)> {
+        DFAReader::new_named_from_lines(&mut dfa.lines().map(|line| Ok(line.to_string())))
+    }
+
+    fn new_named_from_lines(lines : &mut Iterator<Item=io::Result<String>>) -> Result<(DFA,HashMap<String,usize>)> {
+        let mut names = HashMap::new();
+        // line of the first appearance of each state
+        let mut appearances = Vec::new();
+        let dfa = {
+            let mut state = |name: &str, nline: usize| {
+                if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(DFAReaderError::IllformedState(nline));
+                }
+                let id = names.len();
+                if *names.entry(name.to_owned()).or_insert(id) == id {
+                    appearances.push(nline);
+                }
+                Ok(names[name])
+            };
+            try!(DFAReader::new_from_lines_with(lines, false, &ReaderOptions::default(), &mut state))
+        };
+        let mut introduced = dfa.finals().clone();
+        introduced.insert(dfa.start());
+        introduced.extend(dfa.transitions().iter().map(|&(_,src,_)| src));
+        match (0..appearances.len()).find(|id| !introduced.contains(id)) {
+            Some(id) => {
+                let name = names.iter().find(|&(_,&state)| state == id).unwrap().0;
+                Err(DFAReaderError::UnknownState(name.clone(),appearances[id]))
+            },
+            None => Ok((dfa,names)),
+        }
     }
 }
 
@@ -366,11 +709,65 @@ mod test {
              c 2 3\n\
              c 2 4";
         match DFAReader::new_from_string(model) {
-            Err(DFAReaderError::DFA(_,line)) => assert!(line == 4),
+            Err(DFAReaderError::DFA(DFAError::DuplicatedTransition{symb,src,existing,attempted},line)) => {
+                assert!(line == 4);
+                assert!((symb,src,existing,attempted) == ('c',2,3,4));
+            },
             _ => assert!(false, "DuplicatedTransition expected."),
         }
     }
 
+    #[test]
+    fn test_duplicated_transition_message() {
+        let err = DFAReader::new_from_string("0\n3\nc 2 3\nc 2 4").unwrap_err();
+        assert_eq!(format!("{}", err), "Line 4: DFAError Duplicated transition ('c',2): 3 already defined, 4 attempted.");
+    }
+
+    #[test]
+    fn test_named_states() {
+        let model =
+            "start            # starting state\n\
+             accept           # final state\n\
+             a start middle\n\
+             c start accept\n\
+             b middle loop_2\n\
+             a loop_2 middle\n\
+             c loop_2 accept";
+        let (dfa,names) = DFAReader::new_from_string_with_names(model).unwrap();
+        assert_eq!(names.len(), 4);
+        assert_eq!(names["start"], 0);
+        assert_eq!(names["accept"], 1);
+        assert_eq!(names["middle"], 2);
+        assert_eq!(names["loop_2"], 3);
+        assert_eq!(dfa.start(), 0);
+        assert!(dfa.test("ababc"));
+        assert!(!dfa.test("abab"));
+    }
+
+    #[test]
+    fn test_named_states_unknown_state() {
+        let model =
+            "start\n\
+             accept\n\
+             a start middle\n\
+             b middle acept";
+        match DFAReader::new_from_string_with_names(model) {
+            Err(DFAReaderError::UnknownState(name,line)) => assert!((name.as_str(),line) == ("acept",4)),
+            _ => assert!(false, "UnknownState expected."),
+        }
+    }
+
+    #[test]
+    fn test_named_states_illformed_state() {
+        let model =
+            "start state\n\
+             accept";
+        match DFAReader::new_from_string_with_names(model) {
+            Err(DFAReaderError::IllformedState(line)) => assert!(line == 1),
+            _ => assert!(false, "IllformedState expected."),
+        }
+    }
+
     #[test]
     fn test_read_from_fake_file() {
         let file = "fake.txt";
@@ -379,4 +776,165 @@ mod test {
             _ => assert!(false, "Io::Error expected."),
         }
     }
+
+    #[test]
+    fn test_finals_first() {
+        let standard =
+            "0\n\
+             2 3\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 0 3";
+        let finals_first =
+            "2 3  # finals\n\
+             0    # start\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 0 3";
+        let expected = DFAReader::new_from_string(standard).unwrap();
+        assert_eq!(DFAReader::new_from_string_finals_first(finals_first).unwrap(), expected);
+        match DFAReader::new_from_string_finals_first("2 3") {
+            Err(DFAReaderError::MissingStartingState) => assert!(true),
+            _ => assert!(false, "MissingStartingState expected."),
+        }
+    }
+
+    #[test]
+    fn test_limited() {
+        let limits = ReaderLimits{max_line_length: 8, max_tokens: 3};
+        assert!(DFAReader::new_from_string_limited("0\n1\na 0 1", &limits).is_ok());
+        match DFAReader::new_from_string_limited("0\n1 1 1 1\na 0 1", &limits) {
+            Err(DFAReaderError::TooLong(nline)) => assert_eq!(nline, 2),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_new_from_string_with_options() {
+        // (a|b|#)*c exported with commas, '#' being a symbol
+        let model =
+            "0\n\
+             1 ; comment\n\
+             a, 0, 0\n\
+             b,0,0\n\
+             #,0,0\n\
+             c,0,1";
+        let options = ReaderOptions{delimiter: ',', comment: ';'};
+        let dfa = DFAReader::new_from_string_with_options(model, &options).unwrap();
+        assert!(dfa.test("ab#ac"));
+        assert!(!dfa.test("abca"));
+        let options = ReaderOptions{delimiter: '\t', ..ReaderOptions::default()};
+        let dfa = DFAReader::new_from_string_with_options("0\n1\t2\na\t0\t1\nb\t1\t2", &options).unwrap();
+        assert_eq!(dfa, DFAReader::new_from_string("0\n1 2\na 0 1\nb 1 2").unwrap());
+        let options = ReaderOptions{delimiter: ',', ..ReaderOptions::default()};
+        match DFAReader::new_from_string_with_options("0\n1\na,0", &options) {
+            Err(DFAReaderError::IncompleteTransition(nline)) => assert_eq!(nline, 3),
+            _ => assert!(false),
+        }
+        match DFAReader::new_from_string_with_options("0\n1\na 0 1", &options) {
+            Err(DFAReaderError::IllformedTransition(nline)) => assert_eq!(nline, 3),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_new_from_dot() {
+        let model =
+            "0\n\
+             3\n\
+             a 0 1\n\
+             b 0 1\n\
+             c 0 1\n\
+             e 0 1\n\
+             , 0 2\n\
+             - 0 2\n\
+             \" 1 2\n\
+             \\ 1 3\n\
+             d 2 3\n\
+             d 3 3";
+        let dfa = DFAReader::new_from_string(model).unwrap();
+        assert_eq!(DFAReader::new_from_dot(&dfa.to_dot()).unwrap(), dfa);
+        let dfa = DFA::from_words(&["then", "the", "this", "a\nb"]);
+        assert_eq!(DFAReader::new_from_dot(&dfa.to_dot()).unwrap(), dfa);
+    }
+
+    #[test]
+    fn test_reparse_roundtrip() {
+        // (ab)+ with a dead state and an unreachable state
+        let model =
+            "0\n\
+             2\n\
+             a 0 1\n\
+             b 1 2\n\
+             a 2 1\n\
+             c 1 3\n\
+             c 4 2";
+        let dfas = [
+            DFAReader::new_from_string(model).unwrap(),
+            DFA::from_words(&["then", "the", "this", "a\nb", "\"", "\\-,"]),
+            DFA::sigma_star(&['a', 'b', 'c', 'd', 'x']),
+            DFA::contains_substring("aba", &['a', 'b', ',']),
+            DFA::epsilon_only(),
+            DFA::from_words(&["ab", "c"]).star_dfa(),
+        ];
+        for dfa in dfas.iter() {
+            assert!(dfa.reparse_roundtrip_ok(), "{}", dfa.to_dot());
+        }
+        // the reader requires a final state
+        assert!(!DFA::empty_language(&['a', 'b']).reparse_roundtrip_ok());
+    }
+
+    #[test]
+    fn test_new_from_dot_errors() {
+        let models = [
+            ("digraph {\n    0 [shape=circle];\n    0 -> 1 [label=\"a\"];\n}", None),
+            ("digraph {\n    start -> 0;\n    0 [shape=box];\n}", Some(3)),
+            ("digraph {\n    start -> 0;\n    0 -> 1 [label=\"a-\"];\n}", Some(3)),
+            ("digraph {\n    start -> 0;\n    0 -> 1 [color=red];\n}", Some(3)),
+            ("digraph {\n    start -> 0;\n    0 -> 1\n}", Some(3)),
+            ("digraph {\n    start -> x;\n}", Some(2)),
+        ];
+        for &(model,line) in models.iter() {
+            match DFAReader::new_from_dot(model) {
+                Err(DFAReaderError::MissingStartingState) => assert_eq!(line, None),
+                Err(DFAReaderError::IllformedDot(nline)) => assert_eq!(Some(nline), line),
+                Err(DFAReaderError::Parse(_,nline)) => assert_eq!(Some(nline), line),
+                _ => assert!(false, "error expected for: \"{}\"", model),
+            }
+        }
+        match DFAReader::new_from_dot("start -> 0;\n0 -> 1 [label=\"a,b\"];\n0 -> 2 [label=\"b\"];") {
+            Err(DFAReaderError::DFA(DFAError::DuplicatedTransition{..},3)) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "flate2")]
+    fn test_new_from_gz_file() {
+        use std::env;
+        use std::io::Write;
+        use std::fs;
+        use self::flate2::Compression;
+        use self::flate2::write::GzEncoder;
+
+        let model =
+            "0\n\
+             0\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 2 0";
+        let path = env::temp_dir().join(format!("automaton-{}.dfa.gz", std::process::id()));
+        {
+            let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+            encoder.write_all(model.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+        }
+        let dfa = DFAReader::new_from_gz_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(dfa.unwrap(), DFAReader::new_from_string(model).unwrap());
+        match DFAReader::new_from_gz_file("fake.txt.gz") {
+            Err(DFAReaderError::Io(_)) => assert!(true),
+            _ => assert!(false),
+        }
+    }
 }
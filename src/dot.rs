@@ -0,0 +1,190 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Graphviz DOT output shared by the automata.
+
+use std::collections::{HashSet,HashMap};
+use std::io::{self,Write};
+
+// Writes an automaton in the DOT format. The states and the edges are written
+// in increasing order so that the output is deterministic. An edge without
+// symbol is an epsilon transition. The symbols of the transitions between the
+// same states are coalesced into a single edge (see `symbol_ranges`).
+pub fn write_dot<W: Write>(w: &mut W,
+                           start: usize,
+                           finals: &HashSet<usize>,
+                           states: HashSet<usize>,
+                           edges: Vec<(usize,Option<char>,usize)>) -> io::Result<()> {
+    let mut states : Vec<_> = states.into_iter().collect();
+    states.sort();
+    try!(writeln!(w, "digraph {{"));
+    try!(writeln!(w, "    rankdir=LR;"));
+    try!(writeln!(w, "    start [shape=point];"));
+    for state in states {
+        let shape = if finals.contains(&state) { "doublecircle" } else { "circle" };
+        try!(writeln!(w, "    {} [shape={}];", state, shape));
+    }
+    try!(writeln!(w, "    start -> {};", start));
+    for (src,label,dest) in coalesce(edges) {
+        let label = match label {
+            Some(label) => label.chars().map(escape).collect(),
+            None => "ε".to_string(),
+        };
+        try!(writeln!(w, "    {} -> {} [label=\"{}\"];", src, dest, label));
+    }
+    writeln!(w, "}}")
+}
+
+// Groups the symbols of the edges between the same states and formats them
+// with `symbol_ranges`, the epsilon edges being kept apart with no label. The
+// groups are sorted by source state, then by first symbol.
+pub fn coalesce(edges: Vec<(usize,Option<char>,usize)>) -> Vec<(usize,Option<String>,usize)> {
+    let mut groups = HashMap::new();
+    let mut epsilons = Vec::new();
+    for (src,symb,dest) in edges {
+        match symb {
+            Some(symb) => groups.entry((src,dest)).or_insert_with(Vec::new).push(symb),
+            None => epsilons.push((src,None,dest)),
+        }
+    }
+    let mut groups : Vec<_> = groups
+        .into_iter()
+        .map(|((src,dest),mut symbs)| {
+            symbs.sort();
+            (src,symbs,dest)
+        })
+        .collect();
+    groups.sort_by_key(|&(src,ref symbs,dest)| (src,symbs[0],dest));
+    let mut edges : Vec<_> = groups
+        .into_iter()
+        .map(|(src,symbs,dest)| (src,Some(symbol_ranges(&symbs)),dest))
+        .collect();
+    edges.extend(epsilons);
+    edges.sort_by_key(|&(src,ref label,_)| (src,label.is_some()));
+    edges
+}
+
+// Formats sorted symbols as ranges: the runs of at least three consecutive
+// symbols are written `a-c` and the other symbols are written one by one, all
+// separated by commas. The symbols `-`, `,` and `\` are escaped by a backslash.
+pub fn symbol_ranges(symbs: &[char]) -> String {
+    let escaped = |symb: char| match symb {
+        '-' | ',' | '\\' => format!("\\{}", symb),
+        _ => symb.to_string(),
+    };
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < symbs.len() {
+        let mut j = i;
+        while j+1 < symbs.len() && symbs[j+1] as u32 == symbs[j] as u32 + 1 {
+            j += 1;
+        }
+        if j-i >= 2 {
+            parts.push(format!("{}-{}", escaped(symbs[i]), escaped(symbs[j])));
+        } else {
+            parts.extend(symbs[i..j+1].iter().map(|&symb| escaped(symb)));
+        }
+        i = j+1;
+    }
+    parts.join(",")
+}
+
+// Parses an edge label as written by `write_dot`, i.e. the DOT escaping of the
+// `symbol_ranges` of the symbols, and returns the symbols. Returns `None` if
+// the label is ill-formed.
+pub fn parse_label(label: &str) -> Option<Vec<char>> {
+    // the symbols with a flag telling whether they were escaped by a backslash,
+    // once the DOT escaping is removed
+    let mut tokens = Vec::new();
+    let mut chars = unescape(label)?.into_iter();
+    while let Some(symb) = chars.next() {
+        match symb {
+            '\\' => tokens.push((chars.next()?,true)),
+            _ => tokens.push((symb,false)),
+        }
+    }
+    let symbol = |token: Option<&(char,bool)>| match token {
+        Some(&('-',false)) | Some(&(',',false)) | None => None,
+        Some(&(symb,_)) => Some(symb),
+    };
+    let mut symbs = Vec::new();
+    let mut tokens = tokens.iter().peekable();
+    loop {
+        let first = symbol(tokens.next())?;
+        if tokens.peek() == Some(&&('-',false)) {
+            tokens.next();
+            let last = symbol(tokens.next())?;
+            if last < first {
+                return None;
+            }
+            symbs.extend(first..=last);
+        } else {
+            symbs.push(first);
+        }
+        match tokens.next() {
+            Some(&(',',false)) => (),
+            Some(_) => return None,
+            None => return Some(symbs),
+        }
+    }
+}
+
+fn unescape(label: &str) -> Option<Vec<char>> {
+    let mut symbs = Vec::new();
+    let mut chars = label.chars();
+    while let Some(symb) = chars.next() {
+        match symb {
+            '\\' => match chars.next()? {
+                'n' => symbs.push('\n'),
+                symb @ '"' | symb @ '\\' => symbs.push(symb),
+                _ => return None,
+            },
+            '"' => return None,
+            _ => symbs.push(symb),
+        }
+    }
+    Some(symbs)
+}
+
+fn escape(symb: char) -> String {
+    match symb {
+        '"' => "\\\"".to_string(),
+        '\\' => "\\\\".to_string(),
+        '\n' => "\\n".to_string(),
+        _ => symb.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_ranges() {
+        assert_eq!(symbol_ranges(&['a', 'b', 'c', 'd', 'e']), "a-e");
+        assert_eq!(symbol_ranges(&['0', '1', '2', 'a', 'b', 'x', 'y', 'z']), "0-2,a,b,x-z");
+        assert_eq!(symbol_ranges(&[',', '-', '.', 'a']), "\\,-.,a");
+        assert_eq!(symbol_ranges(&['\\']), "\\\\");
+        assert_eq!(symbol_ranges(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_label() {
+        assert_eq!(parse_label("a-e"), Some(vec!['a', 'b', 'c', 'd', 'e']));
+        assert_eq!(parse_label("0-2,a,b,x-z"), Some("012abxyz".chars().collect()));
+        assert_eq!(parse_label("\\\\,-.,a"), Some(vec![',', '-', '.', 'a']));
+        assert_eq!(parse_label("\\\",\\\\\\\\,\\n"), Some(vec!['"', '\\', '\n']));
+        for label in &["", "a,", ",a", "a-", "e-a", "ab", "a--c", "\"", "\\x", "\\\\"] {
+            assert_eq!(parse_label(label), None, "\"{}\" should be ill-formed", label);
+        }
+        let mut symbs : Vec<_> = "\n\"\\,-.09abcdfz".chars().collect();
+        symbs.sort();
+        let label : String = symbol_ranges(&symbs).chars().map(escape).collect();
+        assert_eq!(parse_label(&label), Some(symbs));
+    }
+}
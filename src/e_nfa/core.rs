@@ -12,9 +12,14 @@ use std::collections::{HashSet,HashMap};
 use std::fmt;                          // Formatter, format!, Display, Debug, write!
 use std::error;
 use std::result;
+use std::io::{self,Write};
+
+use dfa::core::DFA;
+use dot;
+use render::{self,Render};
 
 /// The `ENFAError` type.
-#[derive(Debug)]
+#[derive(Debug,PartialEq,Eq)]
 pub enum ENFAError {
     /// The transition from state `usize` with symbol `char` is defined twice.
     DuplicatedTransition(char,usize),
@@ -52,7 +57,7 @@ impl error::Error for ENFAError {
 
 /// The type `ENFA` represents a NonDeterministic Finite Automaton. The transitions
 /// of the automatonn are stored in a hashtable.
-#[derive(Debug)]
+#[derive(Debug,PartialEq,Eq)]
 pub struct ENFA {
     transitions   : HashMap<(char,usize),HashSet<usize>>,
     e_transitions : HashMap<usize,HashSet<usize>>,
@@ -60,6 +65,31 @@ pub struct ENFA {
     finals        : HashSet<usize>,
 }
 
+/// The type `ENFAStats` summarizes the size of a `ENFA`.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ENFAStats {
+    /// Number of states.
+    pub state_count      : usize,
+    /// Number of transitions.
+    pub transition_count : usize,
+    /// Number of epsilon transitions.
+    pub epsilon_count    : usize,
+    /// Number of final states.
+    pub final_count      : usize,
+    /// Number of symbols used by the transitions.
+    pub alphabet_size    : usize,
+    /// Whether each state has a transition for each symbol of the alphabet.
+    pub is_complete      : bool,
+}
+
+impl fmt::Display for ENFAStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "states: {}, transitions: {}, epsilons: {}, finals: {}, alphabet: {}, complete: {}",
+               self.state_count, self.transition_count, self.epsilon_count,
+               self.final_count, self.alphabet_size, self.is_complete)
+    }
+}
+
 /// The `ENFABuilder` follows the builder pattern and allows to create a Deterministic
 /// Finite Automaton. The builder is moved at each call so it is necessary to bind
 /// to a new variable the return value for each function of the builder.
@@ -185,6 +215,33 @@ impl ENFABuilder {
             finals: HashSet::new()
         })
     }
+
+    /// Returns the epsilon closure of `state` given the epsilon transitions
+    /// added so far, i.e. the states reachable from `state` through epsilon
+    /// transitions only, `state` included. The builder is left untouched, so
+    /// the closure can be previewed while the ENFA is being built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::e_nfa::core::*;
+    ///
+    /// fn main() {
+    ///     let builder = ENFABuilder::new()
+    ///         .add_e_transition(0, 1)
+    ///         .add_e_transition(1, 2)
+    ///         .add_transition('a', 2, 3)
+    ///         .unwrap();
+    ///     let mut closure : Vec<_> = builder.closure_preview(0).into_iter().collect();
+    ///     closure.sort();
+    ///     assert_eq!(closure, vec![0, 1, 2]);
+    /// }
+    /// ```
+    pub fn closure_preview(&self, state: usize) -> HashSet<usize> {
+        e_closure(&self.e_transitions, vec![state])
+    }
 }
 
 impl ENFABuilding for ENFABuilder {
@@ -308,28 +365,232 @@ impl ENFA {
     /// }
     /// ```
     pub fn test(&self, input: &str) -> bool {
-        let start : HashSet<_> = [self.start].iter().cloned().collect();
-        input
-            .chars()
-            .fold(Some(start), |states,c| {
-                states.and_then(|states| {
-                    states.iter().fold(Some(HashSet::new()), |acc, state| {
-                        acc.and_then(|acc| {
-                            self.transitions
-                                .get(&(c,*state))
-                                .map(|trans| acc.union(trans).cloned().collect())
-                                //.map(|nexts| {
-                                    //self.e_transitions
-                                        //.get(&*state)
-                                        //.map(|trans| nexts.union(nexts).cloned.collect())
-                                //})
-                        })
-                    })
-                })
+        let mut states = self.e_closure(vec![self.start]);
+        for c in input.chars() {
+            let nexts = states
+                .iter()
+                .filter_map(|&state| self.transitions.get(&(c,state)))
+                .flat_map(|dests| dests.iter().cloned())
+                .collect();
+            states = self.e_closure(nexts);
+            if states.is_empty() {
+                return false;
+            }
+        }
+        !states.is_disjoint(&self.finals)
+    }
+
+    // Builds an ENFA from its parts, without requiring any final state, for the
+    // constructions of the other modules whose language may be empty.
+    pub(crate) fn from_parts(transitions: HashMap<(char,usize),HashSet<usize>>,
+                             e_transitions: HashMap<usize,HashSet<usize>>,
+                             start: usize,
+                             finals: HashSet<usize>) -> ENFA {
+        ENFA{transitions, e_transitions, start, finals}
+    }
+
+    // Returns the states reachable from `states` through epsilon transitions,
+    // including `states`.
+    fn e_closure(&self, states: Vec<usize>) -> HashSet<usize> {
+        e_closure(&self.e_transitions, states)
+    }
+
+    /// Returns an equivalent ENFA whose epsilon transitions are closed under
+    /// transitivity: each state has a direct epsilon transition to every other
+    /// state of its epsilon closure. Unlike an epsilon elimination, the epsilon
+    /// transitions are kept, but a closure then needs a single step. The epsilon
+    /// self loops are dropped since they do not change the closures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::e_nfa::core::*;
+    ///
+    /// fn main() {
+    ///     let nfa = ENFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_e_transition(0, 1)
+    ///         .add_e_transition(1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let saturated = nfa.saturate_epsilon();
+    ///     assert_eq!(saturated.stats().epsilon_count, 3);
+    ///     assert!(saturated.test(""));
+    /// }
+    /// ```
+    pub fn saturate_epsilon(&self) -> ENFA {
+        let e_transitions = self.states()
+            .into_iter()
+            .filter_map(|state| {
+                let mut closure = self.e_closure(vec![state]);
+                closure.remove(&state);
+                if closure.is_empty() { None } else { Some((state,closure)) }
             })
-            .unwrap_or(HashSet::new())
-            .intersection(&self.finals)
-            .next().is_some()
+            .collect();
+        ENFA{transitions: self.transitions.clone(), e_transitions, start: self.start, finals: self.finals.clone()}
+    }
+
+    /// Writes the ENFA in the Graphviz DOT format, the epsilon transitions being
+    /// labelled with `ε`. The states and the transitions are written in
+    /// increasing order, so the output is deterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::e_nfa::core::*;
+    ///
+    /// fn main() {
+    ///     let nfa = ENFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let mut out = Vec::new();
+    ///     nfa.write_dot(&mut out).unwrap();
+    ///     assert!(String::from_utf8(out).unwrap().contains("0 -> 1 [label=\"a\"];"));
+    /// }
+    /// ```
+    pub fn write_dot<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        dot::write_dot(w, self.start, &self.finals, self.states(), self.edges())
+    }
+
+    /// Returns the ENFA in the Graphviz DOT format (see `write_dot`).
+    pub fn to_dot(&self) -> String {
+        let mut out = Vec::new();
+        // can't fail because writing in a vector never fails
+        self.write_dot(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Returns a DFA accepting the same language, built with the subset
+    /// construction over the epsilon closures. Only the subsets reachable from
+    /// the starting state are built, and the empty subset is left out so the DFA
+    /// may be incomplete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::e_nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // a?b
+    ///     let nfa = ENFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_e_transition(0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let dfa = nfa.to_dfa();
+    ///     assert!(dfa.test("ab"));
+    ///     assert!(dfa.test("b"));
+    ///     assert!(!dfa.test("a"));
+    /// }
+    /// ```
+    pub fn to_dfa(&self) -> DFA {
+        let mut alphabet : Vec<_> = self.alphabet().into_iter().collect();
+        alphabet.sort();
+        let mut start : Vec<_> = self.e_closure(vec![self.start]).into_iter().collect();
+        start.sort();
+        let mut ids = HashMap::new();
+        ids.insert(start.clone(), 0);
+        let mut subsets = vec![start];
+        let mut transitions = HashMap::new();
+        let mut finals = HashSet::new();
+        let mut i = 0;
+        while i < subsets.len() {
+            if subsets[i].iter().any(|state| self.finals.contains(state)) {
+                finals.insert(i);
+            }
+            telemetry!("determinization", subsets.len(), subsets.len()-i-1);
+            for &symb in alphabet.iter() {
+                let nexts = subsets[i]
+                    .iter()
+                    .filter_map(|&state| self.transitions.get(&(symb,state)))
+                    .flat_map(|dests| dests.iter().cloned())
+                    .collect();
+                let mut dest : Vec<_> = self.e_closure(nexts).into_iter().collect();
+                if dest.is_empty() {
+                    continue;
+                }
+                dest.sort();
+                let id = subsets.len();
+                let id = *ids.entry(dest.clone()).or_insert(id);
+                if id == subsets.len() {
+                    subsets.push(dest);
+                }
+                transitions.insert((symb,i), id);
+            }
+            i += 1;
+        }
+        DFA::from_parts(transitions, 0, finals)
+    }
+
+    /// Returns a summary of the size of the ENFA.
+    pub fn stats(&self) -> ENFAStats {
+        let states = self.states();
+        let alphabet = self.alphabet();
+        ENFAStats {
+            state_count      : states.len(),
+            transition_count : self.transitions.values().map(|dests| dests.len()).sum(),
+            epsilon_count    : self.e_transitions.values().map(|dests| dests.len()).sum(),
+            final_count      : self.finals.len(),
+            alphabet_size    : alphabet.len(),
+            is_complete      : self.transitions.len() == states.len()*alphabet.len(),
+        }
+    }
+
+    // Returns the transitions as edges, an epsilon transition having no symbol.
+    fn edges(&self) -> Vec<(usize,Option<char>,usize)> {
+        let mut edges : Vec<_> = self.transitions
+            .iter()
+            .flat_map(|(&(symb,src),dests)| dests.iter().map(move |&dest| (src,Some(symb),dest)))
+            .collect();
+        edges.extend(self.e_transitions
+            .iter()
+            .flat_map(|(&src,dests)| dests.iter().map(move |&dest| (src,None,dest))));
+        edges
+    }
+
+    fn states(&self) -> HashSet<usize> {
+        let mut states : HashSet<_> = self.finals.iter().cloned().collect();
+        states.insert(self.start);
+        for (&(_,src),dests) in self.transitions.iter() {
+            states.insert(src);
+            states.extend(dests.iter().cloned());
+        }
+        for (&src,dests) in self.e_transitions.iter() {
+            states.insert(src);
+            states.extend(dests.iter().cloned());
+        }
+        states
+    }
+
+    fn alphabet(&self) -> HashSet<char> {
+        self.transitions.keys().map(|&(symb,_)| symb).collect()
+    }
+}
+
+impl Render for ENFA {
+    fn to_dot(&self) -> String {
+        ENFA::to_dot(self)
+    }
+
+    fn to_table(&self) -> String {
+        render::table(self.start, &self.finals, self.states(), self.edges())
+    }
+
+    fn to_csv(&self) -> String {
+        render::csv(self.edges())
     }
 }
 
@@ -352,6 +613,21 @@ impl fmt::Display for ENFA {
     }
 }
 
+// Returns the states reachable from `states` through the epsilon transitions
+// `e_transitions`, `states` included.
+fn e_closure(e_transitions: &HashMap<usize,HashSet<usize>>, states: Vec<usize>) -> HashSet<usize> {
+    let mut reached : HashSet<_> = states.iter().cloned().collect();
+    let mut stack = states;
+    while let Some(state) = stack.pop() {
+        for &next in e_transitions.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+            if reached.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    reached
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,6 +658,198 @@ mod tests {
         }
     }
 
+    fn check(nfa: &ENFA, samples: &[(&str,bool)]) {
+        for &(input,expected_result) in samples {
+            assert!(nfa.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_nfa_epsilon_self_loop_on_start() {
+        // ab*
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_e_transition(0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 1)
+            .add_e_transition(1, 1)
+            .finalize()
+            .unwrap();
+        check(&nfa, &[("a", true), ("abbb", true), ("", false), ("b", false), ("aa", false), ("aba", false)]);
+    }
+
+    #[test]
+    fn test_nfa_epsilon_diamond() {
+        // 0 -> {1,2} -> 3 by epsilon, then (a|b) from 1 and 2 and c from 3
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(4)
+            .add_e_transition(0, 1)
+            .add_e_transition(0, 2)
+            .add_e_transition(1, 3)
+            .add_e_transition(2, 3)
+            .add_transition('a', 1, 4)
+            .add_transition('b', 2, 4)
+            .add_transition('c', 3, 4)
+            .finalize()
+            .unwrap();
+        check(&nfa, &[("a", true), ("b", true), ("c", true), ("", false), ("ab", false), ("d", false)]);
+    }
+
+    #[test]
+    fn test_nfa_epsilon_chain() {
+        // a, then a chain of 5 epsilon transitions, then b
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(7)
+            .add_transition('a', 0, 1)
+            .add_e_transition(1, 2)
+            .add_e_transition(2, 3)
+            .add_e_transition(3, 4)
+            .add_e_transition(4, 5)
+            .add_e_transition(5, 6)
+            .add_transition('b', 6, 7)
+            .finalize()
+            .unwrap();
+        check(&nfa, &[("ab", true), ("a", false), ("b", false), ("", false), ("abb", false), ("aab", false)]);
+    }
+
+    #[test]
+    fn test_nfa_closure_preview() {
+        let sorted = |closure: HashSet<usize>| {
+            let mut closure : Vec<_> = closure.into_iter().collect();
+            closure.sort();
+            closure
+        };
+        let builder = ENFABuilder::new().add_transition('a', 0, 1).unwrap();
+        assert_eq!(sorted(builder.closure_preview(0)), vec![0]);
+        // states unknown to the builder are their own closure
+        assert_eq!(sorted(builder.closure_preview(7)), vec![7]);
+        let builder = builder.add_e_transition(0, 2).unwrap();
+        assert_eq!(sorted(builder.closure_preview(0)), vec![0, 2]);
+        let builder = builder.add_e_transition(2, 3).unwrap();
+        assert_eq!(sorted(builder.closure_preview(0)), vec![0, 2, 3]);
+        assert_eq!(sorted(builder.closure_preview(2)), vec![2, 3]);
+        // a cycle back to the state
+        let builder = builder.add_e_transition(3, 0).add_e_transition(3, 4).unwrap();
+        assert_eq!(sorted(builder.closure_preview(0)), vec![0, 2, 3, 4]);
+        assert_eq!(sorted(builder.closure_preview(3)), vec![0, 2, 3, 4]);
+        assert_eq!(sorted(builder.closure_preview(1)), vec![1]);
+        let nfa = builder.add_start(0).add_final(4).finalize().unwrap();
+        assert!(nfa.test(""));
+    }
+
+    #[test]
+    fn test_nfa_saturate_epsilon() {
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(4)
+            .add_e_transition(0, 1)
+            .add_e_transition(1, 2)
+            .add_e_transition(2, 0)
+            .add_e_transition(2, 2)
+            .add_transition('a', 2, 3)
+            .add_e_transition(3, 4)
+            .add_e_transition(4, 5)
+            .add_transition('b', 5, 0)
+            .finalize()
+            .unwrap();
+        let saturated = nfa.saturate_epsilon();
+        for state in nfa.states() {
+            let mut closure = nfa.e_closure(vec![state]);
+            closure.remove(&state);
+            let targets = saturated.e_transitions.get(&state).cloned().unwrap_or_default();
+            assert_eq!(targets, closure, "wrong epsilon targets for {}", state);
+            assert_eq!(saturated.e_closure(vec![state]), nfa.e_closure(vec![state]));
+        }
+        assert_eq!(saturated.transitions, nfa.transitions);
+        let samples = [("", false), ("a", true), ("ab", false), ("aba", true), ("b", false)];
+        check(&nfa, &samples);
+        check(&saturated, &samples);
+        assert_eq!(saturated.saturate_epsilon(), saturated);
+    }
+
+    #[test]
+    fn test_nfa_epsilon_to_unreachable_final() {
+        // the final state 2 is only reachable through an epsilon transition
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 1)
+            .add_e_transition(1, 2)
+            .finalize()
+            .unwrap();
+        check(&nfa, &[("a", true), ("ab", true), ("abbb", true), ("", false), ("b", false), ("aa", false)]);
+        // the empty word is accepted through an epsilon transition from the start
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_e_transition(0, 1)
+            .add_transition('a', 0, 0)
+            .finalize()
+            .unwrap();
+        check(&nfa, &[("", true), ("a", true), ("aaa", true), ("b", false)]);
+    }
+
+    #[test]
+    fn test_nfa_epsilon_after_last_symbol() {
+        // (ab)+ where the final state and the loop are reached by epsilon
+        // transitions after the last symbol
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_e_transition(2, 3)
+            .add_e_transition(2, 0)
+            .finalize()
+            .unwrap();
+        check(&nfa, &[("ab", true), ("abab", true), ("", false), ("a", false), ("aba", false), ("ba", false)]);
+    }
+
+    #[test]
+    fn test_nfa_write_dot() {
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_e_transition(1, 2)
+            .finalize()
+            .unwrap();
+        let mut out = Vec::new();
+        nfa.write_dot(&mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert_eq!(dot, nfa.to_dot());
+        assert!(dot.contains("    0 -> 1 [label=\"a\"];\n    1 -> 2 [label=\"ε\"];\n"));
+    }
+
+    #[test]
+    fn test_nfa_stats() {
+        let nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 0, 2)
+            .add_e_transition(1, 3)
+            .add_e_transition(2, 3)
+            .add_e_transition(0, 2)
+            .finalize()
+            .unwrap();
+        let stats = nfa.stats();
+        assert_eq!(stats, ENFAStats {
+            state_count      : 4,
+            transition_count : 2,
+            epsilon_count    : 3,
+            final_count      : 1,
+            alphabet_size    : 1,
+            is_complete      : false,
+        });
+        assert_eq!(format!("{}", stats),
+                   "states: 4, transitions: 2, epsilons: 3, finals: 1, alphabet: 1, complete: false");
+    }
+
     #[test]
     fn test_nfa_builder() {
         let _nfa = ENFABuilder::new()
@@ -402,10 +870,7 @@ mod tests {
             .add_final(3)
             .add_transition('a', 0, 1)
             .finalize();
-        match nfa {
-            Err(ENFAError::MissingStartingState) => assert!(true),
-            _ => assert!(false, "MissingStartingState expected."),
-        }
+        assert_eq!(nfa, Err(ENFAError::MissingStartingState));
     }
 
     #[test]
@@ -414,9 +879,6 @@ mod tests {
             .add_start(0)
             .add_transition('a', 0, 1)
             .finalize();
-        match nfa {
-            Err(ENFAError::MissingFinalStates) => assert!(true),
-            _ => assert!(false, "MissingFinalStates expected."),
-        }
+        assert_eq!(nfa, Err(ENFAError::MissingFinalStates));
     }
 }
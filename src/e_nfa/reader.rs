@@ -19,6 +19,7 @@ use std::result;
 use self::itertools::Itertools;        // fold_results
 
 use e_nfa::core::{ENFA,ENFABuilder,ENFAError,ENFABuilding};
+use reader::{ReaderError,ReaderLimits};
 
 /// Type `ENFAReaderError` describes the list of errors that can occur during
 /// the parsing of a ENFA file.
@@ -42,6 +43,9 @@ pub enum ENFAReaderError {
     Io(io::Error),
     /// Error `Parse` is relative to the parsing errors (a state is an intger).
     Parse(num::ParseIntError,usize),
+    /// Error `TooLong` means the specified line exceeds the `ReaderLimits` given to
+    /// one of the `*_limited` constructors.
+    TooLong(usize),
 }
 
 impl fmt::Display for ENFAReaderError {
@@ -54,6 +58,7 @@ impl fmt::Display for ENFAReaderError {
             ENFAReaderError::IllformedTransition(ref line) => write!(f, "Line {}: too much elements.", line),
             ENFAReaderError::ENFA(ref err,ref line) => write!(f, "Line {}: ENFAError {}", line, err),
             ENFAReaderError::Parse(ref err,ref line) => write!(f, "Line {}: parse error {}", line, err),
+            ENFAReaderError::TooLong(ref line) => write!(f, "Line {}: the line exceeds the reader limits.", line),
         }
     }
 }
@@ -68,6 +73,7 @@ impl error::Error for ENFAReaderError {
             ENFAReaderError::IllformedTransition(_) => "Too much elements.",
             ENFAReaderError::ENFA(ref err,_) => err.description(),
             ENFAReaderError::Parse(ref err,_) => err.description(),
+            ENFAReaderError::TooLong(_) => "The line exceeds the reader limits.",
         }
     }
 
@@ -94,6 +100,19 @@ impl From<num::ParseIntError> for ENFAReaderError {
     }
 }
 
+impl ReaderError for ENFAReaderError {
+    fn line(&self) -> Option<usize> {
+        match *self {
+            ENFAReaderError::IncompleteTransition(line) => Some(line),
+            ENFAReaderError::IllformedTransition(line) => Some(line),
+            ENFAReaderError::ENFA(_,line) => Some(line),
+            ENFAReaderError::Parse(_,line) => Some(line),
+            ENFAReaderError::TooLong(line) => Some(line),
+            _ => None,
+        }
+    }
+}
+
 /// Alias for result::Result<T,ENFAReaderError>.
 pub type Result<T> = result::Result<T,ENFAReaderError>;
 
@@ -134,7 +153,7 @@ impl ENFAReader {
     pub fn new_from_file<P: AsRef<Path>>(file_path: P) -> Result<ENFA> {
         let file = try!(File::open(file_path));
         let file = BufReader::new(file);
-        ENFAReader::new_from_lines(&mut file.lines())
+        ENFAReader::new_from_lines(&mut file.lines(), false)
     }
 
     fn read_start(nfa: ENFABuilder, lines : &mut Iterator<Item=(usize,io::Result<String>)>) -> Result<ENFABuilder> {
@@ -211,7 +230,8 @@ impl ENFAReader {
         }
     }
 
-    fn new_from_lines(lines : &mut Iterator<Item=io::Result<String>>) -> Result<ENFA> {
+    // `finals_first` swaps the starting state line and the finals line.
+    fn new_from_lines(lines : &mut Iterator<Item=io::Result<String>>, finals_first: bool) -> Result<ENFA> {
         let mut nfa = try!(ENFABuilder::new().map_err(|e| ENFAReaderError::ENFA(e,0)));
         let mut lines = lines
             .map(|line| {
@@ -223,8 +243,13 @@ impl ENFAReader {
                 let line = line.as_ref();
                 line.is_err() || !line.unwrap().is_empty()
             });
-        nfa = try!(ENFAReader::read_start(nfa, &mut lines));
-        nfa = try!(ENFAReader::read_finals(nfa, &mut lines));
+        if finals_first {
+            nfa = try!(ENFAReader::read_finals(nfa, &mut lines));
+            nfa = try!(ENFAReader::read_start(nfa, &mut lines));
+        } else {
+            nfa = try!(ENFAReader::read_start(nfa, &mut lines));
+            nfa = try!(ENFAReader::read_finals(nfa, &mut lines));
+        }
         for line in lines {
             nfa = try!(ENFAReader::read_transition(nfa, line));
         }
@@ -264,7 +289,77 @@ impl ENFAReader {
     /// }
     /// ```
     pub fn new_from_string(nfa: &str) -> Result<ENFA> {
-        ENFAReader::new_from_lines(&mut nfa.lines().map(|line| Ok(line.to_string())))
+        ENFAReader::new_from_lines(&mut nfa.lines().map(|line| Ok(line.to_string())), false)
+    }
+
+    /// Reads a ENFA from a file whose finals line comes before the starting state
+    /// line.
+    ///
+    /// See `new_from_string_finals_first`.
+    pub fn new_from_file_finals_first<P: AsRef<Path>>(file_path: P) -> Result<ENFA> {
+        let file = try!(File::open(file_path));
+        let file = BufReader::new(file);
+        ENFAReader::new_from_lines(&mut file.lines(), true)
+    }
+
+    /// Reads a ENFA from a `&str` whose finals line comes before the starting
+    /// state line, as emitted by some tools. The rest of the format is the same
+    /// as for `new_from_string`.
+    ///
+    /// # Description
+    ///
+    /// * `nfa` - The string representation of the ENFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::e_nfa::reader::*;
+    ///
+    /// fn main() {
+    ///     // (ab)+
+    ///     let nfa =
+    ///         "2\n\
+    ///          0\n\
+    ///          a 0 1\n\
+    ///          b 1 2\n\
+    ///          a 2 1";
+    ///     let nfa = ENFAReader::new_from_string_finals_first(nfa).unwrap();
+    ///     assert!(nfa.test("abab"));
+    /// }
+    /// ```
+    pub fn new_from_string_finals_first(nfa: &str) -> Result<ENFA> {
+        ENFAReader::new_from_lines(&mut nfa.lines().map(|line| Ok(line.to_string())), true)
+    }
+
+    /// Reads a ENFA from a `&str` as `new_from_string`, after checking that no
+    /// line exceeds `limits`.
+    ///
+    /// # Errors
+    ///
+    /// Return a `ENFAReaderError::TooLong` with the number of the first line exceeding
+    /// `limits`.
+    pub fn new_from_string_limited(nfa: &str, limits: &ReaderLimits) -> Result<ENFA> {
+        match limits.first_exceeding(nfa.lines()) {
+            Some(nline) => Err(ENFAReaderError::TooLong(nline)),
+            None => ENFAReader::new_from_string(nfa),
+        }
+    }
+
+    /// Reads a ENFA from a file as `new_from_file`. The reading stops on the
+    /// first line exceeding `limits`, without buffering more than
+    /// `limits.max_line_length` bytes of it, so that an untrusted file can not
+    /// exhaust the memory with a single line.
+    ///
+    /// # Errors
+    ///
+    /// Return a `ENFAReaderError::TooLong` with the number of the first line exceeding
+    /// `limits`.
+    pub fn new_from_file_limited<P: AsRef<Path>>(file_path: P, limits: &ReaderLimits) -> Result<ENFA> {
+        let file = try!(File::open(file_path));
+        let lines = try!(try!(limits.read_lines(BufReader::new(file))).map_err(ENFAReaderError::TooLong));
+        ENFAReader::new_from_lines(&mut lines.into_iter().map(Ok), false)
     }
 }
 
@@ -395,4 +490,36 @@ mod test {
             _ => assert!(false, "Io::Error expected."),
         }
     }
+
+    #[test]
+    fn test_finals_first() {
+        let standard =
+            "0\n\
+             2 3\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 0 3";
+        let finals_first =
+            "2 3  # finals\n\
+             0    # start\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 0 3";
+        let expected = ENFAReader::new_from_string(standard).unwrap();
+        assert_eq!(ENFAReader::new_from_string_finals_first(finals_first).unwrap(), expected);
+        match ENFAReader::new_from_string_finals_first("2 3") {
+            Err(ENFAReaderError::MissingStartingState) => assert!(true),
+            _ => assert!(false, "MissingStartingState expected."),
+        }
+    }
+
+    #[test]
+    fn test_limited() {
+        let limits = ReaderLimits{max_line_length: 8, max_tokens: 3};
+        assert!(ENFAReader::new_from_string_limited("0\n1\na 0 1", &limits).is_ok());
+        match ENFAReader::new_from_string_limited("0\n1 1 1 1\na 0 1", &limits) {
+            Err(ENFAReaderError::TooLong(nline)) => assert_eq!(nline, 2),
+            _ => assert!(false),
+        }
+    }
 }
@@ -10,9 +10,28 @@
 
 //! Documentation for automaton api
 
+#[macro_use]
+mod macros;
+
 /// dfa api
 pub mod dfa;
 /// nfa api
 pub mod nfa;
 /// e_nfa api
 pub mod e_nfa;
+/// common reader api
+pub mod reader;
+/// common minimization api
+pub mod minimize;
+/// common acceptance api
+pub mod acceptor;
+/// common rendering api
+pub mod render;
+/// assertions for accept/reject test batteries
+#[cfg(feature = "testing")]
+pub mod testing;
+/// progress events of the long-running operations
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+
+mod dot;
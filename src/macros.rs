@@ -0,0 +1,206 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Builds a DFA from a literal description, without chaining the builder calls.
+/// The macro expands to the `Result<DFA>` returned by `DFABuilder::finalize`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate automaton;
+///
+/// fn main() {
+///     // (ab)*
+///     let dfa = dfa!{
+///         start: 0,
+///         finals: [0],
+///         transitions: { 'a' 0 => 1, 'b' 1 => 0 }
+///     }.unwrap();
+///     assert!(dfa.test("abab"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! dfa {
+    (start: $start:expr,
+     finals: [$($final:expr),* $(,)*],
+     transitions: { $($symb:tt $src:expr => $dest:expr),* $(,)* } $(,)*) => {{
+        use $crate::dfa::core::DFABuilding;
+        let dfa = $crate::dfa::core::DFABuilder::new().add_start($start);
+        $(let dfa = dfa.add_final($final);)*
+        $(let dfa = dfa.add_transition($symb, $src, $dest);)*
+        dfa.finalize()
+    }};
+}
+
+/// Builds a NFA from a literal description, without chaining the builder calls.
+/// The macro expands to the `Result<NFA>` returned by `NFABuilder::finalize`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate automaton;
+///
+/// fn main() {
+///     // (a|b)*b
+///     let nfa = nfa!{
+///         start: 0,
+///         finals: [1],
+///         transitions: { 'a' 0 => 0, 'b' 0 => 0, 'b' 0 => 1 }
+///     }.unwrap();
+///     assert!(nfa.test("abab"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! nfa {
+    (start: $start:expr,
+     finals: [$($final:expr),* $(,)*],
+     transitions: { $($symb:tt $src:expr => $dest:expr),* $(,)* } $(,)*) => {{
+        use $crate::nfa::core::NFABuilding;
+        let nfa = $crate::nfa::core::NFABuilder::new().add_start($start);
+        $(let nfa = nfa.add_final($final);)*
+        $(let nfa = nfa.add_transition($symb, $src, $dest);)*
+        nfa.finalize()
+    }};
+}
+
+/// Builds a ENFA from a literal description, without chaining the builder
+/// calls. The epsilon transitions are optional. The macro expands to the
+/// `Result<ENFA>` returned by `ENFABuilder::finalize`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate automaton;
+///
+/// fn main() {
+///     // a?b
+///     let nfa = enfa!{
+///         start: 0,
+///         finals: [2],
+///         transitions: { 'a' 0 => 1, 'b' 1 => 2 },
+///         e_transitions: { 0 => 1 }
+///     }.unwrap();
+///     assert!(nfa.test("b"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! enfa {
+    (start: $start:expr,
+     finals: [$($final:expr),* $(,)*],
+     transitions: { $($symb:tt $src:expr => $dest:expr),* $(,)* } $(,)*) => {
+        enfa!{
+            start: $start,
+            finals: [$($final),*],
+            transitions: { $($symb $src => $dest),* },
+            e_transitions: {}
+        }
+    };
+    (start: $start:expr,
+     finals: [$($final:expr),* $(,)*],
+     transitions: { $($symb:tt $src:expr => $dest:expr),* $(,)* },
+     e_transitions: { $($e_src:expr => $e_dest:expr),* $(,)* } $(,)*) => {{
+        use $crate::e_nfa::core::ENFABuilding;
+        let nfa = $crate::e_nfa::core::ENFABuilder::new().add_start($start);
+        $(let nfa = nfa.add_final($final);)*
+        $(let nfa = nfa.add_transition($symb, $src, $dest);)*
+        $(let nfa = nfa.add_e_transition($e_src, $e_dest);)*
+        nfa.finalize()
+    }};
+}
+
+// Reports a progress event to the telemetry callback (see `telemetry::report`).
+// The macro expands to nothing if the `telemetry` feature is disabled.
+macro_rules! telemetry {
+    ($operation:expr, $states:expr, $queue:expr) => {
+        #[cfg(feature = "telemetry")]
+        ::telemetry::report($operation, $states, $queue);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use dfa::core::*;
+    use nfa::core::*;
+    use e_nfa::core::*;
+
+    #[test]
+    fn test_dfa_macro() {
+        let dfa = dfa!{
+            start: 0,
+            finals: [3],
+            transitions: {
+                'a' 0 => 1,
+                'c' 0 => 3,
+                'b' 1 => 2,
+                'a' 2 => 1,
+                'c' 2 => 3,
+            }
+        };
+        let expected = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize();
+        assert_eq!(dfa, expected);
+        let duplicated = dfa!{ start: 0, finals: [1], transitions: { 'a' 0 => 1, 'a' 0 => 0 } };
+        assert_eq!(duplicated, Err(DFAError::DuplicatedTransition{symb: 'a', src: 0, existing: 1, attempted: 0}));
+        let missing_finals = dfa!{ start: 0, finals: [], transitions: {} };
+        assert_eq!(missing_finals, Err(DFAError::MissingFinalStates));
+    }
+
+    #[test]
+    fn test_nfa_macro() {
+        let nfa = nfa!{
+            start: 0,
+            finals: [1, 2],
+            transitions: { 'a' 0 => 1, 'a' 0 => 2, 'b' 2 => 2 }
+        };
+        let expected = NFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 0, 2)
+            .add_transition('b', 2, 2)
+            .finalize();
+        assert_eq!(nfa, expected);
+    }
+
+    #[test]
+    fn test_enfa_macro() {
+        let nfa = enfa!{
+            start: 0,
+            finals: [2],
+            transitions: { 'a' 0 => 1, 'b' 1 => 2 },
+            e_transitions: { 0 => 1, 2 => 0 }
+        };
+        let expected = ENFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_e_transition(0, 1)
+            .add_e_transition(2, 0)
+            .finalize();
+        assert_eq!(nfa, expected);
+        let nfa = enfa!{ start: 0, finals: [1], transitions: { 'a' 0 => 1 } };
+        let expected = ENFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .finalize();
+        assert_eq!(nfa, expected);
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dfa::core::DFA;
+use nfa::core::NFA;
+use e_nfa::core::ENFA;
+
+/// `Minimizable` is the trait shared by the automata (`DFA`, `NFA` and `ENFA`)
+/// which can be converted to their minimal DFA. Since the states of the minimal
+/// DFA are numbered in a canonical way (see `DFA::minimize`), two automata of
+/// any types recognize the same language if and only if their minimal DFAs are
+/// equal.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::minimize::Minimizable;
+/// use automaton::dfa::core::*;
+/// use automaton::nfa::core::*;
+///
+/// fn same_language<A: Minimizable, B: Minimizable>(a: &A, b: &B) -> bool {
+///     a.minimal_dfa() == b.minimal_dfa()
+/// }
+///
+/// fn main() {
+///     // a+
+///     let dfa = DFABuilder::new()
+///         .add_start(0)
+///         .add_final(1)
+///         .add_transition('a', 0, 1)
+///         .add_transition('a', 1, 1)
+///         .finalize()
+///         .unwrap();
+///     let nfa = NFABuilder::new()
+///         .add_start(0)
+///         .add_final(1)
+///         .add_transition('a', 0, 0)
+///         .add_transition('a', 0, 1)
+///         .finalize()
+///         .unwrap();
+///     assert!(same_language(&dfa, &nfa));
+/// }
+/// ```
+pub trait Minimizable {
+    /// Returns the minimal DFA accepting the language of the automaton.
+    fn minimal_dfa(&self) -> DFA;
+}
+
+impl Minimizable for DFA {
+    fn minimal_dfa(&self) -> DFA {
+        self.minimize()
+    }
+}
+
+impl Minimizable for NFA {
+    fn minimal_dfa(&self) -> DFA {
+        self.to_dfa().minimize()
+    }
+}
+
+impl Minimizable for ENFA {
+    fn minimal_dfa(&self) -> DFA {
+        self.to_dfa().minimize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::*;
+    use nfa::core::*;
+    use e_nfa::core::*;
+
+    #[test]
+    fn test_minimal_dfa() {
+        // (ab)*c
+        let dfa = DFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 1)
+            .add_transition('c', 2, 3)
+            .finalize()
+            .unwrap();
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 0, 4)
+            .add_transition('c', 0, 3)
+            .add_transition('b', 1, 0)
+            .add_transition('b', 4, 5)
+            .add_transition('c', 5, 3)
+            .finalize()
+            .unwrap();
+        let enfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(4)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_e_transition(2, 0)
+            .add_e_transition(0, 3)
+            .add_transition('c', 3, 4)
+            .finalize()
+            .unwrap();
+        let minimal = dfa.minimal_dfa();
+        assert_eq!(minimal.stats().state_count, 3);
+        assert!(minimal.is_isomorphic(&nfa.minimal_dfa()));
+        assert!(minimal.is_isomorphic(&enfa.minimal_dfa()));
+        assert_eq!(minimal, enfa.minimal_dfa());
+    }
+}
@@ -12,9 +12,15 @@ use std::collections::{HashSet,HashMap};
 use std::fmt;                          // Formatter, format!, Display, Debug, write!
 use std::error;
 use std::result;
+use std::mem;
+use std::io::{self,Write};
+
+use dfa::core::DFA;
+use dot;
+use render::{self,Render};
 
 /// The `NFAError` type.
-#[derive(Debug)]
+#[derive(Debug,PartialEq,Eq)]
 pub enum NFAError {
     /// The transition from state `usize` with symbol `char` is defined twice.
     DuplicatedTransition(char,usize),
@@ -52,13 +58,36 @@ impl error::Error for NFAError {
 
 /// The type `NFA` represents a NonDeterministic Finite Automaton. The transitions
 /// of the automatonn are stored in a hashtable.
-#[derive(Debug)]
+#[derive(Debug,PartialEq,Eq)]
 pub struct NFA {
     transitions : HashMap<(char,usize),HashSet<usize>>,
     start       : usize,
     finals      : HashSet<usize>,
 }
 
+/// The type `NFAStats` summarizes the size of a `NFA`.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct NFAStats {
+    /// Number of states.
+    pub state_count      : usize,
+    /// Number of transitions.
+    pub transition_count : usize,
+    /// Number of final states.
+    pub final_count      : usize,
+    /// Number of symbols used by the transitions.
+    pub alphabet_size    : usize,
+    /// Whether each state has a transition for each symbol of the alphabet.
+    pub is_complete      : bool,
+}
+
+impl fmt::Display for NFAStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "states: {}, transitions: {}, finals: {}, alphabet: {}, complete: {}",
+               self.state_count, self.transition_count, self.final_count,
+               self.alphabet_size, self.is_complete)
+    }
+}
+
 /// The `NFABuilder` follows the builder pattern and allows to create a Deterministic
 /// Finite Automaton. The builder is moved at each call so it is necessary to bind
 /// to a new variable the return value for each function of the builder.
@@ -137,6 +166,10 @@ pub struct NFABuilder {
 /// Alias for result::Result<T,NFAError>.
 pub type Result<T> = result::Result<T,NFAError>;
 
+// The subsets of states built by the subset construction and the transitions
+// between them.
+type Subsets = (Vec<Vec<usize>>,HashMap<(char,usize),usize>);
+
 /// NFABuilding is the trait assiociated to the NFABuilder type. Each NFABuilder
 /// should implement NFABuilding trait.
 ///
@@ -280,23 +313,555 @@ impl NFA {
     /// }
     /// ```
     pub fn test(&self, input: &str) -> bool {
-        let start : HashSet<_> = [self.start].iter().cloned().collect();
-        input
-            .chars()
-            .fold(Some(start), |states,c| {
-                states.and_then(|states| {
-                    states.iter().fold(Some(HashSet::new()), |acc, state| {
-                        acc.and_then(|acc| {
-                            self.transitions
-                                .get(&(c,*state))
-                                .map(|trans| acc.union(trans).cloned().collect())
-                        })
-                    })
-                })
+        self.test_streaming(input.chars())
+    }
+
+    /// Test if the sequence of symbols produced by an iterator is a word of the
+    /// language defined by the NFA.
+    ///
+    /// The input is consumed lazily and the set of current states is stored in
+    /// two bitsets that are reused at each step, so the memory used does not
+    /// depend on the length of the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (ab)*
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 0)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let input = (0..1000).map(|i| if i%2 == 0 { 'a' } else { 'b' });
+    ///     assert!(nfa.test_streaming(input));
+    /// }
+    /// ```
+    pub fn test_streaming<I: Iterator<Item=char>>(&self, input: I) -> bool {
+        let states : Vec<usize> = self.states().into_iter().collect();
+        let index : HashMap<usize,usize> = states.iter().enumerate().map(|(i,&state)| (state,i)).collect();
+        let mut current = vec![0u64; (states.len() + 63) / 64];
+        let mut next = current.clone();
+        let start = index[&self.start];
+        current[start/64] |= 1 << (start%64);
+        for c in input {
+            let mut alive = false;
+            for word in next.iter_mut() {
+                *word = 0;
+            }
+            for (w,&word) in current.iter().enumerate() {
+                let mut word = word;
+                while word != 0 {
+                    let i = w*64 + word.trailing_zeros() as usize;
+                    word &= word-1;
+                    for dest in self.transitions.get(&(c,states[i])).into_iter().flat_map(|dests| dests.iter()) {
+                        let j = index[dest];
+                        next[j/64] |= 1 << (j%64);
+                        alive = true;
+                    }
+                }
+            }
+            if !alive {
+                return false;
+            }
+            mem::swap(&mut current, &mut next);
+        }
+        self.finals.iter().any(|state| {
+            let i = index[state];
+            current[i/64] & (1 << (i%64)) != 0
+        })
+    }
+
+    /// Returns a DFA accepting the same language, built with the subset
+    /// construction. Only the subsets reachable from the starting state are
+    /// built, and the empty subset is left out so the DFA may be incomplete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*ab
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let dfa = nfa.to_dfa();
+    ///     assert!(dfa.test("abab"));
+    ///     assert!(!dfa.test("aba"));
+    /// }
+    /// ```
+    pub fn to_dfa(&self) -> DFA {
+        // can't fail because the number of subsets is not bounded
+        let (subsets,transitions) = self.subset_construction(usize::MAX, true).unwrap();
+        let finals = subsets
+            .iter()
+            .enumerate()
+            .filter(|&(_,subset)| subset.iter().any(|state| self.finals.contains(state)))
+            .map(|(id,_)| id)
+            .collect();
+        DFA::from_parts(transitions, 0, finals)
+    }
+
+    /// Returns a human-readable trace of the subset construction performed by
+    /// `to_dfa`. Each state of the DFA is listed in the order of its creation
+    /// with the subset of states of the NFA it represents, followed by its
+    /// transitions, each with its symbol and the resulting subset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*ab
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let trace = nfa.to_dfa_trace();
+    ///     assert_eq!(trace, "state 0 = {0} (start)\n  \
+    ///                          a -> {0,1} = state 1\n  \
+    ///                          b -> {0} = state 0\n\
+    ///                        state 1 = {0,1}\n  \
+    ///                          a -> {0,1} = state 1\n  \
+    ///                          b -> {0,2} = state 2\n\
+    ///                        state 2 = {0,2} (final)\n  \
+    ///                          a -> {0,1} = state 1\n  \
+    ///                          b -> {0} = state 0\n");
+    /// }
+    /// ```
+    pub fn to_dfa_trace(&self) -> String {
+        // can't fail because the number of subsets is not bounded
+        let (subsets,transitions) = self.subset_construction(usize::MAX, true).unwrap();
+        let mut alphabet : Vec<_> = self.alphabet().into_iter().collect();
+        alphabet.sort();
+        let subset = |id: usize| {
+            let states : Vec<_> = subsets[id].iter().map(|state| state.to_string()).collect();
+            format!("{{{}}}", states.join(","))
+        };
+        let mut trace = String::new();
+        for (id,states) in subsets.iter().enumerate() {
+            trace.push_str(&format!("state {} = {}", id, subset(id)));
+            if id == 0 {
+                trace.push_str(" (start)");
+            }
+            if states.iter().any(|state| self.finals.contains(state)) {
+                trace.push_str(" (final)");
+            }
+            trace.push('\n');
+            for &symb in alphabet.iter() {
+                if let Some(&dest) = transitions.get(&(symb,id)) {
+                    trace.push_str(&format!("  {} -> {} = state {}\n", symb, subset(dest), dest));
+                }
+            }
+        }
+        trace
+    }
+
+    /// Returns the number of non-empty subsets of states reachable from the
+    /// starting state, i.e. the number of states of the DFA built by `to_dfa`,
+    /// without building the transitions of the DFA. The count is aborted with
+    /// `Err(max)` as soon as more than `max` subsets are found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*a(a|b)
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 1, 2)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert_eq!(nfa.reachable_subset_count(10), Ok(4));
+    ///     assert_eq!(nfa.reachable_subset_count(3), Err(3));
+    /// }
+    /// ```
+    pub fn reachable_subset_count(&self, max: usize) -> result::Result<usize,usize> {
+        self.subset_construction(max, false).map(|(subsets,_)| subsets.len())
+    }
+
+    /// Returns a NFA accepting the words accepted by both the NFA and `dfa`.
+    /// The states of the result are the pairs of a state of the NFA and of a
+    /// state of `dfa` reachable from the pair of the starting states: the NFA
+    /// side branches on its nondeterministic transitions while the DFA side
+    /// follows its single transition, so no determinization is needed. A pair
+    /// is final if both of its states are final.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    /// use automaton::dfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*b
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .add_transition('b', 0, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let product = nfa.intersect_dfa(&DFA::from_words(&["ab", "ba"]));
+    ///     assert!(product.test("ab"));
+    ///     assert!(!product.test("ba"));
+    /// }
+    /// ```
+    pub fn intersect_dfa(&self, dfa: &DFA) -> NFA {
+        let mut successors = HashMap::new();
+        for (&(symb,src),dests) in self.transitions.iter() {
+            let mut dests : Vec<_> = dests.iter().cloned().collect();
+            dests.sort();
+            successors.entry(src).or_insert_with(Vec::new).push((symb,dests));
+        }
+        for nexts in successors.values_mut() {
+            nexts.sort();
+        }
+        let start = (self.start,dfa.start());
+        let mut ids = HashMap::new();
+        ids.insert(start, 0);
+        let mut pairs = vec![start];
+        let mut transitions = HashMap::new();
+        let mut finals = HashSet::new();
+        let mut i = 0;
+        while i < pairs.len() {
+            let (state,dfa_state) = pairs[i];
+            if self.finals.contains(&state) && dfa.finals().contains(&dfa_state) {
+                finals.insert(i);
+            }
+            for &(symb,ref dests) in successors.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                let dfa_dest = match dfa.next_state(dfa_state, symb) {
+                    Some(dfa_dest) => dfa_dest,
+                    None => continue,
+                };
+                for &dest in dests.iter() {
+                    let id = pairs.len();
+                    let id = *ids.entry((dest,dfa_dest)).or_insert(id);
+                    if id == pairs.len() {
+                        pairs.push((dest,dfa_dest));
+                    }
+                    transitions.entry((symb,i)).or_insert_with(HashSet::new).insert(id);
+                }
+            }
+            i += 1;
+        }
+        NFA{transitions, start: 0, finals}
+    }
+
+    /// Returns an equivalent NFA shrunk by cheap reductions. The states which are
+    /// unreachable from the starting state or can't reach a final state are
+    /// removed, then the states with the same finality and the same outgoing
+    /// transitions are merged, until no more states can be merged.
+    ///
+    /// This is a heuristic: the minimization of an NFA is PSPACE-hard, and the
+    /// reduced NFA is not necessarily minimal. The language is preserved since
+    /// the merged states accept the same words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // ab|ac, with a dead state
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(3)
+    ///         .add_final(4)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('a', 0, 2)
+    ///         .add_transition('b', 1, 3)
+    ///         .add_transition('c', 2, 4)
+    ///         .add_transition('d', 2, 5)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let reduced = nfa.reduce();
+    ///     assert_eq!(reduced.stats().state_count, 4);
+    ///     assert!(reduced.test("ab"));
+    ///     assert!(reduced.test("ac"));
+    ///     assert!(!reduced.test("ad"));
+    /// }
+    /// ```
+    pub fn reduce(&self) -> NFA {
+        let mut successors = HashMap::new();
+        let mut predecessors = HashMap::new();
+        for (&(_,src),dests) in self.transitions.iter() {
+            for &dest in dests.iter() {
+                successors.entry(src).or_insert_with(Vec::new).push(dest);
+                predecessors.entry(dest).or_insert_with(Vec::new).push(src);
+            }
+        }
+        let reached = |starts: Vec<usize>, edges: &HashMap<usize,Vec<usize>>| {
+            let mut reached : HashSet<_> = starts.iter().cloned().collect();
+            let mut stack = starts;
+            while let Some(state) = stack.pop() {
+                for &next in edges.get(&state).into_iter().flat_map(|nexts| nexts.iter()) {
+                    if reached.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+            reached
+        };
+        let reachable = reached(vec![self.start], &successors);
+        let coreachable = reached(self.finals.iter().cloned().collect(), &predecessors);
+        let useful : HashSet<_> = reachable.intersection(&coreachable).cloned().collect();
+        let transitions = self.transitions
+            .iter()
+            .filter(|&(&(_,src),_)| useful.contains(&src))
+            .filter_map(|(&tr,dests)| {
+                let dests : HashSet<_> = dests.intersection(&useful).cloned().collect();
+                if dests.is_empty() { None } else { Some((tr,dests)) }
             })
-            .unwrap_or(HashSet::new())
-            .intersection(&self.finals)
-            .next().is_some()
+            .collect();
+        let finals = self.finals.intersection(&useful).cloned().collect();
+        let mut nfa = NFA{transitions, start: self.start, finals};
+        loop {
+            let mut outgoing = HashMap::new();
+            for (&(symb,src),dests) in nfa.transitions.iter() {
+                let mut dests : Vec<_> = dests.iter().cloned().collect();
+                dests.sort();
+                outgoing.entry(src).or_insert_with(Vec::new).push((symb,dests));
+            }
+            let mut states : Vec<_> = nfa.states().into_iter().collect();
+            states.sort();
+            // each state is represented by the smallest state with its signature
+            let mut signatures = HashMap::new();
+            let mut representatives = HashMap::new();
+            for state in states {
+                let mut signature = outgoing.remove(&state).unwrap_or_default();
+                signature.sort();
+                let representative = *signatures
+                    .entry((nfa.finals.contains(&state),signature))
+                    .or_insert(state);
+                representatives.insert(state, representative);
+            }
+            if representatives.iter().all(|(state,representative)| state == representative) {
+                return nfa;
+            }
+            // the transitions of a merged state are those of its representative
+            let transitions = nfa.transitions
+                .iter()
+                .filter(|&(&(_,src),_)| representatives[&src] == src)
+                .map(|(&tr,dests)| (tr,dests.iter().map(|dest| representatives[dest]).collect()))
+                .collect();
+            let finals = nfa.finals.iter().map(|state| representatives[state]).collect();
+            nfa = NFA{transitions, start: representatives[&nfa.start], finals};
+        }
+    }
+
+    /// Returns `true` if each `(symbol,source)` pair has at most one destination,
+    /// i.e. if the NFA is in fact a DFA.
+    ///
+    /// See `deterministic_violations` to find the nondeterministic transitions.
+    pub fn is_deterministic(&self) -> bool {
+        self.transitions.values().all(|dests| dests.len() <= 1)
+    }
+
+    /// Returns the sorted `(symbol,source)` pairs that have more than one
+    /// destination, i.e. the places where the NFA is not deterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     // (a|b)*ab
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(2)
+    ///         .add_transition('a', 0, 0)
+    ///         .add_transition('b', 0, 0)
+    ///         .add_transition('a', 0, 1)
+    ///         .add_transition('b', 1, 2)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     assert!(!nfa.is_deterministic());
+    ///     assert_eq!(nfa.deterministic_violations(), vec![('a', 0)]);
+    /// }
+    /// ```
+    pub fn deterministic_violations(&self) -> Vec<(char,usize)> {
+        let mut violations : Vec<_> = self.transitions
+            .iter()
+            .filter(|&(_,dests)| dests.len() > 1)
+            .map(|(&tr,_)| tr)
+            .collect();
+        violations.sort();
+        violations
+    }
+
+    // Builds an NFA from its parts, without requiring any final state, for the
+    // constructions of the other modules whose language may be empty.
+    pub(crate) fn from_parts(transitions: HashMap<(char,usize),HashSet<usize>>, start: usize, finals: HashSet<usize>) -> NFA {
+        NFA{transitions, start, finals}
+    }
+
+    // Runs the subset construction from the starting state and returns the
+    // reachable non-empty subsets, numbered in BFS order, along with the
+    // transitions between them if `with_transitions` is set. Fails with
+    // `Err(max)` if there are more than `max` subsets.
+    fn subset_construction(&self, max: usize, with_transitions: bool) -> result::Result<Subsets,usize> {
+        let mut alphabet : Vec<_> = self.alphabet().into_iter().collect();
+        alphabet.sort();
+        let start = vec![self.start];
+        let mut ids = HashMap::new();
+        ids.insert(start.clone(), 0);
+        let mut subsets = vec![start];
+        let mut transitions = HashMap::new();
+        let mut i = 0;
+        while i < subsets.len() {
+            if subsets.len() > max {
+                return Err(max);
+            }
+            telemetry!("determinization", subsets.len(), subsets.len()-i-1);
+            for &symb in alphabet.iter() {
+                let mut dest : Vec<_> = subsets[i]
+                    .iter()
+                    .filter_map(|&state| self.transitions.get(&(symb,state)))
+                    .flat_map(|dests| dests.iter().cloned())
+                    .collect();
+                if dest.is_empty() {
+                    continue;
+                }
+                dest.sort();
+                dest.dedup();
+                let id = subsets.len();
+                let id = *ids.entry(dest.clone()).or_insert(id);
+                if id == subsets.len() {
+                    subsets.push(dest);
+                }
+                if with_transitions {
+                    transitions.insert((symb,i), id);
+                }
+            }
+            i += 1;
+        }
+        if subsets.len() > max {
+            return Err(max);
+        }
+        Ok((subsets,transitions))
+    }
+
+    /// Writes the NFA in the Graphviz DOT format. The states and the transitions
+    /// are written in increasing order, so the output is deterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::core::*;
+    ///
+    /// fn main() {
+    ///     let nfa = NFABuilder::new()
+    ///         .add_start(0)
+    ///         .add_final(1)
+    ///         .add_transition('a', 0, 1)
+    ///         .finalize()
+    ///         .unwrap();
+    ///     let mut out = Vec::new();
+    ///     nfa.write_dot(&mut out).unwrap();
+    ///     assert!(String::from_utf8(out).unwrap().contains("0 -> 1 [label=\"a\"];"));
+    /// }
+    /// ```
+    pub fn write_dot<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        dot::write_dot(w, self.start, &self.finals, self.states(), self.edges())
+    }
+
+    /// Returns the NFA in the Graphviz DOT format (see `write_dot`).
+    pub fn to_dot(&self) -> String {
+        let mut out = Vec::new();
+        // can't fail because writing in a vector never fails
+        self.write_dot(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Returns a summary of the size of the NFA.
+    pub fn stats(&self) -> NFAStats {
+        let states = self.states();
+        let alphabet = self.alphabet();
+        NFAStats {
+            state_count      : states.len(),
+            transition_count : self.transitions.values().map(|dests| dests.len()).sum(),
+            final_count      : self.finals.len(),
+            alphabet_size    : alphabet.len(),
+            is_complete      : self.transitions.len() == states.len()*alphabet.len(),
+        }
+    }
+
+    fn edges(&self) -> Vec<(usize,Option<char>,usize)> {
+        self.transitions
+            .iter()
+            .flat_map(|(&(symb,src),dests)| dests.iter().map(move |&dest| (src,Some(symb),dest)))
+            .collect()
+    }
+
+    fn states(&self) -> HashSet<usize> {
+        let mut states : HashSet<_> = self.finals.iter().cloned().collect();
+        states.insert(self.start);
+        for (&(_,src),dests) in self.transitions.iter() {
+            states.insert(src);
+            states.extend(dests.iter().cloned());
+        }
+        states
+    }
+
+    fn alphabet(&self) -> HashSet<char> {
+        self.transitions.keys().map(|&(symb,_)| symb).collect()
+    }
+}
+
+impl Render for NFA {
+    fn to_dot(&self) -> String {
+        NFA::to_dot(self)
+    }
+
+    fn to_table(&self) -> String {
+        render::table(self.start, &self.finals, self.states(), self.edges())
+    }
+
+    fn to_csv(&self) -> String {
+        render::csv(self.edges())
     }
 }
 
@@ -319,6 +884,7 @@ impl fmt::Display for NFA {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dfa::core::{DFABuilder,DFABuilding};
 
     #[test]
     fn test_nfa() {
@@ -346,6 +912,260 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nfa_nondeterministic() {
+        // (a|b)*ab
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        let samples =
+            vec![("ab", true),
+                 ("bab", true),
+                 ("abbab", true),
+                 ("", false),
+                 ("a", false),
+                 ("aba", false),];
+
+        for (input,expected_result) in samples {
+            assert!(nfa.test(input) == expected_result, "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_nfa_intersect_dfa() {
+        // (a|b)*a(a|b)
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        // words over {a,b} of even length
+        let even = DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 1)
+            .add_transition('a', 1, 0)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap();
+        let product = nfa.intersect_dfa(&even);
+        let samples =
+            vec![("aa", true),
+                 ("ab", true),
+                 ("bbab", true),
+                 ("abaa", true),
+                 ("", false),
+                 ("a", false),
+                 ("bab", false),
+                 ("abbb", false),
+                 ("aab", false),];
+        for (input,expected_result) in samples {
+            assert!(product.test(input) == expected_result, "input false for: \"{}\"", input);
+            assert_eq!(product.test(input), nfa.test(input) && even.test(input));
+        }
+        let product = nfa.intersect_dfa(&DFA::from_words(&["c"]));
+        assert!(product.finals.is_empty());
+        assert!(!product.test("c"));
+    }
+
+    #[test]
+    fn test_nfa_to_dfa_trace() {
+        // (a|b)*ab
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        let trace = nfa.to_dfa_trace();
+        assert!(trace.starts_with("state 0 = {0} (start)\n"));
+        assert!(trace.contains("\n  a -> {0,1} = state 1\n"));
+        assert!(trace.contains("\nstate 2 = {0,2} (final)\n"));
+        assert_eq!(trace,
+                   "state 0 = {0} (start)\n  a -> {0,1} = state 1\n  b -> {0} = state 0\n\
+                    state 1 = {0,1}\n  a -> {0,1} = state 1\n  b -> {0,2} = state 2\n\
+                    state 2 = {0,2} (final)\n  a -> {0,1} = state 1\n  b -> {0} = state 0\n");
+    }
+
+    #[test]
+    fn test_nfa_reduce() {
+        // (ab|bb)a, the branches being merged in three steps
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(5)
+            .add_final(6)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 2)
+            .add_transition('b', 1, 3)
+            .add_transition('b', 2, 4)
+            .add_transition('a', 3, 5)
+            .add_transition('a', 4, 6)
+            // unreachable and dead states
+            .add_transition('a', 7, 3)
+            .add_transition('c', 1, 8)
+            .finalize()
+            .unwrap();
+        let reduced = nfa.reduce();
+        assert_eq!(reduced.stats().state_count, 4);
+        assert_eq!(reduced.stats().transition_count, 4);
+        assert_eq!(reduced.to_dfa().minimize(), nfa.to_dfa().minimize());
+        assert_eq!(reduced.reduce(), reduced);
+        // an empty language keeps the starting state alone
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        let reduced = nfa.reduce();
+        assert_eq!(reduced.stats().state_count, 1);
+        assert_eq!(reduced.stats().transition_count, 0);
+        assert!(!reduced.test(""));
+    }
+
+    #[test]
+    fn test_nfa_deterministic_violations() {
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 0, 2)
+            .add_transition('b', 0, 1)
+            .add_transition('b', 1, 2)
+            .add_transition('c', 1, 0)
+            .add_transition('c', 1, 1)
+            .add_transition('c', 1, 2)
+            .finalize()
+            .unwrap();
+        assert!(!nfa.is_deterministic());
+        assert_eq!(nfa.deterministic_violations(), vec![('a', 0), ('c', 1)]);
+
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 1)
+            .finalize()
+            .unwrap();
+        assert!(nfa.is_deterministic());
+        assert!(nfa.deterministic_violations().is_empty());
+    }
+
+    #[test]
+    fn test_nfa_test_streaming() {
+        // (a|b)*ab
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        let n = 300_000;
+        let input = (0..n).map(|i| if i%3 == 0 { 'a' } else { 'b' }).chain("ab".chars());
+        assert!(nfa.test_streaming(input));
+        let input = (0..n).map(|i| if i%3 == 0 { 'a' } else { 'b' });
+        assert!(!nfa.test_streaming(input));
+        let input = (0..n).map(|_| 'a').chain("c".chars()).chain("ab".chars());
+        assert!(!nfa.test_streaming(input));
+    }
+
+    #[test]
+    fn test_nfa_to_dfa() {
+        // (a|b)*ab
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        let dfa = nfa.to_dfa();
+        assert_eq!(dfa.stats().state_count, 3);
+        for input in &["", "a", "b", "ab", "ba", "aab", "abab", "abba", "bbab"] {
+            assert!(dfa.test(input) == nfa.test(input), "input false for: \"{}\"", input);
+        }
+    }
+
+    #[test]
+    fn test_nfa_write_dot() {
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        let mut out = Vec::new();
+        nfa.write_dot(&mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert_eq!(dot, nfa.to_dot());
+        assert!(dot.contains("    0 -> 0 [label=\"a\"];\n    0 -> 1 [label=\"a\"];\n"));
+        assert!(dot.contains("    1 [shape=doublecircle];\n"));
+    }
+
+    #[test]
+    fn test_nfa_reachable_subset_count() {
+        // words over {a,b} whose third symbol from the end is 'a': the DFA needs
+        // 2^3 states
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(3)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 1, 2)
+            .add_transition('a', 2, 3)
+            .add_transition('b', 2, 3)
+            .finalize()
+            .unwrap();
+        assert_eq!(nfa.reachable_subset_count(100), Ok(8));
+        assert_eq!(nfa.reachable_subset_count(8), Ok(8));
+        assert_eq!(nfa.reachable_subset_count(7), Err(7));
+        assert_eq!(nfa.reachable_subset_count(0), Err(0));
+        assert_eq!(nfa.to_dfa().stats().state_count, 8);
+    }
+
+    #[test]
+    fn test_nfa_stats() {
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 0, 0)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        assert_eq!(nfa.stats(), NFAStats {
+            state_count      : 3,
+            transition_count : 4,
+            final_count      : 1,
+            alphabet_size    : 2,
+            is_complete      : false,
+        });
+    }
+
     #[test]
     fn test_nfa_builder() {
         let _nfa = NFABuilder::new()
@@ -366,10 +1186,7 @@ mod tests {
             .add_final(3)
             .add_transition('a', 0, 1)
             .finalize();
-        match nfa {
-            Err(NFAError::MissingStartingState) => assert!(true),
-            _ => assert!(false, "MissingStartingState expected."),
-        }
+        assert_eq!(nfa, Err(NFAError::MissingStartingState));
     }
 
     #[test]
@@ -378,9 +1195,6 @@ mod tests {
             .add_start(0)
             .add_transition('a', 0, 1)
             .finalize();
-        match nfa {
-            Err(NFAError::MissingFinalStates) => assert!(true),
-            _ => assert!(false, "MissingFinalStates expected."),
-        }
+        assert_eq!(nfa, Err(NFAError::MissingFinalStates));
     }
 }
@@ -19,6 +19,7 @@ use std::result;
 use self::itertools::Itertools;        // fold_results
 
 use nfa::core::{NFA,NFABuilder,NFAError,NFABuilding};
+use reader::{ReaderError,ReaderLimits};
 
 /// Type `NFAReaderError` describes the list of errors that can occur during
 /// the parsing of a NFA file.
@@ -42,6 +43,9 @@ pub enum NFAReaderError {
     Io(io::Error),
     /// Error `Parse` is relative to the parsing errors (a state is an intger).
     Parse(num::ParseIntError,usize),
+    /// Error `TooLong` means the specified line exceeds the `ReaderLimits` given to
+    /// one of the `*_limited` constructors.
+    TooLong(usize),
 }
 
 impl fmt::Display for NFAReaderError {
@@ -54,6 +58,7 @@ impl fmt::Display for NFAReaderError {
             NFAReaderError::IllformedTransition(ref line) => write!(f, "Line {}: too much elements.", line),
             NFAReaderError::NFA(ref err,ref line) => write!(f, "Line {}: NFAError {}", line, err),
             NFAReaderError::Parse(ref err,ref line) => write!(f, "Line {}: parse error {}", line, err),
+            NFAReaderError::TooLong(ref line) => write!(f, "Line {}: the line exceeds the reader limits.", line),
         }
     }
 }
@@ -68,6 +73,7 @@ impl error::Error for NFAReaderError {
             NFAReaderError::IllformedTransition(_) => "Too much elements.",
             NFAReaderError::NFA(ref err,_) => err.description(),
             NFAReaderError::Parse(ref err,_) => err.description(),
+            NFAReaderError::TooLong(_) => "The line exceeds the reader limits.",
         }
     }
 
@@ -94,6 +100,19 @@ impl From<num::ParseIntError> for NFAReaderError {
     }
 }
 
+impl ReaderError for NFAReaderError {
+    fn line(&self) -> Option<usize> {
+        match *self {
+            NFAReaderError::IncompleteTransition(line) => Some(line),
+            NFAReaderError::IllformedTransition(line) => Some(line),
+            NFAReaderError::NFA(_,line) => Some(line),
+            NFAReaderError::Parse(_,line) => Some(line),
+            NFAReaderError::TooLong(line) => Some(line),
+            _ => None,
+        }
+    }
+}
+
 /// Alias for result::Result<T,NFAReaderError>.
 pub type Result<T> = result::Result<T,NFAReaderError>;
 
@@ -134,7 +153,7 @@ impl NFAReader {
     pub fn new_from_file<P: AsRef<Path>>(file_path: P) -> Result<NFA> {
         let file = try!(File::open(file_path));
         let file = BufReader::new(file);
-        NFAReader::new_from_lines(&mut file.lines())
+        NFAReader::new_from_lines(&mut file.lines(), false)
     }
 
     fn read_start(nfa: NFABuilder, lines : &mut Iterator<Item=(usize,io::Result<String>)>) -> Result<NFABuilder> {
@@ -184,7 +203,8 @@ impl NFAReader {
         Ok(nfa)
     }
 
-    fn new_from_lines(lines : &mut Iterator<Item=io::Result<String>>) -> Result<NFA> {
+    // `finals_first` swaps the starting state line and the finals line.
+    fn new_from_lines(lines : &mut Iterator<Item=io::Result<String>>, finals_first: bool) -> Result<NFA> {
         let mut nfa = try!(NFABuilder::new().map_err(|e| NFAReaderError::NFA(e,0)));
         let mut lines = lines
             .map(|line| {
@@ -196,8 +216,13 @@ impl NFAReader {
                 let line = line.as_ref();
                 line.is_err() || !line.unwrap().is_empty()
             });
-        nfa = try!(NFAReader::read_start(nfa, &mut lines));
-        nfa = try!(NFAReader::read_finals(nfa, &mut lines));
+        if finals_first {
+            nfa = try!(NFAReader::read_finals(nfa, &mut lines));
+            nfa = try!(NFAReader::read_start(nfa, &mut lines));
+        } else {
+            nfa = try!(NFAReader::read_start(nfa, &mut lines));
+            nfa = try!(NFAReader::read_finals(nfa, &mut lines));
+        }
         for line in lines {
             nfa = try!(NFAReader::read_transition(nfa, line));
         }
@@ -237,7 +262,77 @@ impl NFAReader {
     /// }
     /// ```
     pub fn new_from_string(nfa: &str) -> Result<NFA> {
-        NFAReader::new_from_lines(&mut nfa.lines().map(|line| Ok(line.to_string())))
+        NFAReader::new_from_lines(&mut nfa.lines().map(|line| Ok(line.to_string())), false)
+    }
+
+    /// Reads a NFA from a file whose finals line comes before the starting state
+    /// line.
+    ///
+    /// See `new_from_string_finals_first`.
+    pub fn new_from_file_finals_first<P: AsRef<Path>>(file_path: P) -> Result<NFA> {
+        let file = try!(File::open(file_path));
+        let file = BufReader::new(file);
+        NFAReader::new_from_lines(&mut file.lines(), true)
+    }
+
+    /// Reads a NFA from a `&str` whose finals line comes before the starting
+    /// state line, as emitted by some tools. The rest of the format is the same
+    /// as for `new_from_string`.
+    ///
+    /// # Description
+    ///
+    /// * `nfa` - The string representation of the NFA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate automaton;
+    ///
+    /// use automaton::nfa::reader::*;
+    ///
+    /// fn main() {
+    ///     // (ab)+
+    ///     let nfa =
+    ///         "2\n\
+    ///          0\n\
+    ///          a 0 1\n\
+    ///          b 1 2\n\
+    ///          a 2 1";
+    ///     let nfa = NFAReader::new_from_string_finals_first(nfa).unwrap();
+    ///     assert!(nfa.test("abab"));
+    /// }
+    /// ```
+    pub fn new_from_string_finals_first(nfa: &str) -> Result<NFA> {
+        NFAReader::new_from_lines(&mut nfa.lines().map(|line| Ok(line.to_string())), true)
+    }
+
+    /// Reads a NFA from a `&str` as `new_from_string`, after checking that no
+    /// line exceeds `limits`.
+    ///
+    /// # Errors
+    ///
+    /// Return a `NFAReaderError::TooLong` with the number of the first line exceeding
+    /// `limits`.
+    pub fn new_from_string_limited(nfa: &str, limits: &ReaderLimits) -> Result<NFA> {
+        match limits.first_exceeding(nfa.lines()) {
+            Some(nline) => Err(NFAReaderError::TooLong(nline)),
+            None => NFAReader::new_from_string(nfa),
+        }
+    }
+
+    /// Reads a NFA from a file as `new_from_file`. The reading stops on the
+    /// first line exceeding `limits`, without buffering more than
+    /// `limits.max_line_length` bytes of it, so that an untrusted file can not
+    /// exhaust the memory with a single line.
+    ///
+    /// # Errors
+    ///
+    /// Return a `NFAReaderError::TooLong` with the number of the first line exceeding
+    /// `limits`.
+    pub fn new_from_file_limited<P: AsRef<Path>>(file_path: P, limits: &ReaderLimits) -> Result<NFA> {
+        let file = try!(File::open(file_path));
+        let lines = try!(try!(limits.read_lines(BufReader::new(file))).map_err(NFAReaderError::TooLong));
+        NFAReader::new_from_lines(&mut lines.into_iter().map(Ok), false)
     }
 }
 
@@ -367,4 +462,36 @@ mod test {
             _ => assert!(false, "Io::Error expected."),
         }
     }
+
+    #[test]
+    fn test_finals_first() {
+        let standard =
+            "0\n\
+             2 3\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 0 3";
+        let finals_first =
+            "2 3  # finals\n\
+             0    # start\n\
+             a 0 1\n\
+             b 1 2\n\
+             c 0 3";
+        let expected = NFAReader::new_from_string(standard).unwrap();
+        assert_eq!(NFAReader::new_from_string_finals_first(finals_first).unwrap(), expected);
+        match NFAReader::new_from_string_finals_first("2 3") {
+            Err(NFAReaderError::MissingStartingState) => assert!(true),
+            _ => assert!(false, "MissingStartingState expected."),
+        }
+    }
+
+    #[test]
+    fn test_limited() {
+        let limits = ReaderLimits{max_line_length: 8, max_tokens: 3};
+        assert!(NFAReader::new_from_string_limited("0\n1\na 0 1", &limits).is_ok());
+        match NFAReader::new_from_string_limited("0\n1 1 1 1\na 0 1", &limits) {
+            Err(NFAReaderError::TooLong(nline)) => assert_eq!(nline, 2),
+            _ => assert!(false),
+        }
+    }
 }
@@ -0,0 +1,271 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::error;
+use std::io::{self,BufRead,Read};
+use std::result;
+
+/// `ReaderError` is the trait shared by the errors of the automaton readers
+/// (`DFAReaderError`, `NFAReaderError` and `ENFAReaderError`). It allows to handle
+/// the errors of any reader polymorphically.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::reader::ReaderError;
+/// use automaton::dfa::reader::DFAReader;
+/// use automaton::nfa::reader::NFAReader;
+///
+/// fn report<E: ReaderError>(err: E) -> String {
+///     match err.line() {
+///         Some(line) => format!("error on line {}: {}", line, err),
+///         None => format!("error: {}", err),
+///     }
+/// }
+///
+/// fn main() {
+///     let err = DFAReader::new_from_string("0\n1\na 0").unwrap_err();
+///     assert_eq!(err.line(), Some(3));
+///     assert_eq!(report(err), "error on line 3: Line 3: missing the src or the dest state.");
+///     let err = NFAReader::new_from_string("").unwrap_err();
+///     assert_eq!(err.line(), None);
+///     assert!(report(err).starts_with("error: "));
+/// }
+/// ```
+pub trait ReaderError: error::Error {
+    /// Returns the line of the file on which the error occurred, if the error
+    /// is relative to a specific line.
+    fn line(&self) -> Option<usize>;
+}
+
+impl<'a, E: ReaderError + 'a> From<E> for Box<ReaderError + 'a> {
+    fn from(err: E) -> Box<ReaderError + 'a> {
+        Box::new(err)
+    }
+}
+
+/// `ReaderLimits` bounds the size of the lines accepted by the `*_limited`
+/// constructors of the readers, which fail with a `TooLong` error on the first
+/// line exceeding a limit. The default limits are unbounded.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::reader::ReaderLimits;
+/// use automaton::dfa::reader::*;
+///
+/// fn main() {
+///     let limits = ReaderLimits{max_line_length: 16, ..ReaderLimits::default()};
+///     assert!(DFAReader::new_from_string_limited("0\n1\na 0 1", &limits).is_ok());
+///     match DFAReader::new_from_string_limited("0\n1 1 1 1 1 1 1 1 1 1\na 0 1", &limits) {
+///         Err(DFAReaderError::TooLong(line)) => assert_eq!(line, 2),
+///         _ => assert!(false),
+///     }
+/// }
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ReaderLimits {
+    /// Maximal number of bytes of a line, the line terminator excluded.
+    pub max_line_length : usize,
+    /// Maximal number of whitespace separated tokens of a line.
+    pub max_tokens      : usize,
+}
+
+impl Default for ReaderLimits {
+    fn default() -> ReaderLimits {
+        ReaderLimits{max_line_length: usize::MAX, max_tokens: usize::MAX}
+    }
+}
+
+impl ReaderLimits {
+    fn exceeded_by(&self, line: &str) -> bool {
+        line.len() > self.max_line_length
+            || line.split_whitespace().nth(self.max_tokens).is_some()
+    }
+
+    // Returns the number (from 1) of the first line exceeding the limits.
+    pub(crate) fn first_exceeding<'a, I: Iterator<Item=&'a str>>(&self, lines: I) -> Option<usize> {
+        lines
+            .enumerate()
+            .find(|&(_,line)| self.exceeded_by(line))
+            .map(|(nline,_)| nline+1)
+    }
+
+    // Reads the lines of `reader` as `BufRead::lines` does, but never buffers
+    // more than `max_line_length` bytes of a line: the inner result is the
+    // number of the first line exceeding the limits. The length is checked on
+    // the bytes before decoding, a truncated line may end in the middle of a
+    // character.
+    pub(crate) fn read_lines<R: BufRead>(&self, mut reader: R) -> io::Result<result::Result<Vec<String>,usize>> {
+        let mut lines = Vec::new();
+        let mut buf = Vec::new();
+        // room for the "\r\n" terminator
+        let max_read = (self.max_line_length as u64).saturating_add(2);
+        loop {
+            buf.clear();
+            if try!(reader.by_ref().take(max_read).read_until(b'\n', &mut buf)) == 0 {
+                return Ok(Ok(lines));
+            }
+            if buf.last() == Some(&b'\n') {
+                buf.pop();
+                if buf.last() == Some(&b'\r') {
+                    buf.pop();
+                }
+            }
+            if buf.len() > self.max_line_length {
+                return Ok(Err(lines.len()+1));
+            }
+            let line = try!(String::from_utf8(buf.clone())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)));
+            if self.exceeded_by(&line) {
+                return Ok(Err(lines.len()+1));
+            }
+            lines.push(line);
+        }
+    }
+}
+
+/// `ReaderOptions` describes the delimiter of the tokens of a line and the
+/// character starting a comment, for the constructors of the readers taking
+/// options. A whitespace delimiter splits the lines on any run of whitespaces,
+/// which is the default format of the readers. Another delimiter splits the
+/// lines on each of its occurrences, the whitespaces around the tokens being
+/// ignored.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::reader::ReaderOptions;
+/// use automaton::dfa::reader::*;
+///
+/// fn main() {
+///     let options = ReaderOptions{delimiter: ';', ..ReaderOptions::default()};
+///     let dfa = DFAReader::new_from_string_with_options("0\n1\na;0;1 # comment", &options).unwrap();
+///     assert!(dfa.test("a"));
+/// }
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ReaderOptions {
+    /// Character separating the tokens of a line.
+    pub delimiter : char,
+    /// Character starting a comment, which extends to the end of the line.
+    pub comment   : char,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> ReaderOptions {
+        ReaderOptions{delimiter: ' ', comment: '#'}
+    }
+}
+
+impl ReaderOptions {
+    // Returns the contents of a line without its comment and the surrounding
+    // whitespaces.
+    pub(crate) fn uncommented<'a>(&self, line: &'a str) -> &'a str {
+        // can't fail because split always yields at least one item
+        line.split(self.comment).next().unwrap().trim()
+    }
+
+    // Returns the non-empty tokens of a line.
+    pub(crate) fn tokens<'a>(&self, line: &'a str) -> Vec<&'a str> {
+        if self.delimiter.is_whitespace() {
+            line.split_whitespace().collect()
+        } else {
+            line.split(self.delimiter)
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use dfa::reader::DFAReader;
+    use nfa::reader::NFAReader;
+    use e_nfa::reader::ENFAReader;
+
+    fn load(model: &str) -> Result<String,Box<ReaderError>> {
+        match model.lines().next() {
+            Some("# dfa") => Ok(format!("{}", try!(DFAReader::new_from_string(model)))),
+            Some("# nfa") => Ok(format!("{}", try!(NFAReader::new_from_string(model)))),
+            _ => Ok(format!("{}", try!(ENFAReader::new_from_string(model)))),
+        }
+    }
+
+    #[test]
+    fn test_reader_error_line() {
+        let models = [
+            ("# dfa\n0\n1\na 0", Some(4)),
+            ("# nfa\n0\n1\na 0 b", Some(4)),
+            ("# e_nfa\n0\n1 a", Some(3)),
+            ("# dfa\n", None),
+            ("# nfa\n0", None),
+        ];
+        for &(model,line) in models.iter() {
+            match load(model) {
+                Err(e) => assert_eq!(e.line(), line, "wrong line for: \"{}\"", model),
+                Ok(_) => assert!(false, "error expected for: \"{}\"", model),
+            }
+        }
+        assert!(load("# dfa\n0\n1\na 0 1").is_ok());
+    }
+
+    #[test]
+    fn test_reader_error_into_boxed_error() {
+        let err : Box<Error> = DFAReader::new_from_string("a").unwrap_err().into();
+        assert_eq!(format!("{}", err), "Line 1: parse error invalid digit found in string");
+        let err : Box<Error> = NFAReader::new_from_string("").unwrap_err().into();
+        assert_eq!(format!("{}", err), "The file is empty or only contains white characters.");
+        let err : Box<ReaderError> = ENFAReader::new_from_file("fake.txt").unwrap_err().into();
+        assert!(err.line().is_none());
+        assert!(format!("{}", err).starts_with("IO error"));
+    }
+
+    #[test]
+    fn test_reader_limits() {
+        let limits = ReaderLimits{max_line_length: 5, max_tokens: 2};
+        assert_eq!(limits.first_exceeding("0\n1 2\na 0".lines()), None);
+        assert_eq!(limits.first_exceeding("0\n1 2 3\na 0".lines()), Some(2));
+        assert_eq!(limits.first_exceeding("0\n\n123456".lines()), Some(3));
+        assert_eq!(ReaderLimits::default().first_exceeding("1 2 3 4 5 6 7 8".lines()), None);
+
+        let read = |contents: &str| limits.read_lines(contents.as_bytes()).unwrap();
+        assert_eq!(read("0\r\n12345\n\na 0"), Ok(vec!["0".to_string(), "12345".to_string(),
+                                                         String::new(), "a 0".to_string()]));
+        assert_eq!(read("0\n123456\n"), Err(2));
+        assert_eq!(read(&format!("0\n1\n{}", "a".repeat(1 << 20))), Err(3));
+        assert_eq!(read("0 1 2"), Err(1));
+        // the line is truncated in the middle of the fourth 'é'
+        assert_eq!(read("0\néééé\n"), Err(2));
+        assert_eq!(read("0\néé\n"), Ok(vec!["0".to_string(), "éé".to_string()]));
+        assert_eq!(read(""), Ok(vec![]));
+        assert!(limits.read_lines(&[0xff, b'\n'][..]).is_err());
+    }
+
+    #[test]
+    fn test_reader_options() {
+        let options = ReaderOptions::default();
+        assert_eq!(options.tokens(" a \t0  1 "), vec!["a", "0", "1"]);
+        assert_eq!(options.uncommented(" a 0 1 # comment"), "a 0 1");
+        let options = ReaderOptions{delimiter: ',', comment: ';'};
+        assert_eq!(options.tokens("a, 0,,1 "), vec!["a", "0", "1"]);
+        assert_eq!(options.tokens("#,0,1"), vec!["#", "0", "1"]);
+        assert_eq!(options.uncommented("#,0,1;comment"), "#,0,1");
+        let options = ReaderOptions{delimiter: '\t', ..ReaderOptions::default()};
+        assert_eq!(options.tokens("a\t0 \t1"), vec!["a", "0", "1"]);
+    }
+}
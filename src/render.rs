@@ -0,0 +1,220 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashSet;
+
+use dot;
+
+/// `Render` is the trait shared by the automata (`DFA`, `NFA` and `ENFA`) which
+/// can be exported to text formats, so that tools can handle the automata of
+/// any type through `&Render`. In every format the states and the
+/// transitions are written in increasing order.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::render::Render;
+/// use automaton::dfa::core::*;
+/// use automaton::e_nfa::core::*;
+///
+/// fn main() {
+///     let dfa = DFA::from_words(&["a"]);
+///     let nfa = ENFABuilder::new()
+///         .add_start(0)
+///         .add_final(1)
+///         .add_e_transition(0, 1)
+///         .finalize()
+///         .unwrap();
+///     let automata : Vec<&Render> = vec![&dfa, &nfa];
+///     assert_eq!(automata[0].to_table(), "state | a\n->0   | 1\n*1    | -\n");
+///     assert_eq!(automata[1].to_csv(), "src,symbol,dest\n0,,1\n");
+/// }
+/// ```
+pub trait Render {
+    /// Returns the automaton in the Graphviz DOT format.
+    fn to_dot(&self) -> String;
+
+    /// Returns the transition table of the automaton: one row per state and
+    /// one column per symbol, the epsilon transitions being in the column `ε`.
+    /// The starting state is marked by `->` and the final states by `*`. A cell
+    /// lists the destinations separated by commas, or `-` if there are none.
+    fn to_table(&self) -> String;
+
+    /// Returns the transitions of the automaton in the CSV format, with the
+    /// header `src,symbol,dest` and one row per transition. The symbol is
+    /// quoted, an inner quote being doubled, and is left empty for an epsilon
+    /// transition.
+    fn to_csv(&self) -> String;
+}
+
+// Formats the transition table of an automaton (see `Render::to_table`). An
+// edge without symbol is an epsilon transition.
+pub(crate) fn table(start: usize,
+                    finals: &HashSet<usize>,
+                    states: HashSet<usize>,
+                    mut edges: Vec<(usize,Option<char>,usize)>) -> String {
+    edges.sort();
+    let mut states : Vec<_> = states.into_iter().collect();
+    states.sort();
+    let mut symbols : Vec<_> = edges.iter().map(|&(_,symb,_)| symb).collect();
+    symbols.sort();
+    symbols.dedup();
+    let mut header = vec!["state".to_string()];
+    header.extend(symbols.iter().map(|symb| symb.map_or("ε".to_string(), |symb| symb.to_string())));
+    let mut rows = vec![header];
+    for &state in states.iter() {
+        let mut label = String::new();
+        if state == start {
+            label.push_str("->");
+        }
+        if finals.contains(&state) {
+            label.push('*');
+        }
+        label.push_str(&state.to_string());
+        let mut row = vec![label];
+        for &symb in symbols.iter() {
+            let dests : Vec<_> = edges
+                .iter()
+                .filter(|&&(src,s,_)| src == state && s == symb)
+                .map(|&(_,_,dest)| dest.to_string())
+                .collect();
+            row.push(if dests.is_empty() { "-".to_string() } else { dests.join(",") });
+        }
+        rows.push(row);
+    }
+    let mut widths = vec![0; symbols.len()+1];
+    for row in rows.iter() {
+        for (width,cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in rows {
+        let cells : Vec<_> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell,&width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        out.push_str(cells.join(" | ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// Formats the edges of an automaton in the CSV format (see `Render::to_csv`).
+// An edge without symbol is an epsilon transition.
+pub(crate) fn csv(mut edges: Vec<(usize,Option<char>,usize)>) -> String {
+    edges.sort_by_key(|&(src,symb,dest)| (src,symb,dest));
+    let mut out = "src,symbol,dest\n".to_string();
+    for (src,symb,dest) in edges {
+        let symb = match symb {
+            Some('"') => "\"\"\"\"".to_string(),
+            Some(symb) => format!("\"{}\"", symb),
+            None => String::new(),
+        };
+        out.push_str(&format!("{},{},{}\n", src, symb, dest));
+    }
+    out
+}
+
+// Formats an automaton as a Mermaid `stateDiagram-v2`. The states are named
+// `s0`, `s1`,... and labelled by their number, the final states having the
+// class `final`. The edges are coalesced as in the DOT format, an edge without
+// symbol being labelled `ε`. The characters that Mermaid would interpret are
+// written as entity codes.
+pub(crate) fn mermaid(start: usize,
+                      finals: &HashSet<usize>,
+                      states: HashSet<usize>,
+                      edges: Vec<(usize,Option<char>,usize)>) -> String {
+    let mut states : Vec<_> = states.into_iter().collect();
+    states.sort();
+    let mut out = "stateDiagram-v2\n".to_string();
+    out.push_str("    classDef final font-weight:bold,stroke-width:3px\n");
+    for &state in states.iter() {
+        out.push_str(&format!("    state \"{}\" as s{}\n", state, state));
+    }
+    out.push_str(&format!("    [*] --> s{}\n", start));
+    for (src,label,dest) in dot::coalesce(edges) {
+        let label = match label {
+            Some(label) => label
+                .chars()
+                .map(|symb| match symb {
+                    '#' | ';' | ':' | '"' | '\n' => format!("#{};", symb as u32),
+                    _ => symb.to_string(),
+                })
+                .collect(),
+            None => "ε".to_string(),
+        };
+        out.push_str(&format!("    s{} --> s{}: {}\n", src, dest, label));
+    }
+    for state in states.into_iter().filter(|state| finals.contains(state)) {
+        out.push_str(&format!("    class s{} final\n", state));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::*;
+    use nfa::core::*;
+    use e_nfa::core::*;
+
+    #[test]
+    fn test_render() {
+        let dfa = DFA::from_words(&["ab"]);
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(1)
+            .add_transition('a', 0, 0)
+            .add_transition('a', 0, 1)
+            .finalize()
+            .unwrap();
+        let e_nfa = ENFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_e_transition(0, 1)
+            .add_transition('"', 1, 2)
+            .finalize()
+            .unwrap();
+        let automata : Vec<Box<Render>> = vec![Box::new(dfa), Box::new(nfa), Box::new(e_nfa)];
+        for automaton in automata.iter() {
+            let dot = automaton.to_dot();
+            assert!(dot.starts_with("digraph {"));
+            assert!(dot.contains("start -> 0;"));
+            assert!(automaton.to_csv().starts_with("src,symbol,dest\n"));
+            assert!(automaton.to_table().starts_with("state"));
+        }
+        assert_eq!(automata[0].to_csv(), "src,symbol,dest\n0,\"a\",1\n1,\"b\",2\n");
+        assert_eq!(automata[1].to_table(), "state | a\n->0   | 0,1\n*1    | -\n");
+        assert_eq!(automata[1].to_csv(), "src,symbol,dest\n0,\"a\",0\n0,\"a\",1\n");
+        assert_eq!(automata[2].to_table(), "state | ε | \"\n->0   | 1 | -\n1     | - | 2\n*2    | - | -\n");
+        assert_eq!(automata[2].to_csv(), "src,symbol,dest\n0,,1\n1,\"\"\"\",2\n");
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let finals = [2].iter().cloned().collect();
+        let states = (0..3).collect();
+        let edges = vec![(0,Some('b'),1), (0,Some('a'),1), (0,Some('c'),1), (1,None,2), (1,Some(':'),2), (2,Some('#'),2)];
+        assert_eq!(mermaid(0, &finals, states, edges),
+                   "stateDiagram-v2\n\
+                    \x20   classDef final font-weight:bold,stroke-width:3px\n\
+                    \x20   state \"0\" as s0\n\
+                    \x20   state \"1\" as s1\n\
+                    \x20   state \"2\" as s2\n\
+                    \x20   [*] --> s0\n\
+                    \x20   s0 --> s1: a-c\n\
+                    \x20   s1 --> s2: ε\n\
+                    \x20   s1 --> s2: #58;\n\
+                    \x20   s2 --> s2: #35;\n\
+                    \x20   class s2 final\n");
+    }
+}
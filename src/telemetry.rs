@@ -0,0 +1,108 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cell::RefCell;
+
+/// The type `Progress` is an event reported by a long-running operation to the
+/// telemetry callback. The determinizations (`NFA::to_dfa`, `ENFA::to_dfa`) and
+/// the products (`DFA::synchronized_product`, `intersect_all`...) report each
+/// state they process, and `DFA::minimize` reports each refinement of its
+/// partition.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Progress {
+    /// The name of the operation, e.g. `"determinization"`.
+    pub operation : &'static str,
+    /// The number of states generated so far, or the number of classes for the
+    /// minimization.
+    pub states    : usize,
+    /// The number of states generated but not yet processed.
+    pub queue     : usize,
+}
+
+type Callback = Box<Fn(&Progress)>;
+
+thread_local! {
+    static CALLBACK : RefCell<Option<Callback>> = RefCell::new(None);
+}
+
+/// Sets the telemetry callback of the current thread, replacing the previous
+/// one. The operations run on the current thread then invoke it with their
+/// progress events.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+///
+/// use automaton::dfa::core::*;
+/// use automaton::telemetry;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// fn main() {
+///     let events = Rc::new(Cell::new(0));
+///     let counter = events.clone();
+///     telemetry::set_callback(move |_| counter.set(counter.get()+1));
+///     DFA::from_words(&["ab", "cb"]).minimize();
+///     telemetry::clear_callback();
+///     assert!(events.get() > 0);
+/// }
+/// ```
+pub fn set_callback<F: Fn(&Progress) + 'static>(callback: F) {
+    CALLBACK.with(|cell| *cell.borrow_mut() = Some(Box::new(callback)));
+}
+
+/// Removes the telemetry callback of the current thread.
+pub fn clear_callback() {
+    CALLBACK.with(|cell| *cell.borrow_mut() = None);
+}
+
+// Invokes the telemetry callback of the current thread, if any.
+pub(crate) fn report(operation: &'static str, states: usize, queue: usize) {
+    CALLBACK.with(|cell| {
+        if let Some(ref callback) = *cell.borrow() {
+            callback(&Progress{operation, states, queue});
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use nfa::core::*;
+
+    #[test]
+    fn test_telemetry() {
+        // (a|b)*a(a|b)
+        let nfa = NFABuilder::new()
+            .add_start(0)
+            .add_final(2)
+            .add_transition('a', 0, 0)
+            .add_transition('b', 0, 0)
+            .add_transition('a', 0, 1)
+            .add_transition('a', 1, 2)
+            .add_transition('b', 1, 2)
+            .finalize()
+            .unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        set_callback(move |progress| recorded.borrow_mut().push(progress.clone()));
+        let dfa = nfa.to_dfa();
+        clear_callback();
+        let events = events.borrow();
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|progress| progress.operation == "determinization"));
+        // each subset is processed once
+        assert_eq!(events.len(), dfa.stats().state_count);
+        assert_eq!(events.last().unwrap().queue, 0);
+        // nothing is reported without callback
+        nfa.to_dfa();
+        assert_eq!(events.len(), dfa.stats().state_count);
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2016 Vincent Vigneron. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at.your option.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dfa::core::DFA;
+
+/// Asserts that the dfa accepts every word of `accepted` and rejects every
+/// word of `rejected`.
+///
+/// # Panics
+///
+/// Panics if at least one word is misclassified. The message lists all the
+/// words that should have been accepted but were rejected, and vice versa.
+///
+/// # Examples
+///
+/// ```
+/// extern crate automaton;
+/// use automaton::dfa::core::*;
+/// use automaton::testing::assert_language_contains;
+///
+/// # fn main() {
+/// // (ab)*
+/// let dfa = DFABuilder::new()
+///     .add_start(0)
+///     .add_final(0)
+///     .add_transition('a', 0, 1)
+///     .add_transition('b', 1, 0)
+///     .finalize()
+///     .unwrap();
+/// assert_language_contains(&dfa, &["", "ab", "abab"], &["a", "ba", "aba"]);
+/// # }
+/// ```
+pub fn assert_language_contains(dfa: &DFA, accepted: &[&str], rejected: &[&str]) {
+    let wrongly_rejected : Vec<_> = accepted.iter().filter(|word| !dfa.test(word)).collect();
+    let wrongly_accepted : Vec<_> = rejected.iter().filter(|word| dfa.test(word)).collect();
+    if !wrongly_rejected.is_empty() || !wrongly_accepted.is_empty() {
+        panic!("language mismatch:\n  rejected but expected accepted: {:?}\n  accepted but expected rejected: {:?}",
+               wrongly_rejected,
+               wrongly_accepted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::core::{DFABuilder,DFABuilding};
+
+    fn ab_star() -> DFA {
+        DFABuilder::new()
+            .add_start(0)
+            .add_final(0)
+            .add_transition('a', 0, 1)
+            .add_transition('b', 1, 0)
+            .finalize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_assert_language_contains() {
+        assert_language_contains(&ab_star(), &["", "ab", "ababab"], &["a", "b", "abb"]);
+        assert_language_contains(&ab_star(), &[], &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "rejected but expected accepted: [\"aba\", \"b\"]\n  \
+                               accepted but expected rejected: [\"abab\"]")]
+    fn test_assert_language_contains_message() {
+        assert_language_contains(&ab_star(), &["ab", "aba", "b"], &["a", "abab"]);
+    }
+}